@@ -0,0 +1,118 @@
+//! Diffs rlox's tokens or syntax trees against another Lox implementation's, e.g. to run
+//! the jlox test suite through both.
+//!
+//! Run with `cargo run --example differential -- [--ast] <REFERENCE> <FILE>...`.
+//!
+//! `REFERENCE` is run as `REFERENCE tokens FILE` (or `REFERENCE ast FILE` with `--ast`) and
+//! has to print what `rlox --tokens --format=json FILE` (or `--ast --format=json`) does,
+//! as `rlox::canonical` documents; for jlox that's a small printer next to its scanner
+//! or `AstPrinter`. Tokens and statements rlox flags as extensions are left out of the
+//! comparison. Exits with 1 if any file diverges.
+
+use std::{env, fs, process};
+
+use rlox::{canonical, engine::RLoxEngine, scanner::Scanner, timings::Timings};
+use serde_json::Value;
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let ast = args.first().is_some_and(|arg| arg == "--ast");
+    if ast {
+        args.remove(0);
+    }
+    let Some((reference, files)) = args.split_first() else {
+        eprintln!("usage: differential [--ast] <REFERENCE> <FILE>...");
+        process::exit(2);
+    };
+
+    let mut diverged = false;
+    for file in files {
+        let ours = match rlox_output(file, ast) {
+            Ok(ours) => ours,
+            Err(err) => {
+                println!("{}: rlox rejected it: {}", file, err);
+                diverged = true;
+                continue;
+            }
+        };
+        let theirs = match reference_output(reference, file, ast) {
+            Ok(theirs) => theirs,
+            Err(err) => {
+                println!("{}: {}", file, err);
+                diverged = true;
+                continue;
+            }
+        };
+        match first_difference(&ours, &theirs) {
+            Some(difference) => {
+                println!("{}: {}", file, difference);
+                diverged = true;
+            }
+            None => println!("{}: same", file),
+        }
+    }
+    if diverged {
+        process::exit(1);
+    }
+}
+
+/// What `rlox --tokens --format=json` (or `--ast`) prints for `file`, without the entries
+/// flagged as extensions.
+fn rlox_output(file: &str, ast: bool) -> Result<Vec<Value>, String> {
+    let source = fs::read_to_string(file).map_err(|err| err.to_string())?;
+    let output = if ast {
+        let statements = RLoxEngine::parse_source(source, false, &mut Timings::default())
+            .map_err(|errors| errors[0].to_string())?;
+        canonical::program(&statements).map_err(|err| err.to_string())?
+    } else {
+        let mut scanner = Scanner::new(source);
+        scanner.scan_tokens();
+        if let Some(error) = scanner.errors.first() {
+            return Err(error.to_string());
+        }
+        canonical::tokens(&scanner.tokens)
+    };
+    let Value::Array(entries) = output else {
+        unreachable!("both dumps are arrays")
+    };
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.get("extension") != Some(&Value::Bool(true)))
+        .collect())
+}
+
+fn reference_output(reference: &str, file: &str, ast: bool) -> Result<Vec<Value>, String> {
+    let output = process::Command::new(reference)
+        .args([if ast { "ast" } else { "tokens" }, file])
+        .output()
+        .map_err(|err| format!("couldn't run {}: {}", reference, err))?;
+    if !output.status.success() {
+        return Err(format!("{} failed with {}", reference, output.status));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|err| {
+        format!(
+            "{} printed something other than a JSON array: {}",
+            reference, err
+        )
+    })
+}
+
+/// Describes the first entry where the two dumps disagree, or `None` if they don't.
+fn first_difference(ours: &[Value], theirs: &[Value]) -> Option<String> {
+    for (index, (our, their)) in ours.iter().zip(theirs).enumerate() {
+        if our != their {
+            return Some(format!(
+                "entry {} differs\n  rlox:      {}\n  reference: {}",
+                index, our, their
+            ));
+        }
+    }
+    if ours.len() != theirs.len() {
+        return Some(format!(
+            "rlox has {} entries, the reference {}",
+            ours.len(),
+            theirs.len()
+        ));
+    }
+    None
+}
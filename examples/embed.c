@@ -0,0 +1,34 @@
+/*
+ * Embeds rlox in a C program through the C ABI of the `ffi` feature.
+ *
+ *   cargo build --release --features ffi
+ *   cc examples/embed.c -Iinclude -Ltarget/release -lrlox -o embed
+ *   LD_LIBRARY_PATH=target/release ./embed
+ */
+#include <stdio.h>
+
+#include "rlox.h"
+
+static int run(RLoxHandle *engine, const char *source) {
+    int code = rlox_run(engine, source);
+    fputs(rlox_last_output(engine), stdout);
+    if (code != RLOX_OK) {
+        fprintf(stderr, "%s\n", rlox_last_error(engine));
+    }
+    return code;
+}
+
+int main(void) {
+    RLoxHandle *engine = rlox_engine_new();
+    if (engine == NULL) {
+        return 1;
+    }
+
+    /* globals persist between runs */
+    run(engine, "var greeting = \"Hello from Lox\";");
+    run(engine, "print greeting;");
+    int code = run(engine, "print -greeting;");
+
+    rlox_engine_free(engine);
+    return code == RLOX_RUNTIME_ERROR ? 0 : 1;
+}
@@ -0,0 +1,36 @@
+//! Embeds rlox in a Rust program: a Lox script acts as a configuration file, and the host
+//! reads values back out of it with `eval`.
+//!
+//! Run with `cargo run --example embed`.
+
+use rlox::{engine::RLoxEngine, tokens::Object};
+
+const CONFIG: &str = r#"
+var title = "rlox";
+var width = 80;
+var height = width / 2;
+print "config loaded";
+"#;
+
+fn main() {
+    let mut engine = RLoxEngine::new();
+
+    // globals defined by one call stay visible to the next
+    if let Err(diagnostics) = engine.run_source("config.lox", CONFIG) {
+        diagnostics
+            .iter()
+            .for_each(|diagnostic| eprintln!("{}", diagnostic));
+        return;
+    }
+
+    // the final expression may omit its `;`
+    for expression in ["title", "width * height", "height > width", "missing"] {
+        match engine.eval(expression) {
+            Ok(Object::String(string)) => println!("{} = \"{}\"", expression, string),
+            Ok(value) => println!("{} = {}", expression, value),
+            Err(diagnostics) => diagnostics
+                .iter()
+                .for_each(|diagnostic| eprintln!("{}", diagnostic)),
+        }
+    }
+}
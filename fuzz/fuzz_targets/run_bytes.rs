@@ -0,0 +1,19 @@
+//! Runs arbitrary bytes, with invalid UTF-8 replaced, through the scanner, parser
+//! and interpreter. Any panic is a bug.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rlox::{interpreter::Interpreter, parser::Parser, scanner::Scanner};
+
+fuzz_target!(|bytes: &[u8]| {
+    let source = String::from_utf8_lossy(bytes).into_owned();
+    let mut scanner = Scanner::new(source);
+    scanner.scan_tokens();
+    let Ok(statements) = Parser::new(scanner.tokens).parse() else {
+        return;
+    };
+    if scanner.errors.is_empty() {
+        let _ = Interpreter::new().interpret(statements);
+    }
+});
@@ -0,0 +1,20 @@
+//! Runs arbitrary UTF-8 source through the scanner, parser and interpreter.
+//! Any panic is a bug: bad input has to end in a reported error.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rlox::{interpreter::Interpreter, parser::Parser, scanner::Scanner};
+
+fuzz_target!(|source: &str| {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens();
+    // like the CLI, keep parsing after scan errors to exercise the parser's recovery
+    let Ok(statements) = Parser::new(scanner.tokens).parse() else {
+        return;
+    };
+    if scanner.errors.is_empty() {
+        // there are no loops or calls yet, so every program terminates
+        let _ = Interpreter::new().interpret(statements);
+    }
+});
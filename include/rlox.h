@@ -0,0 +1,62 @@
+#ifndef RLOX_H
+#define RLOX_H
+
+/* Generated by cbindgen from src/ffi.rs; don't edit by hand. */
+
+// The run succeeded.
+#define RLOX_OK 0
+
+// The source had scan or parse errors, so nothing ran (EX_DATAERR).
+#define RLOX_COMPILE_ERROR 65
+
+// The program stopped with a runtime error (EX_SOFTWARE).
+#define RLOX_RUNTIME_ERROR 70
+
+// A pointer was null or the source wasn't valid UTF-8.
+#define RLOX_INVALID_ARGUMENT -1
+
+// rlox panicked; this is a bug, and the engine should be freed.
+#define RLOX_PANIC -2
+
+// An engine together with the strings last handed out about it.
+typedef struct RLoxHandle RLoxHandle;
+
+// Creates an engine whose globals persist across runs and whose output is captured.
+//
+// Returns null if creating it panicked. Free it with `rlox_engine_free`.
+struct RLoxHandle *rlox_engine_new(void);
+
+// Frees an engine and every string it handed out. Null is ignored.
+//
+// # Safety
+//
+// `engine` must be null or come from `rlox_engine_new`, and not have been freed already.
+void rlox_engine_free(struct RLoxHandle *engine);
+
+// Runs the NUL-terminated UTF-8 `source` on `engine`.
+//
+// Returns `RLOX_OK`, `RLOX_COMPILE_ERROR` or `RLOX_RUNTIME_ERROR` like the exit code of the
+// command line, or a negative code if the call itself failed.
+//
+// # Safety
+//
+// `engine` must come from `rlox_engine_new`, and `source` must be null or point to a
+// NUL-terminated string.
+int rlox_run(struct RLoxHandle *engine, const char *source);
+
+// What the last `rlox_run` printed; empty if it printed nothing. Null if `engine` is.
+//
+// # Safety
+//
+// `engine` must be null or come from `rlox_engine_new`.
+const char *rlox_last_output(const struct RLoxHandle *engine);
+
+// The errors of the last `rlox_run`, one per line; empty if it succeeded. Null if
+// `engine` is.
+//
+// # Safety
+//
+// `engine` must be null or come from `rlox_engine_new`.
+const char *rlox_last_error(const struct RLoxHandle *engine);
+
+#endif  /* RLOX_H */
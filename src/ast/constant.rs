@@ -0,0 +1,66 @@
+use crate::{
+    errors::RLoxError,
+    interpreter::Interpreter,
+    tokens::{Object, Token},
+};
+
+use super::{expr::Expr, visitor::ExprVisitor};
+
+/// Evaluates `expr` if its value is known without running anything, e.g. `(1 + 2) * 3`,
+/// by the same rules as the interpreter.
+///
+/// Returns `None` for an expression that reads a variable, unless a short-circuit skips
+/// it, and for one that would fail with a runtime error, like `1 / 0`.
+pub fn eval_const(expr: &Expr) -> Option<Object> {
+    expr.accept(&mut ConstEvaluator {}).ok()
+}
+
+/// Evaluates constant expressions, failing on anything that isn't one.
+struct ConstEvaluator {}
+
+impl ExprVisitor<Object> for ConstEvaluator {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Object, RLoxError> {
+        let left = left.accept(self)?;
+        let right = right.accept(self)?;
+        // strings aren't limited in size here; only a running program is
+        Interpreter::apply_binary(left, operator, right, None)
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Object, RLoxError> {
+        let left = left.accept(self)?;
+        if Interpreter::short_circuits(operator, &left) {
+            return Ok(left);
+        }
+        right.accept(self)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<Object, RLoxError> {
+        Ok(value.clone())
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<Object, RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Object, RLoxError> {
+        let right = right.accept(self)?;
+        Interpreter::apply_unary(operator, right)
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<Object, RLoxError> {
+        Err(RLoxError::InterpreterError(
+            name.clone(),
+            format!("'{}' is a variable, not a constant.", name.lexeme),
+        ))
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// Renders the syntax tree as a Graphviz digraph, for `dot -Tsvg` and friends.
+///
+/// Every node is labeled with its kind and its operator, name or value; edges are
+/// labeled with the field they stand for. The statements hang off a `program` root,
+/// in order.
+pub struct DotPrinter {
+    next_id: usize,
+    lines: Vec<String>,
+}
+
+impl DotPrinter {
+    pub fn print_program(stmts: &[Stmt]) -> Result<String, RLoxError> {
+        let mut printer = Self {
+            next_id: 0,
+            lines: vec![],
+        };
+        let root = printer.node("program");
+        for (index, stmt) in stmts.iter().enumerate() {
+            let child = stmt.accept(&mut printer)?;
+            printer.edge(root, child, &index.to_string());
+        }
+
+        let mut graph = String::from("digraph ast {\n    ordering=out;\n");
+        for line in printer.lines {
+            graph.push_str(&format!("    {}\n", line));
+        }
+        graph.push('}');
+        Ok(graph)
+    }
+
+    /// Adds a node with the given label and returns its id.
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.lines
+            .push(format!("n{} [label=\"{}\"];", id, Self::escape(label)));
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        self.lines
+            .push(format!("n{} -> n{} [label=\"{}\"];", from, to, label));
+    }
+
+    /// Escapes text for use inside a quoted DOT string.
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+}
+
+impl ExprVisitor<usize> for DotPrinter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node(&format!("Binary {}", operator.lexeme));
+        let left_id = left.accept(self)?;
+        self.edge(id, left_id, "left");
+        let right_id = right.accept(self)?;
+        self.edge(id, right_id, "right");
+        Ok(id)
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node(&format!("Logical {}", operator.lexeme));
+        let left_id = left.accept(self)?;
+        self.edge(id, left_id, "left");
+        let right_id = right.accept(self)?;
+        self.edge(id, right_id, "right");
+        Ok(id)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<usize, RLoxError> {
+        let label = match value {
+            Object::String(string) => format!("Literal {:?}", string),
+            _ => format!("Literal {}", value),
+        };
+        Ok(self.node(&label))
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<usize, RLoxError> {
+        let id = self.node("Grouping");
+        let expression_id = expression.accept(self)?;
+        self.edge(id, expression_id, "expression");
+        Ok(id)
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<usize, RLoxError> {
+        let id = self.node(&format!("Unary {}", operator.lexeme));
+        let right_id = right.accept(self)?;
+        self.edge(id, right_id, "right");
+        Ok(id)
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<usize, RLoxError> {
+        Ok(self.node(&format!("Variable {}", name.lexeme)))
+    }
+}
+
+impl StmtVisitor<usize> for DotPrinter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<usize, RLoxError> {
+        let id = self.node("Expression");
+        let expression_id = expression.accept(self)?;
+        self.edge(id, expression_id, "expression");
+        Ok(id)
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<usize, RLoxError> {
+        let id = self.node("Print");
+        let expression_id = expression.accept(self)?;
+        self.edge(id, expression_id, "expression");
+        Ok(id)
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node("Debug");
+        let expression_id = expression.accept(self)?;
+        self.edge(id, expression_id, "expression");
+        Ok(id)
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node(&format!("Var {}", name.lexeme));
+        let initializer_id = initializer.accept(self)?;
+        self.edge(id, initializer_id, "initializer");
+        Ok(id)
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node("Assert");
+        let condition_id = condition.accept(self)?;
+        self.edge(id, condition_id, "condition");
+        if let Some(message) = message {
+            let message_id = message.accept(self)?;
+            self.edge(id, message_id, "message");
+        }
+        Ok(id)
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node("If");
+        let condition_id = condition.accept(self)?;
+        self.edge(id, condition_id, "condition");
+        let then_id = then_branch.accept(self)?;
+        self.edge(id, then_id, "then");
+        if let Some(else_branch) = else_branch {
+            let else_id = else_branch.accept(self)?;
+            self.edge(id, else_id, "else");
+        }
+        Ok(id)
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<usize, RLoxError> {
+        let id = self.node("While");
+        let condition_id = condition.accept(self)?;
+        self.edge(id, condition_id, "condition");
+        let body_id = body.accept(self)?;
+        self.edge(id, body_id, "body");
+        Ok(id)
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token},
+};
+
+use super::{expr::Expr, format::Formatter, visitor::ExprVisitor};
+
+/// Identifies a node of a tree that stays in place while it's evaluated.
+///
+/// Nodes don't know where in the source they came from, so an explained evaluation
+/// records which node each value belongs to by identity instead.
+pub fn node_id(expr: &Expr) -> usize {
+    expr as *const Expr as usize
+}
+
+/// Renders an evaluation one reduction at a time: `(1 + 2) * 3`, `3 * 3`, `9`.
+///
+/// Every node that has been evaluated is shown as its value, written as a literal, and a
+/// grouping around a value loses its parentheses. Steps that don't change the text, like
+/// evaluating a literal, aren't shown.
+pub struct StepPrinter {
+    reduced: HashMap<usize, Object>,
+}
+
+impl StepPrinter {
+    /// The expression as written, followed by the text after each step, ending with its
+    /// value. `steps` are the `(node, value)` pairs in the order the nodes were evaluated.
+    pub fn transcript(expr: &Expr, steps: &[(usize, Object)]) -> Result<Vec<String>, RLoxError> {
+        let mut printer = Self {
+            reduced: HashMap::new(),
+        };
+        let mut lines = vec![printer.print(expr)?];
+        for (node, value) in steps {
+            printer.reduced.insert(*node, value.clone());
+            let line = printer.print(expr)?;
+            if lines.last() != Some(&line) {
+                lines.push(line);
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The value `expr` was reduced to, if it has been evaluated.
+    fn reduced(&self, expr: &Expr) -> Option<Result<String, RLoxError>> {
+        let value = self.reduced.get(&node_id(expr))?;
+        Some(Self::literal(value))
+    }
+
+    /// `value` written the way it would be in the source.
+    fn literal(value: &Object) -> Result<String, RLoxError> {
+        match value {
+            Object::Nil => Ok("nil".to_string()),
+            value => Formatter::format_expr(&Expr::Literal {
+                value: value.clone(),
+                span: None,
+            }),
+        }
+    }
+
+    fn print(&mut self, expr: &Expr) -> Result<String, RLoxError> {
+        match self.reduced(expr) {
+            Some(value) => value,
+            None => expr.accept(self),
+        }
+    }
+}
+
+impl ExprVisitor<String> for StepPrinter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        Ok(format!(
+            "{} {} {}",
+            self.print(left)?,
+            operator.lexeme,
+            self.print(right)?
+        ))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        self.visit_binary_expr(left, operator, right)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<String, RLoxError> {
+        Self::literal(value)
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        match self.reduced(expression) {
+            Some(value) => value,
+            None => Ok(format!("({})", expression.accept(self)?)),
+        }
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<String, RLoxError> {
+        let right = self.print(right)?;
+        // `-(-3)` rather than `--3` once a grouping has been reduced to a negative number
+        if right.starts_with('-') {
+            return Ok(format!("{}({})", operator.lexeme, right));
+        }
+        Ok(format!("{}{}", operator.lexeme, right))
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<String, RLoxError> {
+        Ok(name.lexeme.clone())
+    }
+}
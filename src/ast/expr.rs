@@ -1,3 +1,7 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
 use crate::{
     errors::RLoxError,
     tokens::{Object, Token},
@@ -5,36 +9,113 @@ use crate::{
 
 use super::visitor::ExprVisitor;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
 pub enum Expr {
     Binary {
         left: Box<Expr>,
         operator: Token,
         right: Box<Expr>,
     },
+    /// `and` and `or`, which unlike binary operators may skip evaluating `right`.
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
     Literal {
         value: Object,
+        /// Where the literal is in the source; `None` if it wasn't written there, like the
+        /// nil of `var a;`, or the tree was loaded rather than parsed.
+        #[serde(skip)]
+        span: Option<Range<usize>>,
     },
     Grouping {
         expression: Box<Expr>,
+        /// Where the grouping is in the source, parentheses included; see `Literal`.
+        #[serde(skip)]
+        span: Option<Range<usize>>,
     },
     Unary {
         operator: Token,
         right: Box<Expr>,
     },
     Variable {
-        name: Token
+        name: Token,
+    },
+}
+
+impl Expr {
+    pub fn accept<R>(&self, visitor: &mut dyn ExprVisitor<R>) -> Result<R, RLoxError> {
+        match self {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => visitor.visit_binary_expr(left, operator, right),
+            Expr::Logical {
+                left,
+                operator,
+                right,
+            } => visitor.visit_logical_expr(left, operator, right),
+            Expr::Literal { value, .. } => visitor.visit_literal_expr(value),
+            Expr::Grouping { expression, .. } => visitor.visit_grouping_expr(expression),
+            Expr::Unary { operator, right } => visitor.visit_unary_expr(operator, right),
+            Expr::Variable { name } => visitor.visit_variable_expr(name),
+        }
     }
 }
 
 impl Expr {
-    pub fn accept<R>(&self, visitor: &dyn ExprVisitor<R>) -> Result<R, RLoxError> {
+    /// The byte range of the source the expression was parsed from, or `None` if part of it
+    /// wasn't, see [`Expr::Literal`].
+    pub fn span(&self) -> Option<Range<usize>> {
         match self {
-            Expr::Binary { .. } => visitor.visit_binary_expr(self),
-            Expr::Literal { .. } => visitor.visit_literal_expr(self),
-            Expr::Grouping { .. } => visitor.visit_grouping_expr(self),
-            Expr::Unary { .. } => visitor.visit_unary_expr(self),
-            Expr::Variable { .. } => visitor.visit_variable_expr(self),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                Some(left.span()?.start..right.span()?.end)
+            }
+            Expr::Literal { span, .. } | Expr::Grouping { span, .. } => span.clone(),
+            Expr::Unary { operator, right } => Some(operator.offset..right.span()?.end),
+            Expr::Variable { name } => Some(name.span()),
+        }
+    }
+
+    /// Moves the subexpressions out into `pending`, leaving leaves in their place.
+    fn take_children(&mut self, pending: &mut Vec<Expr>) {
+        let leaf = || Expr::Literal {
+            value: Object::Nil,
+            span: None,
+        };
+        match self {
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                pending.push(std::mem::replace(left, leaf()));
+                pending.push(std::mem::replace(right, leaf()));
+            }
+            Expr::Grouping {
+                expression: child, ..
+            }
+            | Expr::Unary { right: child, .. } => pending.push(std::mem::replace(child, leaf())),
+            Expr::Literal { .. } | Expr::Variable { .. } => {}
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        matches!(self, Expr::Literal { .. } | Expr::Variable { .. })
+    }
+}
+
+/// Frees a tree without recursing, so that one too deep for the visitors, e.g. built by a
+/// generator rather than parsed, can't overflow the stack as it's dropped.
+impl Drop for Expr {
+    fn drop(&mut self) {
+        if self.is_leaf() {
+            return;
+        }
+        let mut pending = vec![];
+        self.take_children(&mut pending);
+        while let Some(mut expr) = pending.pop() {
+            // dropped here with nothing left below it
+            expr.take_children(&mut pending);
         }
     }
 }
@@ -0,0 +1,235 @@
+use crate::{
+    errors::RLoxError,
+    keywords::Edition,
+    parser::Parser,
+    scanner::Scanner,
+    tokens::{Object, Token, TokenType},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// Prints a program back as canonical Lox source: one statement per line, single spaces
+/// around binary operators, `var a;` for declarations without a value.
+///
+/// Comments are kept. A comment on the same line as a statement, or inside it, trails
+/// the statement; any other comment stays on its own line, in order. Runs of blank lines
+/// are collapsed into one.
+pub struct Formatter {}
+
+impl Formatter {
+    /// Formats a single expression the way it appears in a formatted program.
+    pub fn format_expr(expr: &Expr) -> Result<String, RLoxError> {
+        expr.accept(&mut Self {})
+    }
+
+    /// Formats `source`, written for `edition`, or returns its scan and parse errors if it
+    /// isn't valid Lox there.
+    pub fn format_source(source: &str, edition: Edition) -> Result<String, Vec<RLoxError>> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.edition(edition);
+        scanner.preserve_comments(true);
+        scanner.scan_tokens();
+        let comments: Vec<Token> = scanner
+            .tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Comment)
+            .cloned()
+            .collect();
+        let statements = Parser::new(scanner.tokens).parse_with_spans();
+        if !scanner.errors.is_empty() {
+            return Err(scanner.errors);
+        }
+        let statements = statements?;
+        let next_starts: Vec<usize> = statements
+            .iter()
+            .skip(1)
+            .map(|(_, span)| span.start)
+            .chain([source.len()])
+            .collect();
+
+        let mut formatted = String::new();
+        let mut comments = comments.into_iter().peekable();
+        // end of the last thing written, to look at the gap before the next one
+        let mut cursor = None;
+        for ((stmt, span), next_start) in statements.into_iter().zip(next_starts) {
+            while let Some(comment) = comments.next_if(|comment| comment.offset < span.start) {
+                Self::new_line(source, &mut formatted, cursor, comment.offset);
+                formatted.push_str(&comment.lexeme);
+                cursor = Some(comment.offset + comment.lexeme.len());
+            }
+
+            Self::new_line(source, &mut formatted, cursor, span.start);
+            formatted.push_str(&stmt.accept(&mut Self {}).map_err(|err| vec![err])?);
+            cursor = Some(span.end);
+
+            let mut end = span.end;
+            while let Some(comment) = comments.next_if(|comment| {
+                comment.offset < span.end
+                    || (comment.offset < next_start && !source[end..comment.offset].contains('\n'))
+            }) {
+                formatted.push(' ');
+                formatted.push_str(&comment.lexeme);
+                end = end.max(comment.offset + comment.lexeme.len());
+                cursor = Some(end);
+            }
+        }
+        for comment in comments {
+            Self::new_line(source, &mut formatted, cursor, comment.offset);
+            formatted.push_str(&comment.lexeme);
+            cursor = Some(comment.offset + comment.lexeme.len());
+        }
+
+        if !formatted.is_empty() {
+            formatted.push('\n');
+        }
+        Ok(formatted)
+    }
+
+    /// Starts a new line for whatever begins at `next`, keeping one blank line if the
+    /// source had any since `cursor`.
+    fn new_line(source: &str, formatted: &mut String, cursor: Option<usize>, next: usize) {
+        let Some(cursor) = cursor else {
+            return;
+        };
+        formatted.push('\n');
+        if source[cursor..next].matches('\n').count() > 1 {
+            formatted.push('\n');
+        }
+    }
+}
+
+impl ExprVisitor<String> for Formatter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        Ok(format!(
+            "{} {} {}",
+            left.accept(self)?,
+            operator.lexeme,
+            right.accept(self)?
+        ))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        self.visit_binary_expr(left, operator, right)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<String, RLoxError> {
+        // Lox strings have no escapes, so the contents go back verbatim
+        match value {
+            Object::String(string) => Ok(format!("\"{}\"", string)),
+            Object::Number(number) => Ok(number.to_literal()),
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        Ok(format!("({})", expression.accept(self)?))
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<String, RLoxError> {
+        Ok(format!("{}{}", operator.lexeme, right.accept(self)?))
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<String, RLoxError> {
+        Ok(name.lexeme.clone())
+    }
+}
+
+impl StmtVisitor<String> for Formatter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        Ok(format!("{};", expression.accept(self)?))
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        Ok(format!("print {};", expression.accept(self)?))
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        Ok(format!("debug {};", expression.accept(self)?))
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<String, RLoxError> {
+        let declared = match annotation {
+            Some(annotation) => format!("{}: {}", name.lexeme, annotation.lexeme),
+            None => name.lexeme.clone(),
+        };
+        // the parser fills in `nil` for `var a;`
+        if let Expr::Literal {
+            value: Object::Nil, ..
+        } = initializer
+        {
+            return Ok(format!("var {};", declared));
+        }
+        Ok(format!("var {} = {};", declared, initializer.accept(self)?))
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        match message {
+            Some(message) => Ok(format!(
+                "assert {}, {};",
+                condition.accept(self)?,
+                message.accept(self)?
+            )),
+            None => Ok(format!("assert {};", condition.accept(self)?)),
+        }
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<String, RLoxError> {
+        let mut formatted = format!(
+            "if ({}) {}",
+            condition.accept(self)?,
+            then_branch.accept(self)?
+        );
+        if let Some(else_branch) = else_branch {
+            formatted.push_str(&format!(" else {}", else_branch.accept(self)?));
+        }
+        Ok(formatted)
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<String, RLoxError> {
+        Ok(format!(
+            "while ({}) {}",
+            condition.accept(self)?,
+            body.accept(self)?
+        ))
+    }
+}
@@ -0,0 +1,221 @@
+use std::fmt;
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::errors::RLoxError;
+
+use super::{expr::Expr, stmt::Stmt};
+
+/// Reads a program in the form printed by `--ast-format=json`.
+///
+/// Errors carry the line in the JSON document; a malformed node is reported with its
+/// path from the root, e.g. `[2].expression.operator.token_type`. The tree still has
+/// to be checked with [`AstValidator`](super::validate::AstValidator) before running it.
+pub fn from_json(source: &str) -> Result<Vec<Stmt>, RLoxError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(source);
+    let stmts: Vec<Stmt> = serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        let line = err.inner().line() as u64;
+        // the line is reported separately, drop serde_json's " at line L column C"
+        let message = err.inner().to_string();
+        let message = message
+            .rsplit_once(" at line ")
+            .map_or(message.as_str(), |(message, _)| message);
+        RLoxError::ParseError(
+            line,
+            format!("Invalid syntax tree at `{}`: {}.", err.path(), message),
+        )
+    })?;
+    Ok(stmts)
+}
+
+// Expr and Stmt are deserialized by hand: serde's derive for internally tagged enums
+// buffers each node before looking at it, which loses the path to a malformed child.
+// Reading the tag first lets the fields stream, at the cost of requiring `type` to
+// come first, as the serializer writes it.
+
+#[derive(Deserialize)]
+enum ExprType {
+    Binary,
+    Logical,
+    Literal,
+    Grouping,
+    Unary,
+    Variable,
+}
+
+#[derive(Deserialize)]
+enum StmtType {
+    Expression,
+    Print,
+    Debug,
+    Var,
+    Assert,
+    If,
+    While,
+}
+
+const EXPR_FIELDS: &[&str] = &["left", "operator", "right", "value", "expression", "name"];
+const STMT_FIELDS: &[&str] = &[
+    "expression",
+    "name",
+    "initializer",
+    "keyword",
+    "text",
+    "condition",
+    "message",
+    "annotation",
+    "then_branch",
+    "else_branch",
+    "body",
+];
+
+/// Reads the `type` tag, which has to be the first field of a node.
+fn read_tag<'de, A: MapAccess<'de>, T: Deserialize<'de>>(map: &mut A) -> Result<T, A::Error> {
+    match map.next_key::<String>()? {
+        Some(key) if key == "type" => map.next_value(),
+        _ => Err(de::Error::custom("expected `type` as the first field")),
+    }
+}
+
+fn required<T, E: de::Error>(field: Option<T>, name: &'static str) -> Result<T, E> {
+    field.ok_or_else(|| E::missing_field(name))
+}
+
+struct ExprVisitor;
+
+impl<'de> Visitor<'de> for ExprVisitor {
+    type Value = Expr;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an expression node")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Expr, A::Error> {
+        let node_type: ExprType = read_tag(&mut map)?;
+        let (mut left, mut operator, mut right) = (None, None, None);
+        let (mut value, mut expression, mut name) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "left" => left = Some(map.next_value()?),
+                "operator" => operator = Some(map.next_value()?),
+                "right" => right = Some(map.next_value()?),
+                "value" => value = Some(map.next_value()?),
+                "expression" => expression = Some(map.next_value()?),
+                "name" => name = Some(map.next_value()?),
+                _ => return Err(de::Error::unknown_field(&key, EXPR_FIELDS)),
+            }
+        }
+
+        Ok(match node_type {
+            ExprType::Binary => Expr::Binary {
+                left: required(left, "left")?,
+                operator: required(operator, "operator")?,
+                right: required(right, "right")?,
+            },
+            ExprType::Logical => Expr::Logical {
+                left: required(left, "left")?,
+                operator: required(operator, "operator")?,
+                right: required(right, "right")?,
+            },
+            ExprType::Literal => Expr::Literal {
+                value: required(value, "value")?,
+                span: None,
+            },
+            ExprType::Grouping => Expr::Grouping {
+                expression: required(expression, "expression")?,
+                span: None,
+            },
+            ExprType::Unary => Expr::Unary {
+                operator: required(operator, "operator")?,
+                right: required(right, "right")?,
+            },
+            ExprType::Variable => Expr::Variable {
+                name: required(name, "name")?,
+            },
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Expr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ExprVisitor)
+    }
+}
+
+struct StmtVisitor;
+
+impl<'de> Visitor<'de> for StmtVisitor {
+    type Value = Stmt;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a statement node")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Stmt, A::Error> {
+        let node_type: StmtType = read_tag(&mut map)?;
+        let (mut expression, mut name, mut initializer) = (None, None, None);
+        let (mut keyword, mut text) = (None, None);
+        let (mut condition, mut message, mut annotation) = (None, None, None);
+        let (mut then_branch, mut else_branch, mut body) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "expression" => expression = Some(map.next_value()?),
+                "name" => name = Some(map.next_value()?),
+                "initializer" => initializer = Some(map.next_value()?),
+                "keyword" => keyword = Some(map.next_value()?),
+                "text" => text = Some(map.next_value()?),
+                "condition" => condition = Some(map.next_value()?),
+                "message" => message = map.next_value()?,
+                "annotation" => annotation = map.next_value()?,
+                "then_branch" => then_branch = Some(map.next_value()?),
+                "else_branch" => else_branch = map.next_value()?,
+                "body" => body = Some(map.next_value()?),
+                _ => return Err(de::Error::unknown_field(&key, STMT_FIELDS)),
+            }
+        }
+
+        Ok(match node_type {
+            StmtType::Expression => Stmt::Expression {
+                expression: required(expression, "expression")?,
+            },
+            StmtType::Print => Stmt::Print {
+                expression: required(expression, "expression")?,
+            },
+            StmtType::Debug => Stmt::Debug {
+                keyword: required(keyword, "keyword")?,
+                expression: required(expression, "expression")?,
+                text: required(text, "text")?,
+            },
+            StmtType::Var => Stmt::Var {
+                name: required(name, "name")?,
+                annotation,
+                initializer: required(initializer, "initializer")?,
+            },
+            StmtType::Assert => Stmt::Assert {
+                keyword: required(keyword, "keyword")?,
+                condition: required(condition, "condition")?,
+                message,
+                text: required(text, "text")?,
+            },
+            StmtType::If => Stmt::If {
+                condition: required(condition, "condition")?,
+                then_branch: required(then_branch, "then_branch")?,
+                else_branch,
+            },
+            StmtType::While => Stmt::While {
+                keyword: required(keyword, "keyword")?,
+                condition: required(condition, "condition")?,
+                body: required(body, "body")?,
+            },
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Stmt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(StmtVisitor)
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt;
+
+use clap::ValueEnum;
+
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token, TokenType},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// A check the linter can run; each can be turned off with `--allow <rule>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LintRule {
+    /// `==` or `!=` with `true` or `false` on one side
+    RedundantBoolComparison,
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .to_possible_value()
+            .expect("every rule has a name on the command line");
+        write!(f, "{}", name.get_name())
+    }
+}
+
+/// Something a rule found suspicious. Unlike an error, it doesn't stop anything.
+pub struct Warning {
+    pub rule: LintRule,
+    pub line: u64,
+    pub message: String,
+}
+
+impl Warning {
+    /// Prints the warning prefixed with the name of the source it came from.
+    pub fn print_in(&self, source: &str) {
+        println!(
+            "[{}, Line {}] Warning: {} ({})",
+            source, self.line, self.message, self.rule
+        );
+    }
+}
+
+/// Runs the enabled rules over a program and collects their warnings.
+pub struct Linter {
+    allowed: Vec<LintRule>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter {
+    pub fn lint_program(stmts: &[Stmt], allowed: &[LintRule]) -> Result<Vec<Warning>, RLoxError> {
+        let mut linter = Self {
+            allowed: allowed.to_vec(),
+            warnings: vec![],
+        };
+        for stmt in stmts {
+            stmt.accept(&mut linter)?;
+        }
+        Ok(linter.warnings)
+    }
+
+    fn warn(&mut self, rule: LintRule, token: &Token, message: String) {
+        if !self.allowed.contains(&rule) {
+            self.warnings.push(Warning {
+                rule,
+                line: token.line,
+                message,
+            });
+        }
+    }
+}
+
+impl ExprVisitor<()> for Linter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<(), RLoxError> {
+        if matches!(
+            operator.token_type,
+            TokenType::EqualEqual | TokenType::BangEqual
+        ) {
+            for side in [left, right] {
+                if let Expr::Literal {
+                    value: Object::Boolean(boolean),
+                    ..
+                } = side
+                {
+                    self.warn(
+                        LintRule::RedundantBoolComparison,
+                        operator,
+                        format!("Comparison with '{}' is redundant.", boolean),
+                    );
+                }
+            }
+        }
+        left.accept(self)?;
+        right.accept(self)
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        _operator: &Token,
+        right: &Expr,
+    ) -> Result<(), RLoxError> {
+        left.accept(self)?;
+        right.accept(self)
+    }
+
+    fn visit_literal_expr(&mut self, _value: &Object) -> Result<(), RLoxError> {
+        Ok(())
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_unary_expr(&mut self, _operator: &Token, right: &Expr) -> Result<(), RLoxError> {
+        right.accept(self)
+    }
+
+    fn visit_variable_expr(&mut self, _name: &Token) -> Result<(), RLoxError> {
+        Ok(())
+    }
+}
+
+impl StmtVisitor<()> for Linter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        _name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<(), RLoxError> {
+        initializer.accept(self)
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        message.map_or(Ok(()), |message| message.accept(self))
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        then_branch.accept(self)?;
+        else_branch.map_or(Ok(()), |else_branch| else_branch.accept(self))
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        body.accept(self)
+    }
+}
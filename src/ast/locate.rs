@@ -0,0 +1,85 @@
+use super::{expr::Expr, stmt::Stmt};
+
+/// Finds the innermost expression of `program` whose own text covers the byte `offset`:
+/// the literal or variable there, or the expression whose operator or parentheses are
+/// there.
+///
+/// Ranges are half-open, so at an offset where one token ends and the next begins, the
+/// later one is found. Whitespace and comments, even between the operands of an
+/// expression, are covered by none, and neither is anything of a tree without spans, like
+/// one loaded from JSON.
+pub fn expr_at(program: &[Stmt], offset: usize) -> Option<&Expr> {
+    let mut expr = program
+        .iter()
+        .flat_map(expressions)
+        .find(|expr| covers(expr, offset))?;
+    loop {
+        match children(expr)
+            .into_iter()
+            .find(|child| covers(child, offset))
+        {
+            Some(child) => expr = child,
+            None if owns(expr, offset) => return Some(expr),
+            None => return None,
+        }
+    }
+}
+
+/// The expressions a statement is made of, including those of its branches or body, in
+/// source order.
+fn expressions(stmt: &Stmt) -> Vec<&Expr> {
+    match stmt {
+        Stmt::Expression { expression }
+        | Stmt::Print { expression }
+        | Stmt::Debug { expression, .. } => vec![expression],
+        Stmt::Var { initializer, .. } => vec![initializer],
+        Stmt::Assert {
+            condition, message, ..
+        } => [Some(condition), message.as_ref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => std::iter::once(condition)
+            .chain(expressions(then_branch))
+            .chain(else_branch.iter().flat_map(|branch| expressions(branch)))
+            .collect(),
+        Stmt::While {
+            condition, body, ..
+        } => std::iter::once(condition)
+            .chain(expressions(body))
+            .collect(),
+    }
+}
+
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+            vec![left, right]
+        }
+        Expr::Grouping { expression, .. } => vec![expression],
+        Expr::Unary { right, .. } => vec![right],
+        Expr::Literal { .. } | Expr::Variable { .. } => vec![],
+    }
+}
+
+fn covers(expr: &Expr, offset: usize) -> bool {
+    expr.span().is_some_and(|span| span.contains(&offset))
+}
+
+/// Whether `offset` is on a token of `expr` itself rather than of a subexpression.
+fn owns(expr: &Expr, offset: usize) -> bool {
+    match expr {
+        Expr::Binary { operator, .. }
+        | Expr::Logical { operator, .. }
+        | Expr::Unary { operator, .. } => operator.span().contains(&offset),
+        // only the parentheses, which are one byte each
+        Expr::Grouping { span, .. } => span
+            .as_ref()
+            .is_some_and(|span| offset == span.start || offset == span.end - 1),
+        Expr::Literal { .. } | Expr::Variable { .. } => covers(expr, offset),
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// The size and shape of a parsed program, e.g. for an editor or to refuse pathological
+/// inputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AstMetrics {
+    /// Statements and expressions alike.
+    pub node_count: usize,
+    /// How deeply the deepest expression nests, counted the way the parser counts against
+    /// [`MAX_NESTING`](crate::parser::MAX_NESTING), which a parsed program never exceeds: a
+    /// lone literal is 1, and every operator or grouping around it adds one.
+    pub max_depth: usize,
+    pub statement_count: usize,
+}
+
+impl AstMetrics {
+    /// Adds the metrics of another program, as if the two were one.
+    pub fn add(&mut self, other: AstMetrics) {
+        self.node_count += other.node_count;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.statement_count += other.statement_count;
+    }
+}
+
+impl fmt::Display for AstMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nodes: {}, statements: {}, max depth: {}",
+            self.node_count, self.statement_count, self.max_depth
+        )
+    }
+}
+
+/// Measures a program in one walk over it.
+pub fn metrics(stmts: &[Stmt]) -> AstMetrics {
+    let mut counter = MetricsCounter::default();
+    for stmt in stmts {
+        stmt.accept(&mut counter)
+            .expect("measuring a program never fails");
+    }
+    counter.metrics
+}
+
+/// How deeply `expression` nests, as [`AstMetrics::max_depth`] counts it.
+pub fn expression_depth(expression: &Expr) -> usize {
+    expression
+        .accept(&mut MetricsCounter::default())
+        .expect("measuring a program never fails")
+}
+
+/// Counts nodes as it goes; visiting an expression returns how deeply it nests.
+#[derive(Default)]
+struct MetricsCounter {
+    metrics: AstMetrics,
+}
+
+impl MetricsCounter {
+    /// Measures an expression that isn't nested in another one.
+    fn top_level(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        let depth = expression.accept(self)?;
+        self.metrics.max_depth = self.metrics.max_depth.max(depth);
+        Ok(())
+    }
+
+    fn statement(&mut self) {
+        self.metrics.node_count += 1;
+        self.metrics.statement_count += 1;
+    }
+}
+
+impl ExprVisitor<usize> for MetricsCounter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        _operator: &Token,
+        right: &Expr,
+    ) -> Result<usize, RLoxError> {
+        self.metrics.node_count += 1;
+        let left = left.accept(self)?;
+        let right = right.accept(self)?;
+        Ok(1 + left.max(right))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<usize, RLoxError> {
+        self.visit_binary_expr(left, operator, right)
+    }
+
+    fn visit_literal_expr(&mut self, _value: &Object) -> Result<usize, RLoxError> {
+        self.metrics.node_count += 1;
+        Ok(1)
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<usize, RLoxError> {
+        self.metrics.node_count += 1;
+        Ok(1 + expression.accept(self)?)
+    }
+
+    fn visit_unary_expr(&mut self, _operator: &Token, right: &Expr) -> Result<usize, RLoxError> {
+        self.metrics.node_count += 1;
+        Ok(1 + right.accept(self)?)
+    }
+
+    fn visit_variable_expr(&mut self, _name: &Token) -> Result<usize, RLoxError> {
+        self.metrics.node_count += 1;
+        Ok(1)
+    }
+}
+
+impl StmtVisitor<()> for MetricsCounter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(expression)
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(expression)
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(expression)
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        _name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(initializer)
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(condition)?;
+        if let Some(message) = message {
+            self.top_level(message)?;
+        }
+        Ok(())
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(condition)?;
+        then_branch.accept(self)?;
+        else_branch.map_or(Ok(()), |else_branch| else_branch.accept(self))
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<(), RLoxError> {
+        self.statement();
+        self.top_level(condition)?;
+        body.accept(self)
+    }
+}
@@ -1,4 +1,18 @@
+pub mod constant;
+pub mod dot;
+pub mod explain;
 pub mod expr;
+pub mod format;
+pub mod json;
+pub mod lint;
+pub mod locate;
+pub mod metrics;
 pub mod print;
+pub mod rpn;
 pub mod stmt;
+pub mod typecheck;
+pub mod validate;
 pub mod visitor;
+
+pub use constant::eval_const;
+pub use metrics::metrics;
@@ -1,63 +1,184 @@
-// use crate::errors::RLoxError;
-
-// use super::{expr::Expr, visitor::ExprVisitor};
-
-// pub struct AstPrinter {}
-
-// impl AstPrinter {
-//     fn parenthesize(lexeme: &str, exprs: Vec<&str>) -> String {
-//         let mut response = format!("({}", lexeme);
-//         exprs.into_iter().for_each(|expression| {
-//             response.push_str(&format!(" {}", expression));
-//         });
-//         response.push(')');
-//         response
-//     }
-// }
-
-// impl ExprVisitor<String> for AstPrinter {
-//     fn visit_binary_expr(&self, expr: &Expr) -> Result<String, RLoxError> {
-//         let Expr::Binary {
-//             left,
-//             operator,
-//             right,
-//         } = expr
-//         else {
-//             panic!("PANIC! `visit_binary_expr` was called with a non Expr::Binary value!")
-//         };
-//         let left_string = left.accept(&Self {})?;
-//         let right_string = right.accept(&Self {})?;
-//         Ok(Self::parenthesize(
-//             &operator.lexeme,
-//             vec![&left_string, &right_string],
-//         ))
-//     }
-
-//     fn visit_literal_expr(&self, expr: &Expr) -> Result<String, RLoxError> {
-//         let Expr::Literal { value } = expr else {
-//             panic!("PANIC! `visit_literal_expr` was called with a non Expr::Literal value!")
-//         };
-
-//         Ok(format!("{:#?}", value))
-//     }
-
-//     fn visit_grouping_expr(&self, expr: &Expr) -> Result<String, RLoxError> {
-//         let Expr::Grouping { expression } = expr else {
-//             panic!("PANIC! `visit_grouping_expr` was called with a non Expr::Grouping value!")
-//         };
-//         let expression_string = expression.accept(&Self {})?;
-//         Ok(Self::parenthesize("group", vec![&expression_string]))
-//     }
-
-//     fn visit_unary_expr(&self, expr: &Expr) -> Result<String, RLoxError> {
-//         let Expr::Unary { operator, right } = expr else {
-//             panic!("PANIC! `visit_unary_expr` was called with a non Expr::Unary value!")
-//         };
-//         let right_string = right.accept(&Self {})?;
-//         Ok(Self::parenthesize(&operator.lexeme, vec![&right_string]))
-//     }
-    
-//     fn visit_variable_expr(&self, expr: &Expr) -> Result<String, RLoxError> {
-//         todo!()
-//     }
-// }
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// Renders the syntax tree in a parenthesized, Lisp-like form, e.g. `(+ 1 (* 2 3))`.
+///
+/// Statements are rendered as `(expr expression)`, `(print expression)`,
+/// `(var name initializer)`, `(if condition then else)` and `(while condition body)`. String literals are quoted and escaped, so every
+/// rendering reads back unambiguously.
+pub struct AstPrinter {}
+
+impl AstPrinter {
+    pub fn print_expr(expr: &Expr) -> Result<String, RLoxError> {
+        expr.accept(&mut Self {})
+    }
+
+    pub fn print_stmt(stmt: &Stmt) -> Result<String, RLoxError> {
+        stmt.accept(&mut Self {})
+    }
+
+    /// Renders every statement of a program, one per line.
+    pub fn print_program(stmts: &[Stmt]) -> Result<String, RLoxError> {
+        let lines = stmts
+            .iter()
+            .map(Self::print_stmt)
+            .collect::<Result<Vec<String>, RLoxError>>()?;
+        Ok(lines.join("\n"))
+    }
+
+    fn parenthesize(lexeme: &str, exprs: Vec<&str>) -> String {
+        let mut response = format!("({}", lexeme);
+        exprs.into_iter().for_each(|expression| {
+            response.push_str(&format!(" {}", expression));
+        });
+        response.push(')');
+        response
+    }
+}
+
+impl ExprVisitor<String> for AstPrinter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        let left_string = left.accept(self)?;
+        let right_string = right.accept(self)?;
+        Ok(Self::parenthesize(
+            &operator.lexeme,
+            vec![&left_string, &right_string],
+        ))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        self.visit_binary_expr(left, operator, right)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<String, RLoxError> {
+        // strings are quoted so they can't be mistaken for identifiers
+        match value {
+            Object::String(string) => Ok(format!("{:?}", string)),
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        let expression_string = expression.accept(self)?;
+        Ok(Self::parenthesize("group", vec![&expression_string]))
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<String, RLoxError> {
+        let right_string = right.accept(self)?;
+        Ok(Self::parenthesize(&operator.lexeme, vec![&right_string]))
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<String, RLoxError> {
+        Ok(name.lexeme.clone())
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        let expression_string = expression.accept(self)?;
+        Ok(Self::parenthesize("expr", vec![&expression_string]))
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        let expression_string = expression.accept(self)?;
+        Ok(Self::parenthesize("print", vec![&expression_string]))
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        let expression_string = expression.accept(self)?;
+        Ok(Self::parenthesize("debug", vec![&expression_string]))
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<String, RLoxError> {
+        let initializer_string = initializer.accept(self)?;
+        Ok(Self::parenthesize(
+            "var",
+            vec![&name.lexeme, &initializer_string],
+        ))
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        let condition_string = condition.accept(self)?;
+        match message {
+            Some(message) => {
+                let message_string = message.accept(self)?;
+                Ok(Self::parenthesize(
+                    "assert",
+                    vec![&condition_string, &message_string],
+                ))
+            }
+            None => Ok(Self::parenthesize("assert", vec![&condition_string])),
+        }
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<String, RLoxError> {
+        let condition_string = condition.accept(self)?;
+        let then_string = then_branch.accept(self)?;
+        match else_branch {
+            Some(else_branch) => {
+                let else_string = else_branch.accept(self)?;
+                Ok(Self::parenthesize(
+                    "if",
+                    vec![&condition_string, &then_string, &else_string],
+                ))
+            }
+            None => Ok(Self::parenthesize(
+                "if",
+                vec![&condition_string, &then_string],
+            )),
+        }
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<String, RLoxError> {
+        let condition_string = condition.accept(self)?;
+        let body_string = body.accept(self)?;
+        Ok(Self::parenthesize(
+            "while",
+            vec![&condition_string, &body_string],
+        ))
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token, TokenType},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// Renders the syntax tree in reverse Polish notation, e.g. `(1 + 2) * (4 - 3)` as
+/// `1 2 + 4 3 - *`.
+///
+/// Groupings vanish since the order already encodes them, and unary minus is written
+/// `~` to tell it apart from subtraction. Statements put their keyword last:
+/// `print a;` is `a print` and `var a = 1;` is `a 1 var`.
+pub struct RpnPrinter {}
+
+impl RpnPrinter {
+    pub fn print_stmt(stmt: &Stmt) -> Result<String, RLoxError> {
+        stmt.accept(&mut Self {})
+    }
+
+    /// Renders every statement of a program, one per line.
+    pub fn print_program(stmts: &[Stmt]) -> Result<String, RLoxError> {
+        let lines = stmts
+            .iter()
+            .map(Self::print_stmt)
+            .collect::<Result<Vec<String>, RLoxError>>()?;
+        Ok(lines.join("\n"))
+    }
+}
+
+impl ExprVisitor<String> for RpnPrinter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        Ok(format!(
+            "{} {} {}",
+            left.accept(self)?,
+            right.accept(self)?,
+            operator.lexeme
+        ))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<String, RLoxError> {
+        self.visit_binary_expr(left, operator, right)
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<String, RLoxError> {
+        match value {
+            Object::String(string) => Ok(format!("{:?}", string)),
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<String, RLoxError> {
+        let symbol = match operator.token_type {
+            TokenType::Minus => "~",
+            _ => &operator.lexeme,
+        };
+        Ok(format!("{} {}", right.accept(self)?, symbol))
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<String, RLoxError> {
+        Ok(name.lexeme.clone())
+    }
+}
+
+impl StmtVisitor<String> for RpnPrinter {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<String, RLoxError> {
+        Ok(format!("{} print", expression.accept(self)?))
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        Ok(format!("{} debug", expression.accept(self)?))
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<String, RLoxError> {
+        Ok(format!("{} {} var", name.lexeme, initializer.accept(self)?))
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<String, RLoxError> {
+        match message {
+            Some(message) => Ok(format!(
+                "{} {} assert",
+                condition.accept(self)?,
+                message.accept(self)?
+            )),
+            None => Ok(format!("{} assert", condition.accept(self)?)),
+        }
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<String, RLoxError> {
+        let condition = condition.accept(self)?;
+        let then_branch = then_branch.accept(self)?;
+        match else_branch {
+            Some(else_branch) => Ok(format!(
+                "{} {} {} if-else",
+                condition,
+                then_branch,
+                else_branch.accept(self)?
+            )),
+            None => Ok(format!("{} {} if", condition, then_branch)),
+        }
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<String, RLoxError> {
+        Ok(format!(
+            "{} {} while",
+            condition.accept(self)?,
+            body.accept(self)?
+        ))
+    }
+}
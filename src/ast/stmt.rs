@@ -1,20 +1,87 @@
+use serde::Serialize;
+
 use crate::{errors::RLoxError, tokens::Token};
 
 use super::{expr::Expr, visitor::StmtVisitor};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
 pub enum Stmt {
-    Expression { expression: Expr },
-    Print { expression: Expr },
-    Var { name: Token, initializer: Expr },
+    Expression {
+        expression: Expr,
+    },
+    Print {
+        expression: Expr,
+    },
+    /// `debug expr;`, which prints `text = value`; `text` is the source of `expression`.
+    Debug {
+        keyword: Token,
+        expression: Expr,
+        text: String,
+    },
+    /// `var name: annotation = initializer;`, where the type annotation is optional and
+    /// only read by the type checker.
+    Var {
+        name: Token,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        annotation: Option<Token>,
+        initializer: Expr,
+    },
+    /// `assert condition, message;` with an optional message; `text` is the source of
+    /// `condition`.
+    Assert {
+        keyword: Token,
+        condition: Expr,
+        message: Option<Expr>,
+        text: String,
+    },
+    /// `if (condition) then_branch else else_branch`, where the `else` is optional and
+    /// belongs to the nearest `if`. The branches are statements, never declarations.
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        else_branch: Option<Box<Stmt>>,
+    },
+    /// `while (condition) body`; `keyword` is where a runaway loop is reported.
+    While {
+        keyword: Token,
+        condition: Expr,
+        body: Box<Stmt>,
+    },
 }
 
 impl Stmt {
     pub fn accept<R>(&self, visitor: &mut dyn StmtVisitor<R>) -> Result<R, RLoxError> {
         match self {
-            Stmt::Expression { .. } => visitor.visit_expr_stmt(self),
-            Stmt::Print { .. } => visitor.visit_print_stmt(self),
-            Stmt::Var { .. } => visitor.visit_var_stmt(self),
+            Stmt::Expression { expression } => visitor.visit_expr_stmt(expression),
+            Stmt::Print { expression } => visitor.visit_print_stmt(expression),
+            Stmt::Debug {
+                keyword,
+                expression,
+                text,
+            } => visitor.visit_debug_stmt(keyword, expression, text),
+            Stmt::Var {
+                name,
+                annotation,
+                initializer,
+            } => visitor.visit_var_stmt(name, annotation.as_ref(), initializer),
+            Stmt::Assert {
+                keyword,
+                condition,
+                message,
+                text,
+            } => visitor.visit_assert_stmt(keyword, condition, message.as_ref(), text),
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => visitor.visit_if_stmt(condition, then_branch, else_branch.as_deref()),
+            Stmt::While {
+                keyword,
+                condition,
+                body,
+            } => visitor.visit_while_stmt(keyword, condition, body),
         }
     }
 }
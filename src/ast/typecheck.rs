@@ -0,0 +1,338 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token, TokenType},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// What the checker knows about the type of a value.
+///
+/// `Any` is the top of the lattice: the type isn't known, e.g. of a global defined by the
+/// host, and nothing involving it is ever flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Nil,
+    Boolean,
+    Number,
+    String,
+    Any,
+}
+
+impl Type {
+    fn of(value: &Object) -> Type {
+        match value {
+            Object::Nil => Type::Nil,
+            Object::Boolean(_) => Type::Boolean,
+            Object::Number(_) => Type::Number,
+            Object::String(_) => Type::String,
+        }
+    }
+
+    /// The type a `var` annotation names, or `None` if the name isn't a type.
+    fn named(name: &str) -> Option<Type> {
+        match name {
+            "nil" => Some(Type::Nil),
+            "boolean" => Some(Type::Boolean),
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "any" => Some(Type::Any),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a value of this type is certainly not a number.
+    fn is_not_number(self) -> bool {
+        !matches!(self, Type::Number | Type::Any)
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Nil => "nil",
+            Type::Boolean => "a boolean",
+            Type::Number => "a number",
+            Type::String => "a string",
+            Type::Any => "any value",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An operation the checker can tell will fail when it runs.
+pub struct TypeIssue {
+    pub line: u64,
+    pub message: String,
+}
+
+impl TypeIssue {
+    /// Prints the issue as a warning prefixed with the name of the source it came from.
+    pub fn print_in(&self, source: &str) {
+        println!("[{}, Line {}] Warning: {}", source, self.line, self.message);
+    }
+
+    /// The issue as an error, for `--strict-types`.
+    pub fn into_error(self) -> RLoxError {
+        RLoxError::ParseError(self.line, self.message)
+    }
+}
+
+/// Infers types from literals and flags operators applied to values of the wrong type,
+/// before anything runs.
+///
+/// Deliberately conservative: a variable has the type it was last declared as, or else
+/// of the value it was last declared with, propagated through the program in order, and
+/// whatever isn't known is [`Type::Any`] and never flagged. In strict mode an `assert` whose condition is a
+/// literal that isn't a boolean is flagged as well.
+pub struct TypeChecker {
+    strict: bool,
+    variables: HashMap<String, Type>,
+    issues: Vec<TypeIssue>,
+}
+
+impl TypeChecker {
+    pub fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            variables: HashMap::new(),
+            issues: vec![],
+        }
+    }
+
+    /// Checks a program, carrying over what earlier programs declared, and returns the
+    /// issues found in it.
+    pub fn check_program(&mut self, stmts: &[Stmt]) -> Result<Vec<TypeIssue>, RLoxError> {
+        for stmt in stmts {
+            stmt.accept(self)?;
+        }
+        Ok(std::mem::take(&mut self.issues))
+    }
+
+    fn flag(&mut self, token: &Token, message: String) {
+        self.issues.push(TypeIssue {
+            line: token.line,
+            message,
+        });
+    }
+}
+
+impl ExprVisitor<Type> for TypeChecker {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Type, RLoxError> {
+        let left = left.accept(self)?;
+        let right = right.accept(self)?;
+        let op = &operator.lexeme;
+        match operator.token_type {
+            TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => {
+                if let Some(wrong) = [left, right].into_iter().find(|side| side.is_not_number()) {
+                    self.flag(
+                        operator,
+                        format!("Operands of '{}' must be numbers, not {}.", op, wrong),
+                    );
+                }
+                if matches!(
+                    operator.token_type,
+                    TokenType::Minus | TokenType::Star | TokenType::Slash
+                ) {
+                    Ok(Type::Number)
+                } else {
+                    Ok(Type::Boolean)
+                }
+            }
+            TokenType::Plus => {
+                let addable = |side: Type| matches!(side, Type::Number | Type::String | Type::Any);
+                let known = left != Type::Any && right != Type::Any;
+                if !addable(left) || !addable(right) || (known && left != right) {
+                    self.flag(
+                        operator,
+                        format!(
+                            "Operands of '+' must be two numbers or two strings, not {} and {}.",
+                            left, right
+                        ),
+                    );
+                    return Ok(Type::Any);
+                }
+                Ok(if known { left } else { Type::Any })
+            }
+            TokenType::EqualEqual | TokenType::BangEqual => {
+                // nil on the left compares with anything; everything else needs its own type
+                let known = left != Type::Any && right != Type::Any;
+                if known && left != Type::Nil && left != right {
+                    self.flag(
+                        operator,
+                        format!("Can't compare {} with {} using '{}'.", left, right, op),
+                    );
+                }
+                Ok(Type::Boolean)
+            }
+            _ => Ok(Type::Any),
+        }
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        _operator: &Token,
+        right: &Expr,
+    ) -> Result<Type, RLoxError> {
+        // the result is one of the operands, so its type is only known if both agree
+        let left = left.accept(self)?;
+        let right = right.accept(self)?;
+        Ok(if left == right { left } else { Type::Any })
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<Type, RLoxError> {
+        Ok(Type::of(value))
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<Type, RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Type, RLoxError> {
+        let right = right.accept(self)?;
+        match operator.token_type {
+            // `!` works on anything
+            TokenType::Bang => Ok(Type::Boolean),
+            TokenType::Minus => {
+                if right.is_not_number() {
+                    self.flag(
+                        operator,
+                        format!("Operand of '-' must be a number, not {}.", right),
+                    );
+                }
+                Ok(Type::Number)
+            }
+            _ => Ok(Type::Any),
+        }
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<Type, RLoxError> {
+        Ok(self
+            .variables
+            .get(&name.lexeme)
+            .copied()
+            .unwrap_or(Type::Any))
+    }
+}
+
+impl StmtVisitor<()> for TypeChecker {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)?;
+        Ok(())
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)?;
+        Ok(())
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        expression.accept(self)?;
+        Ok(())
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<(), RLoxError> {
+        let initialized = initializer.accept(self)?;
+        let Some(annotation) = annotation else {
+            self.variables.insert(name.lexeme.clone(), initialized);
+            return Ok(());
+        };
+        let Some(declared) = Type::named(&annotation.lexeme) else {
+            self.flag(annotation, format!("Unknown type '{}'.", annotation.lexeme));
+            self.variables.insert(name.lexeme.clone(), Type::Any);
+            return Ok(());
+        };
+        // `var a: number;` is initialized with nil, which isn't a mismatch
+        let uninitialized = matches!(
+            initializer,
+            Expr::Literal {
+                value: Object::Nil,
+                ..
+            }
+        );
+        let known = declared != Type::Any && initialized != Type::Any;
+        if known && initialized != declared && !uninitialized {
+            self.flag(
+                name,
+                format!(
+                    "'{}' is declared as {} but initialized with {}.",
+                    name.lexeme, declared, initialized
+                ),
+            );
+        }
+        self.variables.insert(name.lexeme.clone(), declared);
+        Ok(())
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        let condition_type = condition.accept(self)?;
+        if self.strict {
+            if let Expr::Literal { .. } = condition {
+                if condition_type != Type::Boolean {
+                    self.flag(
+                        keyword,
+                        format!("The condition is {}, not a boolean.", condition_type),
+                    );
+                }
+            }
+        }
+        if let Some(message) = message {
+            message.accept(self)?;
+        }
+        Ok(())
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        then_branch.accept(self)?;
+        else_branch.map_or(Ok(()), |else_branch| else_branch.accept(self))
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        body.accept(self)
+    }
+}
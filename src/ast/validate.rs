@@ -0,0 +1,187 @@
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token, TokenType},
+};
+
+use super::{
+    expr::Expr,
+    stmt::Stmt,
+    visitor::{ExprVisitor, StmtVisitor},
+};
+
+/// Checks that a syntax tree which didn't come from the parser is one the parser could
+/// have produced, so a malformed tree is rejected up front instead of panicking in the
+/// interpreter.
+pub struct AstValidator {}
+
+impl AstValidator {
+    pub fn validate_program(stmts: &[Stmt]) -> Result<(), RLoxError> {
+        stmts.iter().try_for_each(|stmt| stmt.accept(&mut Self {}))
+    }
+
+    fn expect(token: &Token, allowed: &[TokenType], role: &str) -> Result<(), RLoxError> {
+        if allowed.contains(&token.token_type) {
+            return Ok(());
+        }
+        Err(RLoxError::ParseError(
+            token.line,
+            format!(
+                "Invalid syntax tree: `{:?}` is not {}.",
+                token.token_type, role
+            ),
+        ))
+    }
+
+    /// Rejects a declaration as the body of `owner`, which the parser doesn't allow.
+    fn check_body(body: &Stmt, owner: &str) -> Result<(), RLoxError> {
+        match body {
+            Stmt::Var { name, .. } => Err(RLoxError::ParseError(
+                name.line,
+                format!(
+                    "Invalid syntax tree: a declaration can't be the body of {}.",
+                    owner
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl ExprVisitor<()> for AstValidator {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<(), RLoxError> {
+        Self::expect(
+            operator,
+            &[
+                TokenType::Minus,
+                TokenType::Plus,
+                TokenType::Slash,
+                TokenType::Star,
+                TokenType::BangEqual,
+                TokenType::EqualEqual,
+                TokenType::Greater,
+                TokenType::GreaterEqual,
+                TokenType::Less,
+                TokenType::LessEqual,
+            ],
+            "a binary operator",
+        )?;
+        left.accept(self)?;
+        right.accept(self)
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<(), RLoxError> {
+        Self::expect(
+            operator,
+            &[TokenType::And, TokenType::Or],
+            "a logical operator",
+        )?;
+        left.accept(self)?;
+        right.accept(self)
+    }
+
+    fn visit_literal_expr(&mut self, _value: &Object) -> Result<(), RLoxError> {
+        Ok(())
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<(), RLoxError> {
+        Self::expect(
+            operator,
+            &[TokenType::Bang, TokenType::Minus],
+            "a unary operator",
+        )?;
+        right.accept(self)
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<(), RLoxError> {
+        Self::expect(name, &[TokenType::Identifier], "an identifier")
+    }
+}
+
+impl StmtVisitor<()> for AstValidator {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        expression.accept(self)
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        Self::expect(keyword, &[TokenType::Debug], "'debug'")?;
+        expression.accept(self)
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<(), RLoxError> {
+        Self::expect(name, &[TokenType::Identifier], "an identifier")?;
+        if let Some(annotation) = annotation {
+            Self::expect(
+                annotation,
+                &[TokenType::Identifier, TokenType::Nil],
+                "a type name",
+            )?;
+        }
+        initializer.accept(self)
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<(), RLoxError> {
+        Self::expect(keyword, &[TokenType::Assert], "'assert'")?;
+        condition.accept(self)?;
+        message.map_or(Ok(()), |message| message.accept(self))
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), RLoxError> {
+        condition.accept(self)?;
+        for branch in [Some(then_branch), else_branch].into_iter().flatten() {
+            Self::check_body(branch, "an 'if'")?;
+            branch.accept(self)?;
+        }
+        Ok(())
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<(), RLoxError> {
+        Self::expect(keyword, &[TokenType::While], "'while'")?;
+        condition.accept(self)?;
+        Self::check_body(body, "a 'while'")?;
+        body.accept(self)
+    }
+}
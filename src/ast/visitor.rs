@@ -1,17 +1,68 @@
-use crate::errors::RLoxError;
+use crate::{
+    errors::RLoxError,
+    tokens::{Object, Token},
+};
 
 use super::{expr::Expr, stmt::Stmt};
 
+/// Visits expressions.
+///
+/// `Expr::accept` is the only place that matches on the node: each method receives
+/// the fields of its variant, so adding a variant fails to compile until every
+/// visitor handles it. Methods take `&mut self` so visitors can keep state, like the
+/// interpreter's environment or the node ids of [`DotPrinter`](super::dot::DotPrinter).
 pub trait ExprVisitor<R> {
-    fn visit_binary_expr(&self, expr: &Expr) -> Result<R, RLoxError>;
-    fn visit_literal_expr(&self, expr: &Expr) -> Result<R, RLoxError>;
-    fn visit_grouping_expr(&self, expr: &Expr) -> Result<R, RLoxError>;
-    fn visit_unary_expr(&self, expr: &Expr) -> Result<R, RLoxError>;
-    fn visit_variable_expr(&self, expr: &Expr) -> Result<R, RLoxError>;
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<R, RLoxError>;
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<R, RLoxError>;
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<R, RLoxError>;
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<R, RLoxError>;
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<R, RLoxError>;
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<R, RLoxError>;
 }
 
+/// Visits statements, receiving the fields of each variant like [`ExprVisitor`].
 pub trait StmtVisitor<R> {
-    fn visit_expr_stmt(&self, stmt: &Stmt) -> Result<R, RLoxError>;
-    fn visit_print_stmt(&self, stmt: &Stmt) -> Result<R, RLoxError>;
-    fn visit_var_stmt(&mut self, stmt: &Stmt) -> Result<R, RLoxError>;
-}
\ No newline at end of file
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<R, RLoxError>;
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<R, RLoxError>;
+    fn visit_debug_stmt(
+        &mut self,
+        keyword: &Token,
+        expression: &Expr,
+        text: &str,
+    ) -> Result<R, RLoxError>;
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<R, RLoxError>;
+    fn visit_assert_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        text: &str,
+    ) -> Result<R, RLoxError>;
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<R, RLoxError>;
+    fn visit_while_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<R, RLoxError>;
+}
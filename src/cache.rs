@@ -0,0 +1,56 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ast::{json, stmt::Stmt, validate::AstValidator},
+    keywords::Edition,
+};
+
+/// Bumped whenever the syntax tree's JSON form changes, which invalidates every cache.
+const FORMAT_VERSION: u32 = 3;
+
+/// Where the cached syntax tree of `path` lives: next to it, with a `.loxc` extension.
+pub fn cache_path(path: &str) -> PathBuf {
+    Path::new(path).with_extension("loxc")
+}
+
+/// Writes the `statements` parsed from `source` in `edition` to the cache file of `path`.
+///
+/// The file is a header line with the format version, the edition and a hash of the
+/// source, followed by the statements in the form printed by `--ast-format=json`.
+pub fn write(path: &str, source: &str, edition: Edition, statements: &[Stmt]) -> io::Result<()> {
+    let tree = serde_json::to_string(statements)
+        .expect("syntax trees only hold JSON-representable values");
+    fs::write(
+        cache_path(path),
+        format!("{}\n{}", header(source, edition), tree),
+    )
+}
+
+/// Returns the cached statements of `path` if there is a cache written by this format
+/// version for exactly this `source`, parsed in `edition`.
+///
+/// A missing, stale or corrupt cache is never an error, just a miss.
+pub fn load(path: &str, source: &str, edition: Edition) -> Option<Vec<Stmt>> {
+    let cache = fs::read_to_string(cache_path(path)).ok()?;
+    let (found, tree) = cache.split_once('\n')?;
+    if found != header(source, edition) {
+        return None;
+    }
+    let statements = json::from_json(tree).ok()?;
+    AstValidator::validate_program(&statements).ok()?;
+    Some(statements)
+}
+
+fn header(source: &str, edition: Edition) -> String {
+    format!("rloxc {} {} {:016x}", FORMAT_VERSION, edition, hash(source))
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it is the same across builds.
+fn hash(source: &str) -> u64 {
+    source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
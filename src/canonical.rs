@@ -0,0 +1,317 @@
+//! Tokens and syntax trees in the vocabulary of *Crafting Interpreters*, so rlox's output
+//! can be diffed against jlox, clox or any other Lox written from the book.
+//!
+//! Tokens map to objects whose `type` is the book's `TokenType` name:
+//!
+//! ```json
+//! {"type": "NUMBER", "lexeme": "1", "literal": 1.0, "line": 1}
+//! ```
+//!
+//! `literal` is set only for `STRING` and `NUMBER`, as in jlox's scanner, and numbers are
+//! always floats there, like the book's `Double`. Syntax tree nodes map to objects whose
+//! `kind` is the name of the book's visitor method without `visit` and `Expr`/`Stmt`:
+//!
+//! | kind         | fields                                  |
+//! |--------------|-----------------------------------------|
+//! | `Binary`     | `left`, `operator`, `right`             |
+//! | `Grouping`   | `expression`                            |
+//! | `Literal`    | `value`                                 |
+//! | `Logical`    | `left`, `operator`, `right`             |
+//! | `Unary`      | `operator`, `right`                     |
+//! | `Variable`   | `name`                                  |
+//! | `Expression` | `expression`                            |
+//! | `If`         | `condition`, `thenBranch`, `elseBranch` |
+//! | `Print`      | `expression`                            |
+//! | `Var`        | `name`, `initializer`                   |
+//! | `While`      | `condition`, `body`                     |
+//!
+//! An `operator` is the token type's name, e.g. `"PLUS"`, a `name` is the identifier's
+//! lexeme, and a missing `elseBranch` is `null`. What rlox has beyond the book is kept but
+//! carries `"extension": true`, so a harness can leave it out of the comparison: the `:`
+//! token and the `assert` and `debug` keywords, the `Assert` (`condition`, `message`) and
+//! `Debug` (`expression`) statements, and a `Var` with a type `annotation`.
+//!
+//! Keys are written in alphabetical order, so equal inputs give identical text.
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    ast::{
+        expr::Expr,
+        stmt::Stmt,
+        visitor::{ExprVisitor, StmtVisitor},
+    },
+    errors::RLoxError,
+    tokens::{Object, Token, TokenType},
+};
+
+/// The token stream as a JSON array, one object per token.
+pub fn tokens(tokens: &[Token]) -> Value {
+    tokens.iter().map(token).collect()
+}
+
+/// A program as a JSON array, one object per statement.
+pub fn program(stmts: &[Stmt]) -> Result<Value, RLoxError> {
+    stmts
+        .iter()
+        .map(|stmt| stmt.accept(&mut CanonicalTree))
+        .collect()
+}
+
+fn token(token: &Token) -> Value {
+    let literal = match (&token.token_type, &token.literal) {
+        (TokenType::Number | TokenType::String, Some(literal)) => literal_value(literal),
+        _ => Value::Null,
+    };
+    let mut object = json!({
+        "type": type_name(token.token_type),
+        "lexeme": token.lexeme,
+        "literal": literal,
+        "line": token.line,
+    });
+    if is_extension(token.token_type) {
+        object["extension"] = Value::Bool(true);
+    }
+    object
+}
+
+/// The book's name for a token type, e.g. `BANG_EQUAL`.
+pub fn type_name(token_type: TokenType) -> &'static str {
+    match token_type {
+        TokenType::LeftParen => "LEFT_PAREN",
+        TokenType::RightParen => "RIGHT_PAREN",
+        TokenType::LeftBrace => "LEFT_BRACE",
+        TokenType::RightBrace => "RIGHT_BRACE",
+        TokenType::Comma => "COMMA",
+        TokenType::Dot => "DOT",
+        TokenType::Colon => "COLON",
+        TokenType::Minus => "MINUS",
+        TokenType::Plus => "PLUS",
+        TokenType::Semicolon => "SEMICOLON",
+        TokenType::Slash => "SLASH",
+        TokenType::Star => "STAR",
+        TokenType::Bang => "BANG",
+        TokenType::BangEqual => "BANG_EQUAL",
+        TokenType::Equal => "EQUAL",
+        TokenType::EqualEqual => "EQUAL_EQUAL",
+        TokenType::Greater => "GREATER",
+        TokenType::GreaterEqual => "GREATER_EQUAL",
+        TokenType::Less => "LESS",
+        TokenType::LessEqual => "LESS_EQUAL",
+        TokenType::Identifier => "IDENTIFIER",
+        TokenType::String => "STRING",
+        TokenType::Number => "NUMBER",
+        TokenType::And => "AND",
+        TokenType::Assert => "ASSERT",
+        TokenType::Class => "CLASS",
+        TokenType::Debug => "DEBUG",
+        TokenType::Else => "ELSE",
+        TokenType::False => "FALSE",
+        TokenType::Fun => "FUN",
+        TokenType::For => "FOR",
+        TokenType::If => "IF",
+        TokenType::Nil => "NIL",
+        TokenType::Or => "OR",
+        TokenType::Print => "PRINT",
+        TokenType::Return => "RETURN",
+        TokenType::Super => "SUPER",
+        TokenType::This => "THIS",
+        TokenType::True => "TRUE",
+        TokenType::Var => "VAR",
+        TokenType::While => "WHILE",
+        TokenType::Comment => "COMMENT",
+        TokenType::Eof => "EOF",
+    }
+}
+
+/// Returns true for the token types the book's Lox doesn't have.
+fn is_extension(token_type: TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Colon | TokenType::Assert | TokenType::Debug | TokenType::Comment
+    )
+}
+
+fn literal_value(value: &Object) -> Value {
+    match value {
+        Object::Nil => Value::Null,
+        Object::Boolean(boolean) => Value::Bool(*boolean),
+        Object::Number(number) => json!(number.as_f64()),
+        Object::String(string) => Value::String(string.clone()),
+    }
+}
+
+/// A node of the given kind with `fields`.
+fn node(kind: &str, fields: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+    let mut object = Map::new();
+    object.insert("kind".to_string(), Value::String(kind.to_string()));
+    for (field, value) in fields {
+        object.insert(field.to_string(), value);
+    }
+    Value::Object(object)
+}
+
+fn extension(mut node: Value) -> Value {
+    node["extension"] = Value::Bool(true);
+    node
+}
+
+struct CanonicalTree;
+
+impl ExprVisitor<Value> for CanonicalTree {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Value, RLoxError> {
+        Ok(node(
+            "Binary",
+            [
+                ("left", left.accept(self)?),
+                ("operator", json!(type_name(operator.token_type))),
+                ("right", right.accept(self)?),
+            ],
+        ))
+    }
+
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Value, RLoxError> {
+        Ok(node(
+            "Logical",
+            [
+                ("left", left.accept(self)?),
+                ("operator", json!(type_name(operator.token_type))),
+                ("right", right.accept(self)?),
+            ],
+        ))
+    }
+
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<Value, RLoxError> {
+        Ok(node("Literal", [("value", literal_value(value))]))
+    }
+
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<Value, RLoxError> {
+        Ok(node("Grouping", [("expression", expression.accept(self)?)]))
+    }
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Value, RLoxError> {
+        Ok(node(
+            "Unary",
+            [
+                ("operator", json!(type_name(operator.token_type))),
+                ("right", right.accept(self)?),
+            ],
+        ))
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<Value, RLoxError> {
+        Ok(node("Variable", [("name", json!(name.lexeme))]))
+    }
+}
+
+impl StmtVisitor<Value> for CanonicalTree {
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<Value, RLoxError> {
+        Ok(node(
+            "Expression",
+            [("expression", expression.accept(self)?)],
+        ))
+    }
+
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<Value, RLoxError> {
+        Ok(node("Print", [("expression", expression.accept(self)?)]))
+    }
+
+    fn visit_debug_stmt(
+        &mut self,
+        _keyword: &Token,
+        expression: &Expr,
+        _text: &str,
+    ) -> Result<Value, RLoxError> {
+        Ok(extension(node(
+            "Debug",
+            [("expression", expression.accept(self)?)],
+        )))
+    }
+
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<Value, RLoxError> {
+        let var = node(
+            "Var",
+            [
+                ("name", json!(name.lexeme)),
+                ("initializer", initializer.accept(self)?),
+            ],
+        );
+        Ok(match annotation {
+            Some(annotation) => {
+                let mut var = extension(var);
+                var["annotation"] = json!(annotation.lexeme);
+                var
+            }
+            None => var,
+        })
+    }
+
+    fn visit_assert_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        _text: &str,
+    ) -> Result<Value, RLoxError> {
+        let message = match message {
+            Some(message) => message.accept(self)?,
+            None => Value::Null,
+        };
+        Ok(extension(node(
+            "Assert",
+            [("condition", condition.accept(self)?), ("message", message)],
+        )))
+    }
+
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<Value, RLoxError> {
+        let condition = condition.accept(self)?;
+        let then_branch = then_branch.accept(self)?;
+        let else_branch = match else_branch {
+            Some(else_branch) => else_branch.accept(self)?,
+            None => Value::Null,
+        };
+        Ok(node(
+            "If",
+            [
+                ("condition", condition),
+                ("thenBranch", then_branch),
+                ("elseBranch", else_branch),
+            ],
+        ))
+    }
+
+    fn visit_while_stmt(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<Value, RLoxError> {
+        Ok(node(
+            "While",
+            [
+                ("condition", condition.accept(self)?),
+                ("body", body.accept(self)?),
+            ],
+        ))
+    }
+}
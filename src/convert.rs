@@ -0,0 +1,63 @@
+use std::{error::Error, fmt};
+
+use serde_json::Value;
+
+use crate::{number::Number, tokens::Object};
+
+/// Why a JSON value has no Lox counterpart.
+#[derive(Debug)]
+pub struct ConversionError {
+    pub message: String,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ConversionError {}
+
+impl Object {
+    /// Converts the value to JSON: nil is `null`, and booleans, numbers and strings map to
+    /// their JSON counterparts. Integers stay JSON integers.
+    ///
+    /// JSON has no infinities or NaN, so like `serde_json` those numbers become `null`.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Object::Nil => Value::Null,
+            Object::Boolean(boolean) => Value::Bool(*boolean),
+            Object::Number(Number::Int(int)) => Value::from(*int),
+            Object::Number(Number::Float(float)) => {
+                serde_json::Number::from_f64(*float).map_or(Value::Null, Value::Number)
+            }
+            Object::String(string) => Value::String(string.clone()),
+        }
+    }
+
+    /// Converts a JSON value to the Lox value [`to_json`](Self::to_json) would turn into it.
+    ///
+    /// Arrays and objects are refused, since Lox has no lists or maps yet.
+    pub fn from_json(value: &Value) -> Result<Object, ConversionError> {
+        match value {
+            Value::Null => Ok(Object::Nil),
+            Value::Bool(boolean) => Ok(Object::Boolean(*boolean)),
+            Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+                (Some(int), _) => Ok(Object::Number(Number::Int(int))),
+                (None, Some(float)) if float.is_finite() => {
+                    Ok(Object::Number(Number::Float(float)))
+                }
+                _ => Err(ConversionError {
+                    message: format!("The number {} is out of range for Lox.", number),
+                }),
+            },
+            Value::String(string) => Ok(Object::String(string.clone())),
+            Value::Array(_) => Err(ConversionError {
+                message: "Lox has no lists, so JSON arrays can't be converted.".to_string(),
+            }),
+            Value::Object(_) => Err(ConversionError {
+                message: "Lox has no maps, so JSON objects can't be converted.".to_string(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{
+    ast::{self, explain::StepPrinter, expr::Expr, metrics::AstMetrics, stmt::Stmt},
+    cache,
+    convert::ConversionError,
+    errors::{Diagnostic, RLoxError},
+    execution::{Execution, ExecutionHandle},
+    interpreter::{Event, Interpreter},
+    keywords::Edition,
+    options::{InterpreterOptions, OptionsError},
+    parser::Parser,
+    scanner::Scanner,
+    snapshot::{self, SnapshotError},
+    stats::Stats,
+    symbol::Interner,
+    timings::{Instant, Phase, Timings},
+    tokens::Object,
+};
+
+/// Name the errors of [`RLoxEngine::eval`] are reported under.
+pub const EVAL_SOURCE: &str = "<eval>";
+
+/// Runs Lox code on behalf of a host program.
+///
+/// Wires the scanner, parser and interpreter together. One engine keeps a single global
+/// environment, so definitions made by one call are visible to the next. The command line
+/// and the REPL are both built on it.
+///
+/// An engine is `Send`, so it can be moved to a worker thread, but not meant to be shared:
+/// use one engine per thread. Its environment is owned outright rather than reference
+/// counted, and what a host hands it, like an output writer, has to be `Send` as well.
+///
+/// Engines are isolated from each other: the crate keeps no mutable global state (the
+/// keyword table is the only global, and it never changes), errors are returned rather
+/// than printed, and captured output is per engine. Only `print` without capturing or a
+/// writer goes to the process's shared stdout.
+#[derive(Default)]
+pub struct RLoxEngine {
+    interpreter: Interpreter,
+    timings: Timings,
+    /// The size of the programs run since the metrics were last taken.
+    metrics: AstMetrics,
+    /// Whether sources may be loaded from an up-to-date `.loxc` cache next to them.
+    use_cache: bool,
+    /// The edition sources are scanned in.
+    edition: Edition,
+}
+
+impl RLoxEngine {
+    pub fn new() -> Self {
+        Self {
+            interpreter: Interpreter::new_with_defaults(),
+            timings: Timings::default(),
+            metrics: AstMetrics::default(),
+            use_cache: false,
+            edition: Edition::default(),
+        }
+    }
+
+    /// Creates an engine whose interpreter is set up as `options` say.
+    pub fn with_options(options: InterpreterOptions) -> Result<Self, OptionsError> {
+        let edition = options.edition;
+        Ok(Self {
+            interpreter: Interpreter::with_options(options)?,
+            edition,
+            ..Self::new()
+        })
+    }
+
+    /// The edition sources are scanned in, as set by
+    /// [`InterpreterOptions::edition`].
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    /// Lets [`run_sources`](Self::run_sources) skip scanning and parsing a source whose
+    /// name is a path with an up-to-date `.loxc` cache. Off by default.
+    pub fn use_cache(&mut self, enabled: bool) {
+        self.use_cache = enabled;
+    }
+
+    /// Makes `print` append to a buffer read by [`take_output`](Self::take_output) instead
+    /// of writing to stdout. Turning it off drops whatever hasn't been taken yet.
+    ///
+    /// Output stays in execution order across calls, and a runtime error keeps everything
+    /// printed before it.
+    pub fn capture_output(&mut self, enabled: bool) {
+        self.interpreter.capture_output(enabled);
+    }
+
+    /// Returns everything printed since capturing started or the last take; empty when
+    /// output isn't captured.
+    pub fn take_output(&mut self) -> String {
+        self.interpreter.take_output()
+    }
+
+    /// Records what programs do as [`Event`]s, read by [`take_events`](Self::take_events);
+    /// see [`Interpreter::record_events`].
+    pub fn record_events(&mut self, enabled: bool) {
+        self.interpreter.record_events(enabled);
+    }
+
+    /// Returns the events recorded since recording started or the last take.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.interpreter.take_events()
+    }
+
+    /// The text `value` is shown as, with numbers formatted the way `print` formats them.
+    pub fn format_value(&self, value: &Object) -> String {
+        self.interpreter.format_value(value)
+    }
+
+    /// The text `value` is shown as for developers, with strings quoted and escaped, e.g.
+    /// by the REPL's echo.
+    pub fn inspect_value(&self, value: &Object) -> String {
+        self.interpreter.inspect_value(value)
+    }
+
+    /// Returns how long each phase took since the last call, and starts counting afresh.
+    pub fn take_timings(&mut self) -> Timings {
+        std::mem::take(&mut self.timings)
+    }
+
+    /// Returns the combined metrics of the programs run since the last call, and starts
+    /// counting afresh.
+    pub fn take_metrics(&mut self) -> AstMetrics {
+        std::mem::take(&mut self.metrics)
+    }
+
+    /// Returns what the interpreter did since the last call, and starts counting afresh.
+    pub fn take_stats(&mut self) -> Stats {
+        self.interpreter.take_stats()
+    }
+
+    /// Returns every global binding, sorted by name.
+    pub fn globals(&self) -> impl Iterator<Item = (&str, &Object)> {
+        self.interpreter.environment.entries()
+    }
+
+    /// Binds `name` to `value` in the global environment.
+    pub fn define(&mut self, name: &str, value: Object) {
+        self.interpreter.environment.define(name.to_string(), value);
+    }
+
+    /// Binds `name` to `value` as a built-in, which programs can't redefine unless
+    /// [`allow_shadow_natives`](InterpreterOptions::allow_shadow_natives) is set. The host
+    /// itself still can, with either method.
+    pub fn define_builtin(&mut self, name: &str, value: Object) {
+        self.interpreter
+            .environment
+            .define_builtin(name.to_string(), value);
+    }
+
+    /// Binds `name` to the Lox counterpart of a JSON value, see [`Object::from_json`].
+    pub fn set_global_json(
+        &mut self,
+        name: &str,
+        value: &serde_json::Value,
+    ) -> Result<(), ConversionError> {
+        self.define(name, Object::from_json(value)?);
+        Ok(())
+    }
+
+    /// Returns the global `name` as JSON, see [`Object::to_json`], or `None` if it isn't defined.
+    pub fn get_global_json(&self, name: &str) -> Option<serde_json::Value> {
+        self.interpreter
+            .environment
+            .lookup(name)
+            .map(Object::to_json)
+    }
+
+    /// Serializes the global bindings, e.g. to restore them in a later process with
+    /// [`restore`](Self::restore).
+    ///
+    /// Every value Lox has today is plain data. Values that aren't, such as functions, will
+    /// be left out rather than make the snapshot fail.
+    pub fn snapshot(&self) -> Vec<u8> {
+        snapshot::write(self.globals(), self.interpreter.environment.builtins())
+    }
+
+    /// Creates an engine whose globals are those of a [`snapshot`](Self::snapshot), built-ins
+    /// included.
+    ///
+    /// Fails without partially restoring anything if the snapshot is from another format
+    /// version or holds a value this version doesn't know.
+    pub fn restore(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut engine = Self::new();
+        let snapshot = snapshot::read(bytes)?;
+        for (name, value) in snapshot.globals {
+            if snapshot.builtins.contains(&name) {
+                engine.define_builtin(&name, value);
+            } else {
+                engine.define(&name, value);
+            }
+        }
+        Ok(engine)
+    }
+
+    /// Runs a Lox program; `name` labels its errors, e.g. with a file name.
+    ///
+    /// Nothing is executed if scanning or parsing reported any error. If the program ends
+    /// with an expression statement, its value is the result of the run, e.g. for a
+    /// config file that computes a setting; a declaration or nil gives `None`.
+    pub fn run_source(
+        &mut self,
+        name: &str,
+        source: &str,
+    ) -> Result<Option<Object>, Vec<Diagnostic>> {
+        self.run_sources(&[(name, source)])
+    }
+
+    /// Runs several named programs in order, so later ones see earlier definitions.
+    ///
+    /// Every source is scanned and parsed before anything runs; if any of them has errors,
+    /// all errors are returned and nothing is executed. A runtime error stops the run
+    /// before the next source, unless the interpreter was told to
+    /// [`keep_going`](InterpreterOptions::keep_going). The result is that of the last
+    /// program, as [`run_source`](Self::run_source) describes.
+    pub fn run_sources(
+        &mut self,
+        sources: &[(&str, &str)],
+    ) -> Result<Option<Object>, Vec<Diagnostic>> {
+        let mut programs = vec![];
+        let mut errors = vec![];
+        for &(name, source) in sources {
+            if self.use_cache {
+                let start = Instant::now();
+                if let Some(statements) = cache::load(name, source, self.edition) {
+                    self.timings.parse += start.elapsed();
+                    self.timings.cached += 1;
+                    programs.push((name, statements));
+                    continue;
+                }
+            }
+
+            match self.parse_here(source, 1, false) {
+                Ok(statements) => programs.push((name, statements)),
+                Err(source_errors) => errors.extend(Diagnostic::all(name, source_errors)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut value = None;
+        for (name, statements) in programs {
+            match self.run_program(name, statements) {
+                Ok(result) => value = result,
+                Err(diagnostics) => {
+                    let recovered = diagnostics
+                        .iter()
+                        .all(|diagnostic| self.recovers_from(&diagnostic.error));
+                    errors.extend(diagnostics);
+                    if !recovered {
+                        break;
+                    }
+                    value = None;
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Runs a Lox program like [`run_source`](Self::run_source), but only its first `budget`
+    /// top-level statements, returning a handle to resume it with if any are left.
+    ///
+    /// The program is scanned and parsed in full up front; a scan or parse error completes
+    /// the run without executing anything.
+    pub fn run_budgeted(&mut self, name: &str, source: &str, budget: usize) -> Execution {
+        match self.parse_here(source, 1, false) {
+            Ok(statements) => ExecutionHandle::new(name, statements).resume(self, budget),
+            Err(errors) => Execution::Completed(Err(Diagnostic::all(name, errors))),
+        }
+    }
+
+    /// Returns true if a run carries on after `err`, see
+    /// [`keep_going`](InterpreterOptions::keep_going).
+    pub(crate) fn recovers_from(&self, err: &RLoxError) -> bool {
+        self.interpreter.recovers_from(err)
+    }
+
+    /// Interprets already parsed statements; `name` labels a runtime error. The result is
+    /// as [`run_source`](Self::run_source) describes.
+    pub fn run_program(
+        &mut self,
+        name: &str,
+        statements: Vec<Stmt>,
+    ) -> Result<Option<Object>, Vec<Diagnostic>> {
+        match self.interpret(name, statements) {
+            Ok(Some(Object::Nil)) => Ok(None),
+            Ok(value) => Ok(value),
+            Err(errors) => Err(Diagnostic::all(name, errors)),
+        }
+    }
+
+    /// Runs `source` the way the REPL does and returns the value of its last statement if
+    /// that is an expression statement, or nil otherwise.
+    ///
+    /// The final expression may omit its `;`, so `eval("1 + 2")` returns `3`. Errors are
+    /// labeled [`EVAL_SOURCE`].
+    pub fn eval(&mut self, source: &str) -> Result<Object, Vec<Diagnostic>> {
+        self.eval_from_line(1, source)
+    }
+
+    /// Like [`eval`](Self::eval), but numbers the lines of `source` from `first_line`, so a
+    /// REPL can number lines across the whole session.
+    pub fn eval_from_line(
+        &mut self,
+        first_line: u64,
+        source: &str,
+    ) -> Result<Object, Vec<Diagnostic>> {
+        let statements = self
+            .parse_here(source, first_line, true)
+            .map_err(|errors| Diagnostic::all(EVAL_SOURCE, errors))?;
+        match self.interpret(EVAL_SOURCE, statements) {
+            Ok(value) => Ok(value.unwrap_or(Object::Nil)),
+            Err(errors) => Err(Diagnostic::all(EVAL_SOURCE, errors)),
+        }
+    }
+
+    /// Evaluates a single parsed expression against the global environment.
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Object, RLoxError> {
+        let start = Instant::now();
+        let result = self.interpreter.interpret_expression(expr);
+        self.timings.interpret += start.elapsed();
+        result
+    }
+
+    /// Evaluates the expression `source` against the global environment and returns each
+    /// step of the evaluation as a line, see [`StepPrinter`]. Errors are labeled
+    /// [`EVAL_SOURCE`].
+    pub fn explain(&mut self, source: &str) -> Result<Vec<String>, Vec<Diagnostic>> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.edition(self.edition);
+        scanner.interner(self.interpreter.environment.take_interner());
+        scanner.scan_tokens();
+        self.interpreter
+            .environment
+            .restore_interner(scanner.take_interner());
+        if !scanner.errors.is_empty() {
+            return Err(Diagnostic::all(EVAL_SOURCE, scanner.errors));
+        }
+        let expr = Parser::new(scanner.tokens)
+            .parse_expression()
+            .map_err(|err| vec![Diagnostic::new(EVAL_SOURCE, err)])?;
+        let start = Instant::now();
+        let result = self.interpreter.explain_expression(&expr);
+        self.timings.interpret += start.elapsed();
+        let (_, steps) = result.map_err(|err| vec![Diagnostic::new(EVAL_SOURCE, err)])?;
+        StepPrinter::transcript(&expr, &steps)
+            .map_err(|err| vec![Diagnostic::new(EVAL_SOURCE, err)])
+    }
+
+    /// Scans and parses `input`, collecting the errors of both phases in source order and
+    /// timing each.
+    ///
+    /// In `repl_mode` the final expression statement may omit its `;`.
+    pub fn parse_source(
+        input: String,
+        repl_mode: bool,
+        timings: &mut Timings,
+    ) -> Result<Vec<Stmt>, Vec<RLoxError>> {
+        Self::parse_from_line(
+            input,
+            Edition::default(),
+            &mut Interner::new(),
+            1,
+            repl_mode,
+            timings,
+        )
+    }
+
+    /// Scans and parses a program like [`parse_source`](Self::parse_source), reserving only
+    /// the keywords of `edition`.
+    pub fn parse_in_edition(
+        input: String,
+        edition: Edition,
+        timings: &mut Timings,
+    ) -> Result<Vec<Stmt>, Vec<RLoxError>> {
+        Self::parse_from_line(input, edition, &mut Interner::new(), 1, false, timings)
+    }
+
+    /// Parses `source` to run on this engine: in its edition, and with the identifiers
+    /// interned where its environment will look for them.
+    fn parse_here(
+        &mut self,
+        source: &str,
+        first_line: u64,
+        repl_mode: bool,
+    ) -> Result<Vec<Stmt>, Vec<RLoxError>> {
+        let mut interner = self.interpreter.environment.take_interner();
+        let result = Self::parse_from_line(
+            source.to_string(),
+            self.edition,
+            &mut interner,
+            first_line,
+            repl_mode,
+            &mut self.timings,
+        );
+        self.interpreter.environment.restore_interner(interner);
+        result
+    }
+
+    /// [`parse_source`](Self::parse_source) in `edition`, with lines numbered from
+    /// `first_line` and names interned into `interner`.
+    fn parse_from_line(
+        input: String,
+        edition: Edition,
+        interner: &mut Interner,
+        first_line: u64,
+        repl_mode: bool,
+        timings: &mut Timings,
+    ) -> Result<Vec<Stmt>, Vec<RLoxError>> {
+        // lexing
+        let start = Instant::now();
+        let mut scanner = Scanner::new(input);
+        scanner.edition(edition);
+        scanner.first_line(first_line);
+        scanner.interner(std::mem::take(interner));
+        scanner.scan_tokens();
+        *interner = scanner.take_interner();
+        let mut errors = scanner.errors;
+        timings.scan += start.elapsed();
+        if !errors.is_empty() {
+            timings.failed_in = Some(Phase::Scan);
+        }
+
+        // parsing
+        let start = Instant::now();
+        let mut parser = if repl_mode {
+            Parser::new_repl(scanner.tokens)
+        } else {
+            Parser::new(scanner.tokens)
+        };
+        let result = parser.parse();
+        timings.parse += start.elapsed();
+
+        match result {
+            Ok(statements) if errors.is_empty() => Ok(statements),
+            Ok(_) => Err(errors),
+            Err(parse_errors) => {
+                timings.failed_in.get_or_insert(Phase::Parse);
+                errors.extend(parse_errors);
+                // in source order; on the same line, a scan error comes first
+                errors.sort_by_key(RLoxError::line);
+                Err(errors)
+            }
+        }
+    }
+
+    /// Interprets already parsed statements from the source `name`, adding the time taken
+    /// to the timings and their size to the metrics. Fails with every runtime error, recovered from or not, in order.
+    ///
+    /// A panic, e.g. in a writer the host supplied, fails the run with
+    /// [`RLoxError::Internal`] rather than unwinding through the host.
+    fn interpret(
+        &mut self,
+        name: &str,
+        statements: Vec<Stmt>,
+    ) -> Result<Option<Object>, Vec<RLoxError>> {
+        self.metrics.add(ast::metrics(&statements));
+        self.interpreter.set_source_name(name);
+        let start = Instant::now();
+        // Asserting unwind safety is sound because a panic can't leave the interpreter
+        // unusable: each global is a single map insert, so it holds whatever the statements
+        // before the panic defined, and everything else is reset when the next run starts.
+        let result =
+            panic::catch_unwind(AssertUnwindSafe(|| self.interpreter.interpret(statements)))
+                .unwrap_or_else(|panic| Err(RLoxError::from_panic(panic)));
+        self.timings.interpret += start.elapsed();
+        let mut errors = self.interpreter.take_errors();
+        let value = result.unwrap_or_else(|err| {
+            errors.push(err);
+            None
+        });
+        if !errors.is_empty() {
+            self.timings.failed_in = Some(Phase::Interpret);
+            return Err(errors);
+        }
+        Ok(value)
+    }
+}
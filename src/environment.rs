@@ -1,35 +1,106 @@
-use std::collections::HashMap;
+use std::collections::BTreeSet;
 
 use crate::{
     errors::RLoxError,
+    symbol::{Interner, Symbol},
     tokens::{Object, Token},
 };
 
-#[derive(Debug)]
+/// Global bindings, found by the [`Symbol`] of their name.
+///
+/// Everything that lists them, like `:env` or a snapshot, does so sorted by name, so in
+/// the same order every time.
+#[derive(Debug, Default)]
 pub struct Environment {
-    values: HashMap<String, Object>,
+    /// Interns the names of the bindings, and those of the programs that run here.
+    interner: Interner,
+    /// The value of every name, at the index of its symbol; `None` if it isn't bound.
+    values: Vec<Option<Object>>,
+    /// Names defined natively rather than by Lox code, which programs may not redefine.
+    builtins: BTreeSet<String>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
-            values: HashMap::new()
+            interner: Interner::new(),
+            values: vec![],
+            builtins: BTreeSet::new(),
         }
     }
 
     pub fn define(&mut self, name: String, value: Object) {
-        self.values.insert(name, value);
+        let symbol = self.interner.intern(&name);
+        if self.values.len() <= symbol.index() {
+            self.values.resize(symbol.index() + 1, None);
+        }
+        self.values[symbol.index()] = Some(value);
+    }
+
+    /// Binds `name` like [`define`](Self::define) and marks it as built-in.
+    pub fn define_builtin(&mut self, name: String, value: Object) {
+        self.builtins.insert(name.clone());
+        self.define(name, value);
+    }
+
+    /// Returns true if `name` was defined with [`define_builtin`](Self::define_builtin).
+    pub fn is_builtin(&self, name: &str) -> bool {
+        self.builtins.contains(name)
     }
 
+    /// Returns the names of the built-in bindings, sorted.
+    pub fn builtins(&self) -> impl Iterator<Item = &str> {
+        self.builtins.iter().map(String::as_str)
+    }
+
+    /// Returns the value `name` is bound to, going by its symbol if it was scanned with
+    /// this environment's [`interner`](Self::take_interner).
     pub fn get(&self, name: &Token) -> Result<Object, RLoxError> {
-        if let Some(value) = self.values.get(&name.lexeme) {
+        let symbol = name
+            .symbol
+            .and_then(|symbol| self.interner.confirm(symbol, &name.lexeme))
+            .or_else(|| self.interner.get(&name.lexeme));
+        if let Some(value) = symbol.and_then(|symbol| self.value(symbol)) {
             return Ok(value.clone());
         }
 
-
         Err(RLoxError::InterpreterError(
             name.clone(),
             "Unknown variable used.".to_string(),
         ))
     }
+
+    /// Returns the value bound to `name`, if any.
+    pub fn lookup(&self, name: &str) -> Option<&Object> {
+        self.value(self.interner.get(name)?)
+    }
+
+    /// Returns every binding defined in this environment, sorted by name.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Object)> {
+        let mut entries: Vec<(&str, &Object)> = self
+            .interner
+            .names()
+            .zip(&self.values)
+            .filter_map(|(name, value)| Some((name, value.as_ref()?)))
+            .collect();
+        entries.sort_by_key(|(name, _)| *name);
+        entries.into_iter()
+    }
+
+    /// Lends out the interner names are kept in, e.g. for a scanner to intern a program's
+    /// identifiers into, so its variables are found by their symbols' indexes.
+    /// Must be given back with [`restore_interner`](Self::restore_interner) before the
+    /// environment is used again.
+    pub fn take_interner(&mut self) -> Interner {
+        std::mem::take(&mut self.interner)
+    }
+
+    /// Gives back the interner lent out by [`take_interner`](Self::take_interner).
+    pub fn restore_interner(&mut self, interner: Interner) {
+        self.interner = interner;
+    }
+
+    fn value(&self, symbol: Symbol) -> Option<&Object> {
+        self.values.get(symbol.index())?.as_ref()
+    }
 }
@@ -1,26 +1,121 @@
+use std::{any::Any, fmt};
+
 use crate::tokens::Token;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum RLoxError {
+    ScanError(u64, String),          // line and message
     ParseError(u64, String),         // line and message
     InterpreterError(Token, String), // operator and message
+    Interrupted,                     // execution was cancelled with Ctrl-C
+    Aborted(String),                 // the host stopped execution, e.g. at its step limit
+    Internal(String),                // rlox panicked, which is a bug; the panic's message
 }
 
 impl RLoxError {
-    pub fn print(self) {
+    pub fn line(&self) -> Option<u64> {
         match self {
-            Self::InterpreterError(operator, message) => {
-                println!("[Line {}] Error: {}", operator.line, message)
-            }
-            Self::ParseError(line, message) => println!("[Line {}] Error: {}", line, message),
+            Self::ScanError(line, _) | Self::ParseError(line, _) => Some(*line),
+            Self::InterpreterError(operator, _) => Some(operator.line),
+            Self::Interrupted | Self::Aborted(_) | Self::Internal(_) => None,
         }
     }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::ScanError(_, message)
+            | Self::ParseError(_, message)
+            | Self::InterpreterError(_, message)
+            | Self::Aborted(message)
+            | Self::Internal(message) => message,
+            Self::Interrupted => "Interrupted.",
+        }
+    }
+
+    /// The process exit code for a run that failed with this error (sysexits.h).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ScanError(..) | Self::ParseError(..) => 65, // EX_DATAERR
+            Self::InterpreterError(..)
+            | Self::Interrupted
+            | Self::Aborted(_)
+            | Self::Internal(_) => 70, // EX_SOFTWARE
+        }
+    }
+
+    /// The error for a panic caught while running a program, with the payload `panic`
+    /// was raised with.
+    pub fn from_panic(panic: Box<dyn Any + Send>) -> Self {
+        let cause = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("no message");
+        Self::Internal(format!(
+            "Internal error: {}. This is a bug in rlox; please report it.",
+            cause.trim_end_matches('.')
+        ))
+    }
+
+    pub fn print(self) {
+        println!("{}", self)
+    }
+
+    /// Prints the error prefixed with the name of the source it came from.
+    pub fn print_in(self, source: &str) {
+        Diagnostic::new(source, self).print()
+    }
 }
 
-pub fn rlox_error(line: u64, message: &str) {
-    report(line, "", message);
+impl fmt::Display for RLoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line() {
+            Some(line) => write!(f, "[Line {}] Error: {}", line, self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+/// An error together with the name of the source it was found in, e.g. a file name.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub source: String,
+    pub error: RLoxError,
+}
+
+impl Diagnostic {
+    pub fn new(source: &str, error: RLoxError) -> Self {
+        Self {
+            source: source.to_string(),
+            error,
+        }
+    }
+
+    /// Attributes every error in `errors` to `source`.
+    pub fn all(source: &str, errors: Vec<RLoxError>) -> Vec<Self> {
+        errors
+            .into_iter()
+            .map(|error| Self::new(source, error))
+            .collect()
+    }
+
+    pub fn print(self) {
+        println!("{}", self)
+    }
 }
 
-pub fn report(line: u64, location: &str, message: &str) {
-    println!("[Line {}] Error {}: {}", line, location, message);
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error.line() {
+            Some(line) => write!(
+                f,
+                "[{}, Line {}] Error: {}",
+                self.source,
+                line,
+                self.error.message()
+            ),
+            None => write!(f, "[{}] {}", self.source, self.error.message()),
+        }
+    }
 }
@@ -0,0 +1,72 @@
+//! Running a program a slice at a time, for hosts that can't block until it finishes,
+//! like a GUI running scripts on its UI thread.
+
+use std::vec;
+
+use crate::{ast::stmt::Stmt, engine::RLoxEngine, errors::Diagnostic, tokens::Object};
+
+/// How far a budgeted run got, see [`RLoxEngine::run_budgeted`].
+pub enum Execution {
+    /// The program ran to its end, with the result
+    /// [`run_source`](RLoxEngine::run_source) would give, or stopped with an error.
+    Completed(Result<Option<Object>, Vec<Diagnostic>>),
+    /// The budget ran out first; resume the program with the handle.
+    Paused(ExecutionHandle),
+}
+
+/// A program paused between two top-level statements.
+///
+/// Its globals and anything it printed stay with the engine, so the engine must not run
+/// anything else before the program is resumed. Output is flushed at every pause.
+pub struct ExecutionHandle {
+    name: String,
+    remaining: vec::IntoIter<Stmt>,
+    /// Runtime errors recovered from so far, when keeping going.
+    errors: Vec<Diagnostic>,
+}
+
+impl ExecutionHandle {
+    pub(crate) fn new(name: &str, statements: Vec<Stmt>) -> Self {
+        Self {
+            name: name.to_string(),
+            remaining: statements.into_iter(),
+            errors: vec![],
+        }
+    }
+
+    /// How many top-level statements are still to run.
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+
+    /// Runs up to `budget` more top-level statements of the program on `engine`, the one
+    /// that started it.
+    ///
+    /// Only top-level statements count against the budget, however long each takes; the
+    /// step limit of [`InterpreterOptions::max_steps`](crate::options::InterpreterOptions::max_steps)
+    /// applies to each slice on its own.
+    pub fn resume(mut self, engine: &mut RLoxEngine, budget: usize) -> Execution {
+        let slice: Vec<Stmt> = self.remaining.by_ref().take(budget).collect();
+        let value = match engine.run_program(&self.name, slice) {
+            Ok(value) => value,
+            Err(diagnostics) => {
+                let recovered = diagnostics
+                    .iter()
+                    .all(|diagnostic| engine.recovers_from(&diagnostic.error));
+                self.errors.extend(diagnostics);
+                if !recovered {
+                    return Execution::Completed(Err(self.errors));
+                }
+                None
+            }
+        };
+        if self.remaining.len() > 0 {
+            return Execution::Paused(self);
+        }
+        if self.errors.is_empty() {
+            Execution::Completed(Ok(value))
+        } else {
+            Execution::Completed(Err(self.errors))
+        }
+    }
+}
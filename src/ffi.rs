@@ -0,0 +1,154 @@
+//! A C ABI over [`RLoxEngine`], enabled by the `ffi` feature, for hosts that aren't
+//! written in Rust. The declarations are in `include/rlox.h`.
+//!
+//! Strings returned to C are NUL-terminated copies owned by the engine; they stay valid
+//! until the next call that takes the same engine. No panic crosses the boundary: one is
+//! reported as [`RLOX_PANIC`].
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::engine::RLoxEngine;
+
+/// The run succeeded.
+pub const RLOX_OK: c_int = 0;
+/// The source had scan or parse errors, so nothing ran (EX_DATAERR).
+pub const RLOX_COMPILE_ERROR: c_int = 65;
+/// The program stopped with a runtime error (EX_SOFTWARE).
+pub const RLOX_RUNTIME_ERROR: c_int = 70;
+/// A pointer was null or the source wasn't valid UTF-8.
+pub const RLOX_INVALID_ARGUMENT: c_int = -1;
+/// rlox panicked; this is a bug, and the engine should be freed.
+pub const RLOX_PANIC: c_int = -2;
+
+/// An engine together with the strings last handed out about it.
+pub struct RLoxHandle {
+    engine: RLoxEngine,
+    output: CString,
+    error: CString,
+}
+
+impl RLoxHandle {
+    /// Runs `source`, keeping its output and errors for `rlox_last_output` and
+    /// `rlox_last_error`.
+    fn run(&mut self, source: &str) -> c_int {
+        let result = self.engine.run_source("<input>", source);
+        self.output = to_c_string(self.engine.take_output());
+        match result {
+            Ok(_) => {
+                self.error = CString::default();
+                RLOX_OK
+            }
+            Err(diagnostics) => {
+                let code = diagnostics
+                    .first()
+                    .map_or(RLOX_OK, |diagnostic| diagnostic.error.exit_code());
+                let errors: Vec<String> = diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.error.to_string())
+                    .collect();
+                self.error = to_c_string(errors.join("\n"));
+                code
+            }
+        }
+    }
+
+    fn fail(&mut self, message: &str) {
+        self.output = CString::default();
+        self.error = to_c_string(message.to_string());
+    }
+}
+
+/// Creates an engine whose globals persist across runs and whose output is captured.
+///
+/// Returns null if creating it panicked. Free it with `rlox_engine_free`.
+#[no_mangle]
+pub extern "C" fn rlox_engine_new() -> *mut RLoxHandle {
+    panic::catch_unwind(|| {
+        let mut engine = RLoxEngine::new();
+        engine.capture_output(true);
+        Box::into_raw(Box::new(RLoxHandle {
+            engine,
+            output: CString::default(),
+            error: CString::default(),
+        }))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Frees an engine and every string it handed out. Null is ignored.
+///
+/// # Safety
+///
+/// `engine` must be null or come from `rlox_engine_new`, and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn rlox_engine_free(engine: *mut RLoxHandle) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+/// Runs the NUL-terminated UTF-8 `source` on `engine`.
+///
+/// Returns `RLOX_OK`, `RLOX_COMPILE_ERROR` or `RLOX_RUNTIME_ERROR` like the exit code of the
+/// command line, or a negative code if the call itself failed.
+///
+/// # Safety
+///
+/// `engine` must come from `rlox_engine_new`, and `source` must be null or point to a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rlox_run(engine: *mut RLoxHandle, source: *const c_char) -> c_int {
+    let Some(handle) = engine.as_mut() else {
+        return RLOX_INVALID_ARGUMENT;
+    };
+    if source.is_null() {
+        handle.fail("The source is null.");
+        return RLOX_INVALID_ARGUMENT;
+    }
+    let Ok(source) = CStr::from_ptr(source).to_str() else {
+        handle.fail("The source is not valid UTF-8.");
+        return RLOX_INVALID_ARGUMENT;
+    };
+
+    match panic::catch_unwind(AssertUnwindSafe(|| handle.run(source))) {
+        Ok(code) => code,
+        Err(_) => {
+            handle.fail("rlox panicked while running the source.");
+            RLOX_PANIC
+        }
+    }
+}
+
+/// What the last `rlox_run` printed; empty if it printed nothing. Null if `engine` is.
+///
+/// # Safety
+///
+/// `engine` must be null or come from `rlox_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn rlox_last_output(engine: *const RLoxHandle) -> *const c_char {
+    engine
+        .as_ref()
+        .map_or(ptr::null(), |handle| handle.output.as_ptr())
+}
+
+/// The errors of the last `rlox_run`, one per line; empty if it succeeded. Null if
+/// `engine` is.
+///
+/// # Safety
+///
+/// `engine` must be null or come from `rlox_engine_new`.
+#[no_mangle]
+pub unsafe extern "C" fn rlox_last_error(engine: *const RLoxHandle) -> *const c_char {
+    engine
+        .as_ref()
+        .map_or(ptr::null(), |handle| handle.error.as_ptr())
+}
+
+/// The source came from a C string, so neither it nor anything it prints contains a NUL.
+fn to_c_string(text: String) -> CString {
+    CString::new(text).expect("text derived from a C string has no NUL")
+}
@@ -0,0 +1,309 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+    ops::Range,
+};
+
+use crate::{
+    keywords::Edition,
+    scanner::Scanner,
+    tokens::{Token, TokenType},
+};
+
+/// How a stretch of source text is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Operator,
+    Identifier,
+    /// Text the scanner rejected, an unterminated string, or an unmatched bracket.
+    Error,
+}
+
+impl Highlight {
+    fn ansi_code(self) -> &'static str {
+        match self {
+            Highlight::Keyword => "\x1b[35m",
+            Highlight::String => "\x1b[32m",
+            Highlight::Number => "\x1b[33m",
+            Highlight::Comment => "\x1b[2m",
+            Highlight::Operator => "\x1b[36m",
+            Highlight::Identifier => "",
+            Highlight::Error => "\x1b[4;31m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Returns `true` if output written to stdout should be colored.
+///
+/// Honors the `NO_COLOR` convention and doesn't color output that isn't going to a
+/// terminal unless `CLICOLOR_FORCE` is set to something other than `0`, e.g. for `less -R`.
+pub fn color_enabled() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let forced =
+        env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0");
+    !no_color && (forced || io::stdout().is_terminal())
+}
+
+/// Splits `source`, written for `edition`, into highlighted spans using the real scanner.
+///
+/// Whitespace is left out; everything else is covered by exactly one span, in source order.
+/// Never fails: text the scanner can't make sense of is classified as [`Highlight::Error`].
+pub fn classify(source: &str, edition: Edition) -> Vec<(Range<usize>, Highlight)> {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.edition(edition);
+    scanner.scan_tokens();
+    let tokens: Vec<&Token> = scanner
+        .tokens
+        .iter()
+        .filter(|token| token.token_type != TokenType::Eof)
+        .collect();
+    let unmatched = unmatched_brackets(&tokens);
+
+    let mut spans = vec![];
+    let mut cursor = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        classify_gap(source, cursor..token.offset, &mut spans);
+
+        let end = token.offset + token.lexeme.len();
+        let highlight = if unmatched.contains(&index) {
+            Highlight::Error
+        } else {
+            token_highlight(token.token_type)
+        };
+        spans.push((token.offset..end, highlight));
+        cursor = end;
+    }
+    classify_gap(source, cursor..source.len(), &mut spans);
+    spans
+}
+
+/// Returns `source`, written for `edition`, with ANSI color codes around every
+/// highlighted span. Apart from the inserted codes the text is reproduced byte for byte.
+pub fn paint(source: &str, edition: Edition) -> String {
+    let mut painted = String::with_capacity(source.len() * 2);
+    let mut cursor = 0;
+    for (range, highlight) in classify(source, edition) {
+        painted.push_str(&source[cursor..range.start]);
+        let code = highlight.ansi_code();
+        if code.is_empty() {
+            painted.push_str(&source[range.clone()]);
+        } else {
+            painted.push_str(code);
+            painted.push_str(&source[range.clone()]);
+            painted.push_str(RESET);
+        }
+        cursor = range.end;
+    }
+    painted.push_str(&source[cursor..]);
+    painted
+}
+
+fn token_highlight(token_type: TokenType) -> Highlight {
+    match token_type {
+        TokenType::And
+        | TokenType::Assert
+        | TokenType::Class
+        | TokenType::Debug
+        | TokenType::Else
+        | TokenType::False
+        | TokenType::Fun
+        | TokenType::For
+        | TokenType::If
+        | TokenType::Nil
+        | TokenType::Or
+        | TokenType::Print
+        | TokenType::Return
+        | TokenType::Super
+        | TokenType::This
+        | TokenType::True
+        | TokenType::Var
+        | TokenType::While => Highlight::Keyword,
+        TokenType::String => Highlight::String,
+        TokenType::Number => Highlight::Number,
+        TokenType::Comment => Highlight::Comment,
+        TokenType::Identifier | TokenType::Eof => Highlight::Identifier,
+        _ => Highlight::Operator,
+    }
+}
+
+/// Returns the indexes of the brackets in `tokens` that have no matching partner.
+fn unmatched_brackets(tokens: &[&Token]) -> Vec<usize> {
+    let mut open: Vec<(usize, TokenType)> = vec![];
+    let mut unmatched = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        match token.token_type {
+            TokenType::LeftParen | TokenType::LeftBrace => open.push((index, token.token_type)),
+            TokenType::RightParen | TokenType::RightBrace => {
+                let expected = if token.token_type == TokenType::RightParen {
+                    TokenType::LeftParen
+                } else {
+                    TokenType::LeftBrace
+                };
+                match open.last() {
+                    Some((_, opening)) if *opening == expected => {
+                        open.pop();
+                    }
+                    _ => unmatched.push(index),
+                }
+            }
+            _ => {}
+        }
+    }
+    unmatched.extend(open.into_iter().map(|(index, _)| index));
+    unmatched
+}
+
+/// Classifies the text between two tokens, which the scanner skipped: whitespace,
+/// comments, or characters it reported as errors.
+fn classify_gap(source: &str, gap: Range<usize>, spans: &mut Vec<(Range<usize>, Highlight)>) {
+    let mut position = gap.start;
+    while position < gap.end {
+        let rest = &source[position..gap.end];
+        let Some(character) = rest.chars().next() else {
+            break;
+        };
+
+        if character.is_whitespace() {
+            position += character.len_utf8();
+            continue;
+        }
+
+        let length = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            block_comment_length(rest)
+        } else if character == '"' {
+            // the scanner only skips a quote when the string never terminates
+            rest.len()
+        } else {
+            character.len_utf8()
+        };
+
+        let highlight = if rest.starts_with("//") || rest.starts_with("/*") {
+            Highlight::Comment
+        } else {
+            Highlight::Error
+        };
+        spans.push((position..position + length, highlight));
+        position += length;
+    }
+}
+
+/// Length of the (possibly nested, possibly unterminated) block comment at the start of `text`.
+fn block_comment_length(text: &str) -> usize {
+    let mut depth = 0;
+    let mut index = 0;
+    let bytes = text.as_bytes();
+    while index < bytes.len() {
+        if bytes[index..].starts_with(b"/*") {
+            depth += 1;
+            index += 2;
+        } else if bytes[index..].starts_with(b"*/") {
+            depth -= 1;
+            index += 2;
+            if depth == 0 {
+                return index;
+            }
+        } else {
+            index += 1;
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each span's text with its highlight.
+    fn spans(source: &str) -> Vec<(&str, Highlight)> {
+        classify(source, Edition::Latest)
+            .into_iter()
+            .map(|(range, highlight)| (&source[range], highlight))
+            .collect()
+    }
+
+    #[test]
+    fn tokens_are_classified_by_type() {
+        assert_eq!(
+            spans("var total = 1.5 + \"s\";"),
+            [
+                ("var", Highlight::Keyword),
+                ("total", Highlight::Identifier),
+                ("=", Highlight::Operator),
+                ("1.5", Highlight::Number),
+                ("+", Highlight::Operator),
+                ("\"s\"", Highlight::String),
+                (";", Highlight::Operator),
+            ]
+        );
+        assert_eq!(
+            spans("if (nil) print true;")
+                .into_iter()
+                .filter(|(_, highlight)| *highlight == Highlight::Keyword)
+                .map(|(text, _)| text)
+                .collect::<Vec<_>>(),
+            ["if", "nil", "print", "true"]
+        );
+    }
+
+    #[test]
+    fn comments_are_classified_wherever_they_are() {
+        assert_eq!(
+            spans("// note\n1; /* a /* nested */ one */ 2 // end"),
+            [
+                ("// note", Highlight::Comment),
+                ("1", Highlight::Number),
+                (";", Highlight::Operator),
+                ("/* a /* nested */ one */", Highlight::Comment),
+                ("2", Highlight::Number),
+                ("// end", Highlight::Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn words_reserved_after_the_edition_are_names() {
+        let source = "var assert = debug;";
+        let keywords = |edition| {
+            classify(source, edition)
+                .into_iter()
+                .filter(|(_, highlight)| *highlight == Highlight::Keyword)
+                .map(|(range, _)| &source[range])
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keywords(Edition::Lox2015), ["var"]);
+        assert_eq!(keywords(Edition::Latest), ["var", "assert", "debug"]);
+    }
+
+    #[test]
+    fn an_unterminated_string_is_an_error_to_the_end() {
+        assert_eq!(
+            spans("print \"open\nstill open"),
+            [
+                ("print", Highlight::Keyword),
+                ("\"open\nstill open", Highlight::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejected_characters_and_unmatched_brackets_are_errors() {
+        assert_eq!(
+            spans("(1 @ ))"),
+            [
+                ("(", Highlight::Operator),
+                ("1", Highlight::Number),
+                ("@", Highlight::Error),
+                (")", Highlight::Operator),
+                (")", Highlight::Error),
+            ]
+        );
+    }
+}
@@ -1,26 +1,449 @@
+use std::{
+    io::{self, BufWriter, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
 use crate::{
-    ast::{stmt::Stmt, visitor::StmtVisitor}, environment::Environment, errors::RLoxError, tokens::{Object, Token, TokenType}
+    ast::{explain, format::Formatter, stmt::Stmt, visitor::StmtVisitor},
+    environment::Environment,
+    errors::RLoxError,
+    number::Number,
+    options::{InterpreterOptions, OptionsError},
+    stats::Stats,
+    tokens::{Object, Token, TokenType},
 };
 
 use crate::ast::{expr::Expr, visitor::ExprVisitor};
 
+/// Where `print` writes.
+///
+/// Stdout and a host's writer are buffered, and flushed when a run ends, successfully or
+/// not, when the interpreter is dropped, and after every line when unbuffered.
+enum Output {
+    Stdout(BufWriter<io::Stdout>),
+    Captured(String),
+    Writer(BufWriter<Box<dyn Write + Send>>),
+}
+
+impl Output {
+    fn stdout() -> Self {
+        Output::Stdout(BufWriter::new(io::stdout()))
+    }
+}
+
+/// Something a program did that an observer can compare across ways of running it, see
+/// [`Interpreter::record_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A line written by `print` or `debug`.
+    Output(String),
+    /// A global was defined, with its value as [`Object::inspected`] shows it.
+    Global { name: String, value: String },
+    /// A runtime error, as it's displayed.
+    Error(String),
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Output::stdout()
+    }
+}
+
+#[derive(Default)]
 pub struct Interpreter {
-    pub environment: Environment
+    pub environment: Environment,
+    /// Raised from outside (e.g. a Ctrl-C handler) to stop execution at the next statement.
+    interrupted: Option<Arc<AtomicBool>>,
+    output: Output,
+    /// How many statements one run may execute.
+    max_steps: Option<u64>,
+    /// How many times the body of one `while` may run each time the loop is reached.
+    max_loop_iterations: Option<u64>,
+    /// How many bytes a single value may take up.
+    max_value_bytes: Option<usize>,
+    /// What has been done since the stats were last taken.
+    stats: Stats,
+    /// `stats.statements` when the current run started, which the step limit counts from.
+    run_started_at: u64,
+    /// Decimal places shown for non-integral numbers; shortest round-trip if `None`.
+    float_precision: Option<usize>,
+    /// Skips `debug` statements without evaluating their expressions.
+    strip_debug: bool,
+    /// Skips `assert` statements without evaluating their conditions.
+    disable_asserts: bool,
+    /// Lets programs redefine built-in globals, e.g. to stub them in tests.
+    allow_shadow_natives: bool,
+    /// Name of the program being run, e.g. its file, which `debug` output starts with.
+    source_name: String,
+    /// Name of the variable whose initializer is being evaluated, to explain reading it.
+    initializing: Option<String>,
+    /// Recovers from runtime errors at top-level statement boundaries.
+    keep_going: bool,
+    /// Runtime errors recovered from since they were last taken.
+    recovered: Vec<RLoxError>,
+    /// Flushes the output after every line rather than only when a run ends.
+    unbuffered: bool,
+    /// While explaining an expression, every node evaluated so far with its value, see
+    /// [`explain_expression`](Self::explain_expression).
+    explaining: Option<Vec<(usize, Object)>>,
+    /// What the program did since the events were last taken, while recording.
+    events: Option<Vec<Event>>,
 }
 
 impl Interpreter {
-    pub fn interpret(&mut self, stmts: Vec<Stmt>) -> Result<(), RLoxError> {
+    pub fn new() -> Self {
+        Self {
+            environment: Environment::new(),
+            interrupted: None,
+            output: Output::stdout(),
+            max_steps: None,
+            max_loop_iterations: None,
+            max_value_bytes: None,
+            stats: Stats::default(),
+            run_started_at: 0,
+            float_precision: None,
+            strip_debug: false,
+            disable_asserts: false,
+            allow_shadow_natives: false,
+            source_name: String::new(),
+            initializing: None,
+            keep_going: false,
+            recovered: vec![],
+            unbuffered: false,
+            explaining: None,
+            events: None,
+        }
+    }
+
+    /// Creates an interpreter with the default globals, set up as `options` say.
+    pub fn with_options(options: InterpreterOptions) -> Result<Self, OptionsError> {
+        let options = options.build()?;
+        let mut interpreter = Self::new_with_defaults();
+        interpreter.interrupted = options.interrupt_flag;
+        interpreter.max_steps = options.max_steps;
+        interpreter.max_loop_iterations = options.max_loop_iterations;
+        interpreter.max_value_bytes = options.max_value_bytes;
+        interpreter.float_precision = options.float_precision;
+        interpreter.strip_debug = options.strip_debug;
+        interpreter.disable_asserts = options.disable_asserts;
+        interpreter.allow_shadow_natives = options.allow_shadow_natives;
+        interpreter.keep_going = options.keep_going;
+        interpreter.unbuffered = options.unbuffered;
+        if let Some(writer) = options.writer {
+            interpreter.output = Output::Writer(BufWriter::new(writer));
+        }
+        interpreter.capture_output(options.capture_output);
+        Ok(interpreter)
+    }
+
+    /// Creates an interpreter with the globals every program starts with.
+    ///
+    /// Used both on startup and by the REPL's `:reset` so the two can't drift apart.
+    pub fn new_with_defaults() -> Self {
+        Self::new()
+    }
+
+    /// Executes `stmts` in order.
+    ///
+    /// Returns the value of the last statement if it is an expression statement. When
+    /// keeping going, a statement that fails with a runtime error is abandoned and kept for
+    /// [`take_errors`](Self::take_errors), and the next one runs.
+    ///
+    /// Whatever was printed is flushed before returning, so it comes out ahead of an error
+    /// the caller reports.
+    pub fn interpret(&mut self, stmts: Vec<Stmt>) -> Result<Option<Object>, RLoxError> {
+        let result = self.execute_all(stmts);
+        let flushed = self.flush();
+        let value = result?;
+        flushed?;
+        Ok(value)
+    }
+
+    fn execute_all(&mut self, stmts: Vec<Stmt>) -> Result<Option<Object>, RLoxError> {
+        self.run_started_at = self.stats.statements;
+        let mut last_value = None;
         for stmt in stmts {
-            self.execute(stmt)?;
+            let result = match &stmt {
+                Stmt::Expression { expression } => self
+                    .checkpoint()
+                    .and_then(|_| self.evaluate(expression))
+                    .map(Some),
+                _ => self.execute(&stmt).map(|_| None),
+            };
+            if let Err(err) = &result {
+                self.record(|| Event::Error(err.to_string()));
+            }
+            last_value = match result {
+                Ok(value) => value,
+                Err(err) if self.recovers_from(&err) => {
+                    self.stats.recovered_errors += 1;
+                    self.recovered.push(err);
+                    None
+                }
+                Err(err) => return Err(err),
+            };
+        }
+        Ok(last_value)
+    }
+
+    /// Starts or stops recording an [`Event`] for every line of output, global definition
+    /// and runtime error, e.g. to check that two ways of running a program agree. Stopping
+    /// drops whatever hasn't been taken yet.
+    ///
+    /// While not recording, nothing is formatted or stored.
+    pub fn record_events(&mut self, enabled: bool) {
+        match (&self.events, enabled) {
+            (None, true) => self.events = Some(vec![]),
+            (Some(_), false) => self.events = None,
+            _ => {}
+        }
+    }
+
+    /// Returns what was done since the last take, and starts counting afresh.
+    pub fn take_stats(&mut self) -> Stats {
+        std::mem::take(&mut self.stats)
+    }
+
+    /// Returns the events recorded since recording started or the last take, in order.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Records the event `event` builds, if recording.
+    fn record(&mut self, event: impl FnOnce() -> Event) {
+        if let Some(events) = &mut self.events {
+            events.push(event());
+        }
+    }
+
+    /// Returns true if a run carries on after `err`: it's a runtime error and the
+    /// interpreter keeps going.
+    pub fn recovers_from(&self, err: &RLoxError) -> bool {
+        self.keep_going && matches!(err, RLoxError::InterpreterError(..))
+    }
+
+    /// Returns the runtime errors recovered from since the last take, in order.
+    pub fn take_errors(&mut self) -> Vec<RLoxError> {
+        std::mem::take(&mut self.recovered)
+    }
+
+    /// Evaluates a single expression against the current environment.
+    pub fn interpret_expression(&mut self, expr: &Expr) -> Result<Object, RLoxError> {
+        self.run_started_at = self.stats.statements;
+        self.checkpoint()?;
+        self.evaluate(expr)
+    }
+
+    /// Evaluates a single expression like [`interpret_expression`](Self::interpret_expression),
+    /// also returning each node it evaluated, by [`node_id`](explain::node_id), with its
+    /// value, in the order evaluation finished them.
+    pub fn explain_expression(
+        &mut self,
+        expr: &Expr,
+    ) -> Result<(Object, Vec<(usize, Object)>), RLoxError> {
+        self.explaining = Some(vec![]);
+        let result = self.interpret_expression(expr);
+        let steps = self.explaining.take().unwrap_or_default();
+        result.map(|value| (value, steps))
+    }
+
+    /// Sets the name `debug` output is labeled with, e.g. the file being run.
+    pub fn set_source_name(&mut self, name: &str) {
+        self.source_name = name.to_string();
+    }
+
+    /// Makes `print` append to a buffer instead of writing to stdout or a writer, or go
+    /// back to stdout.
+    ///
+    /// Turning capturing off drops whatever hasn't been taken yet.
+    pub fn capture_output(&mut self, enabled: bool) {
+        match self.output {
+            Output::Captured(_) if !enabled => self.output = Output::stdout(),
+            Output::Captured(_) => {}
+            _ if enabled => self.output = Output::Captured(String::new()),
+            _ => {}
+        }
+    }
+
+    /// Returns everything printed since capturing started or the last take, in order.
+    pub fn take_output(&mut self) -> String {
+        match &mut self.output {
+            Output::Captured(buffer) => std::mem::take(buffer),
+            _ => String::new(),
+        }
+    }
+
+    /// The text `value` is shown as by the REPL and other hosts, formatted the way `print`
+    /// formats it.
+    pub fn format_value(&self, value: &Object) -> String {
+        value.formatted(self.float_precision)
+    }
+
+    /// The text `value` is shown as for developers, as [`Object::inspected`] describes.
+    pub fn inspect_value(&self, value: &Object) -> String {
+        value.inspected(self.float_precision)
+    }
+
+    /// Writes a line of program output wherever `print` goes.
+    fn write_line(&mut self, text: &str) -> Result<(), RLoxError> {
+        self.record(|| Event::Output(text.to_string()));
+        self.stats.output_bytes += text.len() as u64 + 1;
+        let written = match &mut self.output {
+            Output::Stdout(writer) => writeln!(writer, "{}", text),
+            Output::Captured(buffer) => {
+                buffer.push_str(text);
+                buffer.push('\n');
+                Ok(())
+            }
+            Output::Writer(writer) => writeln!(writer, "{}", text),
+        };
+        written.map_err(Self::write_failed)?;
+        if self.unbuffered {
+            self.flush()?;
         }
         Ok(())
     }
 
-    fn execute(&mut self, stmt: Stmt) -> Result<(), RLoxError> {
-        stmt.accept(self)
+    /// Writes out whatever `print` has buffered.
+    pub fn flush(&mut self) -> Result<(), RLoxError> {
+        let flushed = match &mut self.output {
+            Output::Stdout(writer) => writer.flush(),
+            Output::Captured(_) => Ok(()),
+            Output::Writer(writer) => writer.flush(),
+        };
+        flushed.map_err(Self::write_failed)
+    }
+
+    fn write_failed(err: io::Error) -> RLoxError {
+        RLoxError::Aborted(format!("Could not write the output: {}.", err))
+    }
+
+    /// Evaluates the expression of a `debug` statement, prints `[name:line] text = value`
+    /// like Rust's `dbg!` and returns the value.
+    fn debug(
+        &mut self,
+        keyword: &Token,
+        expression: &Expr,
+        text: &str,
+    ) -> Result<Object, RLoxError> {
+        let value = self.evaluate(expression)?;
+        let line = format!(
+            "[{}:{}] {} = {}",
+            self.source_name,
+            keyword.line,
+            text,
+            self.format_value(&value)
+        );
+        self.write_line(&line)?;
+        Ok(value)
+    }
+
+    /// Evaluates an assertion's condition and fails with a runtime error on the `assert`
+    /// keyword if it is falsey.
+    ///
+    /// When the condition is a comparison, its operands are evaluated once and the values
+    /// of those that aren't literals are listed in the error, e.g.
+    /// `Assertion failed: x < limit (x = 12, limit = 10).` They're shown the way the REPL
+    /// shows them, so `(name = "lox ")` gives away the trailing space.
+    fn assert(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        text: &str,
+    ) -> Result<(), RLoxError> {
+        let mut condition = condition;
+        while let Expr::Grouping { expression, .. } = condition {
+            condition = expression;
+        }
+
+        let mut operands = vec![];
+        let value = match condition {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } if Self::is_comparison(operator.token_type) => {
+                let left_value = self.evaluate(left)?;
+                let right_value = self.evaluate(right)?;
+                let value = self.binary(left_value.clone(), operator, right_value.clone())?;
+                for (operand, operand_value) in [(left, left_value), (right, right_value)] {
+                    if !matches!(**operand, Expr::Literal { .. }) {
+                        operands.push(format!(
+                            "{} = {}",
+                            Formatter::format_expr(operand)?,
+                            self.inspect_value(&operand_value)
+                        ));
+                    }
+                }
+                value
+            }
+            _ => self.evaluate(condition)?,
+        };
+        if Self::is_truthy(value) {
+            return Ok(());
+        }
+
+        let mut error = format!("Assertion failed: {}", text);
+        if !operands.is_empty() {
+            error.push_str(&format!(" ({})", operands.join(", ")));
+        }
+        if let Some(message) = message {
+            let message = self.evaluate(message)?;
+            error.push_str(&format!(": {}", self.format_value(&message)));
+        }
+        error.push('.');
+        Err(RLoxError::InterpreterError(keyword.clone(), error))
+    }
+
+    fn is_comparison(token_type: TokenType) -> bool {
+        matches!(
+            token_type,
+            TokenType::EqualEqual
+                | TokenType::BangEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+        )
     }
 
-    fn check_number_operand(&self, operator: Token, right: Object) -> Result<f64, RLoxError> {
+    /// Executes a statement, top-level or in a branch or loop, unless it's a `debug` or
+    /// `assert` statement that is being skipped. A skipped statement is still a step, so a
+    /// loop whose body is skipped can be interrupted and stops at the step limit.
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), RLoxError> {
+        self.checkpoint()?;
+        match stmt {
+            Stmt::Debug { .. } if self.strip_debug => Ok(()),
+            Stmt::Assert { .. } if self.disable_asserts => Ok(()),
+            _ => stmt.accept(self),
+        }
+    }
+
+    /// The single check point consulted before every statement, which stops the run if it
+    /// was interrupted or has used up its steps.
+    fn checkpoint(&mut self) -> Result<(), RLoxError> {
+        if let Some(interrupted) = &self.interrupted {
+            if interrupted.load(Ordering::Relaxed) {
+                return Err(RLoxError::Interrupted);
+            }
+        }
+        if let Some(max_steps) = self.max_steps {
+            if self.stats.statements - self.run_started_at == max_steps {
+                return Err(RLoxError::Aborted(format!(
+                    "Stopped at the step limit of {}.",
+                    max_steps
+                )));
+            }
+        }
+        self.stats.statements += 1;
+        Ok(())
+    }
+
+    fn check_number_operand(operator: Token, right: Object) -> Result<Number, RLoxError> {
         if let Object::Number(number) = right {
             return Ok(number);
         }
@@ -31,11 +454,10 @@ impl Interpreter {
     }
 
     fn check_number_operands(
-        &self,
         operator: Token,
         left: Object,
         right: Object,
-    ) -> Result<(f64, f64), RLoxError> {
+    ) -> Result<(Number, Number), RLoxError> {
         if let Object::Number(left_number) = left {
             if let Object::Number(right_number) = right {
                 return Ok((left_number, right_number));
@@ -48,7 +470,6 @@ impl Interpreter {
     }
 
     fn check_string_operands(
-        &self,
         operator: Token,
         left: Object,
         right: Object,
@@ -64,11 +485,160 @@ impl Interpreter {
         ))
     }
 
-    fn evaluate(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        expr.accept(self)
+    /// Applies a unary operator to an operand that has already been evaluated, like
+    /// [`apply_binary`](Self::apply_binary).
+    pub(crate) fn apply_unary(operator: &Token, right_side: Object) -> Result<Object, RLoxError> {
+        match operator.token_type {
+            TokenType::Bang => Ok(Object::Boolean(!Self::is_truthy(right_side))),
+            TokenType::Minus => {
+                // check if the right_side is a number
+                let number = Self::check_number_operand(operator.clone(), right_side)?;
+                Ok(Object::Number(-number))
+            }
+            _ => Err(Self::unknown_operator(operator)),
+        }
+    }
+
+    /// Whether `left` decides `and` or `or` on its own, so the right operand is skipped.
+    pub(crate) fn short_circuits(operator: &Token, left: &Object) -> bool {
+        match operator.token_type {
+            TokenType::Or => Self::is_truthy(left.clone()),
+            _ => !Self::is_truthy(left.clone()),
+        }
+    }
+
+    /// Only reachable with a syntax tree that didn't come from the parser.
+    fn unknown_operator(operator: &Token) -> RLoxError {
+        RLoxError::InterpreterError(
+            operator.clone(),
+            format!("Unknown operator '{}'.", operator.lexeme),
+        )
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Object, RLoxError> {
+        let value = expr.accept(self)?;
+        if let Some(steps) = &mut self.explaining {
+            steps.push((explain::node_id(expr), value.clone()));
+        }
+        Ok(value)
+    }
+
+    /// Fails if a value of `bytes` would be over the size `limit`, before it's created.
+    fn check_size(limit: Option<usize>, operator: &Token, bytes: usize) -> Result<(), RLoxError> {
+        match limit {
+            Some(limit) if bytes > limit => Err(RLoxError::InterpreterError(
+                operator.clone(),
+                format!("Value exceeds the configured size limit ({} bytes).", limit),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies a binary operator to operands that have already been evaluated.
+    fn binary(
+        &self,
+        left_resolved: Object,
+        operator: &Token,
+        right_resolved: Object,
+    ) -> Result<Object, RLoxError> {
+        Self::apply_binary(
+            left_resolved,
+            operator,
+            right_resolved,
+            self.max_value_bytes,
+        )
+    }
+
+    /// Applies a binary operator the way a program does, without an interpreter, e.g. to
+    /// fold constants; `max_value_bytes` is the size limit of a string it builds.
+    pub(crate) fn apply_binary(
+        left_resolved: Object,
+        operator: &Token,
+        right_resolved: Object,
+        max_value_bytes: Option<usize>,
+    ) -> Result<Object, RLoxError> {
+        // The == and != checks work with any pair of objects, as long as both sides are the same type.
+        if let TokenType::EqualEqual = operator.token_type {
+            let result = Self::is_equal(left_resolved, right_resolved);
+
+            if let Err(err) = result {
+                return Err(RLoxError::InterpreterError(operator.clone(), err));
+            } else {
+                return Ok(Object::Boolean(result.unwrap()));
+            }
+        } else if let TokenType::BangEqual = operator.token_type {
+            let result = Self::is_equal(left_resolved, right_resolved);
+
+            if let Err(err) = result {
+                return Err(RLoxError::InterpreterError(operator.clone(), err));
+            } else {
+                return Ok(Object::Boolean(!result.unwrap()));
+            }
+        }
+
+        if let Ok((left_number, right_number)) = Self::check_number_operands(
+            operator.clone(),
+            left_resolved.clone(),
+            right_resolved.clone(),
+        ) {
+            let return_number = match operator.token_type {
+                TokenType::Minus => Some(Ok(left_number - right_number)),
+                TokenType::Slash => {
+                    if !right_number.is_zero() {
+                        Some(Ok(left_number / right_number))
+                    } else {
+                        Some(Err(RLoxError::InterpreterError(
+                            operator.clone(),
+                            "Number cannot be divided by zero".to_string(),
+                        )))
+                    }
+                }
+                TokenType::Star => Some(Ok(left_number * right_number)),
+                TokenType::Plus => Some(Ok(left_number + right_number)),
+                _ => None,
+            };
+
+            let return_bool = match operator.token_type {
+                TokenType::Greater => Some(left_number > right_number),
+                TokenType::GreaterEqual => Some(left_number >= right_number),
+                TokenType::Less => Some(left_number < right_number),
+                TokenType::LessEqual => Some(left_number <= right_number),
+                _ => None,
+            };
+
+            if let Some(number) = return_number {
+                return Ok(Object::Number(number?));
+            } else if let Some(boolean) = return_bool {
+                return Ok(Object::Boolean(boolean));
+            }
+            return Err(Self::unknown_operator(operator));
+        }
+
+        if let Ok((left_string, right_string)) =
+            Self::check_string_operands(operator.clone(), left_resolved, right_resolved)
+        {
+            // `+` is the only operator that works on strings
+            if operator.token_type != TokenType::Plus {
+                return Err(RLoxError::InterpreterError(
+                    operator.clone(),
+                    "Operands must be numbers.".to_string(),
+                ));
+            }
+            Self::check_size(
+                max_value_bytes,
+                operator,
+                left_string.len() + right_string.len(),
+            )?;
+            return Ok(Object::String(format!("{}{}", left_string, right_string)));
+        }
+        Err(RLoxError::InterpreterError(
+            operator.clone(),
+            "The resolved values on right and left are not of the same type.".to_string(),
+        ))
     }
 
-    fn is_truthy(&self, object: Object) -> bool {
+    /// `nil` and `false` are falsey, everything else is truthy.
+    pub(crate) fn is_truthy(object: Object) -> bool {
         match object {
             Object::Nil => false,
             Object::Boolean(boolean) => boolean,
@@ -76,9 +646,9 @@ impl Interpreter {
         }
     }
 
-    fn is_equal(&self, left_side: Object, right_side: Object) -> Result<bool, String> {
+    fn is_equal(left_side: Object, right_side: Object) -> Result<bool, String> {
         let error_message =
-            format!("Unexpected different types on the left and right sides of the operator.");
+            "Unexpected different types on the left and right sides of the operator.".to_string();
 
         match left_side {
             Object::Nil => {
@@ -91,175 +661,178 @@ impl Interpreter {
                 if let Object::Boolean(right_boolean) = right_side {
                     return Ok(left_boolean == right_boolean);
                 }
-                return Err(error_message);
+                Err(error_message)
             }
             Object::Number(left_number) => {
                 if let Object::Number(right_number) = right_side {
                     return Ok(left_number == right_number);
                 }
-                return Err(error_message);
+                Err(error_message)
             }
             Object::String(left_string) => {
                 if let Object::String(right_string) = right_side {
                     return Ok(left_string == right_string);
                 }
-                return Err(error_message);
+                Err(error_message)
             }
         }
     }
 }
 
 impl StmtVisitor<()> for Interpreter {
-    fn visit_expr_stmt(&self, stmt: &Stmt) -> Result<(), RLoxError> {
-        if let Stmt::Expression { expression } = stmt {
-            self.evaluate(&expression)?;
-            return Ok(());
-        }
-        unreachable!()
+    fn visit_expr_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        self.evaluate(expression)?;
+        Ok(())
     }
 
-    fn visit_print_stmt(&self, stmt: &Stmt) -> Result<(), RLoxError> {
-        if let Stmt::Print { expression } = stmt {
-            let value = self.evaluate(&expression)?;
-            value.print();
-            return Ok(());
-        }
-        unreachable!()
-    }
-    
-    fn visit_var_stmt(&mut self, stmt: &Stmt) -> Result<(), RLoxError> {
-        if let Stmt::Var { name, initializer } = stmt {
-            let value = self.evaluate(&initializer)?;
-            self.environment.define(name.lexeme.clone(), value);
-            return Ok(())
-        }
-        unreachable!()
+    fn visit_print_stmt(&mut self, expression: &Expr) -> Result<(), RLoxError> {
+        let text = self.evaluate(expression)?.printed(self.float_precision);
+        self.write_line(&text)
     }
-}
 
-impl ExprVisitor<Object> for Interpreter {
-    fn visit_binary_expr(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        if let Expr::Binary {
-            left,
-            operator,
-            right,
-        } = expr
-        {
-            let left_resolved = self.evaluate(left)?;
-            let right_resolved = self.evaluate(right)?;
+    fn visit_debug_stmt(
+        &mut self,
+        keyword: &Token,
+        expression: &Expr,
+        text: &str,
+    ) -> Result<(), RLoxError> {
+        self.debug(keyword, expression, text)?;
+        Ok(())
+    }
 
-            // The == and != checks work with any pair of objects, as long as both sides are the same type.
-            if let TokenType::EqualEqual = operator.token_type {
-                let result = self.is_equal(left_resolved, right_resolved);
+    fn visit_var_stmt(
+        &mut self,
+        name: &Token,
+        _annotation: Option<&Token>,
+        initializer: &Expr,
+    ) -> Result<(), RLoxError> {
+        if self.environment.is_builtin(&name.lexeme) && !self.allow_shadow_natives {
+            return Err(RLoxError::InterpreterError(
+                name.clone(),
+                format!("Cannot redefine built-in '{}'.", name.lexeme),
+            ));
+        }
+        let outer = self.initializing.replace(name.lexeme.clone());
+        let value = self.evaluate(initializer);
+        self.initializing = outer;
+        let value = value?;
+        let precision = self.float_precision;
+        self.record(|| Event::Global {
+            name: name.lexeme.clone(),
+            value: value.inspected(precision),
+        });
+        self.environment.define(name.lexeme.clone(), value);
+        Ok(())
+    }
 
-                if let Err(err) = result {
-                    return Err(RLoxError::InterpreterError(operator.clone(), err));
-                } else {
-                    return Ok(Object::Boolean(result.unwrap()));
-                }
-            } else if let TokenType::BangEqual = operator.token_type {
-                let result = self.is_equal(left_resolved, right_resolved);
+    fn visit_assert_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        message: Option<&Expr>,
+        text: &str,
+    ) -> Result<(), RLoxError> {
+        self.assert(keyword, condition, message, text)
+    }
 
-                if let Err(err) = result {
-                    return Err(RLoxError::InterpreterError(operator.clone(), err));
-                } else {
-                    return Ok(Object::Boolean(!result.unwrap()));
-                }
-            }
+    fn visit_if_stmt(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), RLoxError> {
+        if Self::is_truthy(self.evaluate(condition)?) {
+            self.execute(then_branch)
+        } else if let Some(else_branch) = else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
 
-            if let Ok((left_number, right_number)) = self.check_number_operands(
-                operator.clone(),
-                left_resolved.clone(),
-                right_resolved.clone(),
-            ) {
-                let return_number = match operator.token_type {
-                    TokenType::Minus => Some(Ok(left_number - right_number)),
-                    TokenType::Slash => {
-                        if right_number != 0.0 {
-                            Some(Ok(left_number / right_number))
-                        } else {
-                            Some(Err(RLoxError::InterpreterError(
-                                operator.clone(),
-                                "Number cannot be divided by zero".to_string(),
-                            )))
-                        }
-                    }
-                    TokenType::Star => Some(Ok(left_number * right_number)),
-                    TokenType::Plus => Some(Ok(left_number + right_number)),
-                    _ => None,
-                };
-
-                let return_bool = match operator.token_type {
-                    TokenType::Greater => Some(left_number > right_number),
-                    TokenType::GreaterEqual => Some(left_number >= right_number),
-                    TokenType::Less => Some(left_number < right_number),
-                    TokenType::LessEqual => Some(left_number <= right_number),
-                    _ => None,
-                };
-
-                if let Some(number) = return_number {
-                    return Ok(Object::Number(number?));
-                } else if let Some(boolean) = return_bool {
-                    return Ok(Object::Boolean(boolean));
-                }
-                panic!("Unexpected operator between numbers");
+    fn visit_while_stmt(
+        &mut self,
+        keyword: &Token,
+        condition: &Expr,
+        body: &Stmt,
+    ) -> Result<(), RLoxError> {
+        // counted afresh every time the loop is reached, e.g. again on the next run of an
+        // enclosing loop
+        let mut iterations = 0;
+        while Self::is_truthy(self.evaluate(condition)?) {
+            if self.max_loop_iterations == Some(iterations) {
+                return Err(RLoxError::InterpreterError(
+                    keyword.clone(),
+                    format!(
+                        "Loop exceeded {} iterations (possible infinite loop) at line {}.",
+                        iterations, keyword.line
+                    ),
+                ));
             }
+            iterations += 1;
+            self.execute(body)?;
+        }
+        Ok(())
+    }
+}
 
-            if let Ok((left_string, right_string)) =
-                self.check_string_operands(operator.clone(), left_resolved, right_resolved)
-            {
-                let return_string = match operator.token_type {
-                    TokenType::Plus => format!("{}{}", left_string, right_string),
-                    _ => panic!("Expected arithmetic operators"),
-                };
+impl ExprVisitor<Object> for Interpreter {
+    fn visit_binary_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Object, RLoxError> {
+        let left_resolved = self.evaluate(left)?;
+        let right_resolved = self.evaluate(right)?;
+        let value = self.binary(left_resolved, operator, right_resolved)?;
+        self.stats.created(&value);
+        Ok(value)
+    }
 
-                return Ok(Object::String(return_string));
-            }
-            return Err(RLoxError::InterpreterError(
-                operator.clone(),
-                "The resolved values on right and left are not of the same type.".to_string(),
-            ));
+    fn visit_logical_expr(
+        &mut self,
+        left: &Expr,
+        operator: &Token,
+        right: &Expr,
+    ) -> Result<Object, RLoxError> {
+        // the result is the deciding operand itself, not its truthiness, so that
+        // `name or "default"` works
+        let left = self.evaluate(left)?;
+        if Self::short_circuits(operator, &left) {
+            return Ok(left);
         }
-        unreachable!()
+        self.evaluate(right)
     }
 
-    fn visit_literal_expr(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        if let Expr::Literal { value } = expr {
-            return Ok(value.clone());
-        }
-        panic!("Expected literal, got other value")
+    fn visit_literal_expr(&mut self, value: &Object) -> Result<Object, RLoxError> {
+        self.stats.created(value);
+        Ok(value.clone())
     }
 
-    fn visit_grouping_expr(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        if let Expr::Grouping { expression } = expr {
-            return self.evaluate(expression);
-        }
-        panic!("Expected grouping, got other value")
-    }
-
-    fn visit_unary_expr(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        if let Expr::Unary { operator, right } = expr {
-            let right_side = self.evaluate(right)?;
-            return match operator.token_type {
-                TokenType::Bang => Ok(Object::Boolean(!self.is_truthy(right_side))),
-                TokenType::Minus => {
-                    // check if the right_side is a number
-                    self.check_number_operand(operator.clone(), right_side.clone())?;
-                    if let Object::Number(number) = right_side {
-                        return Ok(Object::Number(-number));
-                    }
-                    panic!("Expected number got something else");
-                }
-                _ => panic!("Expected BANG or MINUS but got something else"),
-            };
-        }
-        panic!("Expected grouping, got other value")
+    fn visit_grouping_expr(&mut self, expression: &Expr) -> Result<Object, RLoxError> {
+        self.evaluate(expression)
     }
-    
-    fn visit_variable_expr(&self, expr: &Expr) -> Result<Object, RLoxError> {
-        if let Expr::Variable { name } = expr {
-            return self.environment.get(name);
-        }
-        unreachable!()
+
+    fn visit_unary_expr(&mut self, operator: &Token, right: &Expr) -> Result<Object, RLoxError> {
+        let right_side = self.evaluate(right)?;
+        let value = Self::apply_unary(operator, right_side)?;
+        self.stats.created(&value);
+        Ok(value)
+    }
+
+    fn visit_variable_expr(&mut self, name: &Token) -> Result<Object, RLoxError> {
+        self.environment.get(name).map_err(|err| {
+            if self.initializing.as_ref() != Some(&name.lexeme) {
+                return err;
+            }
+            RLoxError::InterpreterError(
+                name.clone(),
+                format!(
+                    "Cannot read '{}' in its own initializer; it has not been defined yet.",
+                    name.lexeme
+                ),
+            )
+        })
     }
 }
@@ -1,26 +1,62 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
+use clap::ValueEnum;
 use once_cell::sync::Lazy;
 
 use crate::tokens::TokenType;
 
-pub static KEYWORDS: Lazy<HashMap<&str, TokenType>> = Lazy::new(|| {
-    let mut keywords: HashMap<&str, TokenType> = HashMap::new();
-    keywords.insert("and", TokenType::And);
-    keywords.insert("class", TokenType::Class);
-    keywords.insert("else", TokenType::Else);
-    keywords.insert("for", TokenType::For);
-    keywords.insert("fun", TokenType::Fun);
-    keywords.insert("if", TokenType::If);
-    keywords.insert("nil", TokenType::Nil);
-    keywords.insert("or", TokenType::Or);
-    keywords.insert("print", TokenType::Print);
-    keywords.insert("return", TokenType::Return);
-    keywords.insert("super", TokenType::Super);
-    keywords.insert("this", TokenType::This);
-    keywords.insert("true", TokenType::True);
-    keywords.insert("false", TokenType::False);
-    keywords.insert("var", TokenType::Var);
-    keywords.insert("while", TokenType::While);
+/// A version of the language, which decides what words are reserved.
+///
+/// Reserving a new word would break programs that use it as a name, so each keyword
+/// belongs to the edition that introduced it, and programs written for an older edition
+/// keep scanning the newer words as identifiers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Edition {
+    /// The keywords of the book's Lox.
+    #[value(name = "2015")]
+    Lox2015,
+    /// Every keyword, including `assert` and `debug`.
+    #[default]
+    Latest,
+}
+
+impl fmt::Display for Edition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .to_possible_value()
+            .expect("every edition has a name on the command line");
+        write!(f, "{}", name.get_name())
+    }
+}
+
+/// Every keyword, with the edition that introduced it.
+pub static KEYWORDS: Lazy<HashMap<&str, (TokenType, Edition)>> = Lazy::new(|| {
+    let mut keywords: HashMap<&str, (TokenType, Edition)> = HashMap::new();
+    keywords.insert("and", (TokenType::And, Edition::Lox2015));
+    keywords.insert("assert", (TokenType::Assert, Edition::Latest));
+    keywords.insert("class", (TokenType::Class, Edition::Lox2015));
+    keywords.insert("debug", (TokenType::Debug, Edition::Latest));
+    keywords.insert("else", (TokenType::Else, Edition::Lox2015));
+    keywords.insert("for", (TokenType::For, Edition::Lox2015));
+    keywords.insert("fun", (TokenType::Fun, Edition::Lox2015));
+    keywords.insert("if", (TokenType::If, Edition::Lox2015));
+    keywords.insert("nil", (TokenType::Nil, Edition::Lox2015));
+    keywords.insert("or", (TokenType::Or, Edition::Lox2015));
+    keywords.insert("print", (TokenType::Print, Edition::Lox2015));
+    keywords.insert("return", (TokenType::Return, Edition::Lox2015));
+    keywords.insert("super", (TokenType::Super, Edition::Lox2015));
+    keywords.insert("this", (TokenType::This, Edition::Lox2015));
+    keywords.insert("true", (TokenType::True, Edition::Lox2015));
+    keywords.insert("false", (TokenType::False, Edition::Lox2015));
+    keywords.insert("var", (TokenType::Var, Edition::Lox2015));
+    keywords.insert("while", (TokenType::While, Edition::Lox2015));
     keywords
 });
+
+/// The keyword `word` is in `edition`, if it is one there.
+pub fn keyword(word: &str, edition: Edition) -> Option<TokenType> {
+    KEYWORDS
+        .get(word)
+        .filter(|(_, since)| *since <= edition)
+        .map(|(token_type, _)| *token_type)
+}
@@ -0,0 +1,30 @@
+pub mod ast;
+pub mod cache;
+pub mod canonical;
+pub mod convert;
+pub mod engine;
+pub mod environment;
+pub mod errors;
+pub mod execution;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod highlight;
+pub mod interpreter;
+pub mod keywords;
+pub mod number;
+pub mod options;
+pub mod parser;
+#[cfg(feature = "cli")]
+pub mod repl;
+#[cfg(feature = "cli")]
+pub mod rlox;
+pub mod scanner;
+pub mod snapshot;
+pub mod stats;
+pub mod symbol;
+pub mod timings;
+pub mod tokens;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "cli")]
+pub mod watch;
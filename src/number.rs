@@ -0,0 +1,251 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A Lox number.
+///
+/// Lox has a single number type, but integers are kept exact while they fit in an `i64`:
+/// a literal without a decimal point is an `Int`, and `+`, `-`, `*` and `/` on two `Int`s
+/// give an `Int` unless the result overflows or, for `/`, isn't whole. Anything involving
+/// a `Float` gives a `Float`. Comparisons and equality don't care which of the two a
+/// number is, so `1 == 1.0`, and like `f64` they don't tell `0` and `-0` apart.
+#[derive(Debug, Clone, Copy)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    /// Parses a number literal as the scanner produces it: digits, optionally followed by
+    /// a decimal point and more digits. Returns `None` if it's too large for an `f64`,
+    /// rather than rounding it to infinity.
+    pub fn parse(literal: &str) -> Option<Number> {
+        if !literal.contains('.') {
+            if let Ok(int) = literal.parse() {
+                return Some(Number::Int(int));
+            }
+        }
+        literal
+            .parse()
+            .ok()
+            .filter(|float: &f64| float.is_finite())
+            .map(Number::Float)
+    }
+
+    /// How the number is written in Lox source so that it scans back to the same variant:
+    /// a whole `Float` keeps a `.0`, which [`Display`](fmt::Display) leaves out.
+    pub fn to_literal(self) -> String {
+        match self {
+            Number::Float(float) if float.is_finite() && float.fract() == 0.0 => {
+                format!("{:.1}", float)
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// The text the number is shown as. A `Float` with a fractional part is rounded to
+    /// `float_precision` decimal places if given, and otherwise written as the shortest
+    /// text that reads back as the same value. Ints and whole Floats never show decimals,
+    /// and zero never shows a sign, even when it's `-0` or rounds to zero from below.
+    pub fn format(self, float_precision: Option<usize>) -> String {
+        let text = match (self, float_precision) {
+            (Number::Int(int), _) => int.to_string(),
+            (Number::Float(float), Some(precision))
+                if float.is_finite() && float.fract() != 0.0 =>
+            {
+                format!("{:.*}", precision, float)
+            }
+            (Number::Float(float), _) => float.to_string(),
+        };
+        match text.strip_prefix('-') {
+            Some(unsigned) if unsigned.chars().all(|c| c == '0' || c == '.') => {
+                unsigned.to_string()
+            }
+            _ => text,
+        }
+    }
+
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(int) => int as f64,
+            Number::Float(float) => float,
+        }
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.as_f64() == 0.0
+    }
+
+    /// Applies `int` to two `Int`s, falling back to `float` when either is a `Float` or
+    /// `int` has no exact result.
+    fn combine(
+        self,
+        other: Number,
+        int: impl Fn(i64, i64) -> Option<i64>,
+        float: impl Fn(f64, f64) -> f64,
+    ) -> Number {
+        if let (Number::Int(left), Number::Int(right)) = (self, other) {
+            if let Some(result) = int(left, right) {
+                return Number::Int(result);
+            }
+        }
+        Number::Float(float(self.as_f64(), other.as_f64()))
+    }
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Number) -> Number {
+        self.combine(other, i64::checked_add, |left, right| left + right)
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Number) -> Number {
+        self.combine(other, i64::checked_sub, |left, right| left - right)
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, other: Number) -> Number {
+        self.combine(other, i64::checked_mul, |left, right| left * right)
+    }
+}
+
+/// Stays an `Int` only when the quotient is whole. Dividing by zero is the caller's to
+/// reject; here it follows `f64`.
+impl Div for Number {
+    type Output = Number;
+
+    fn div(self, other: Number) -> Number {
+        self.combine(
+            other,
+            |left, right| {
+                if left.checked_rem(right)? == 0 {
+                    left.checked_div(right)
+                } else {
+                    None
+                }
+            },
+            |left, right| left / right,
+        )
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Int(int) => int
+                .checked_neg()
+                .map_or(Number::Float(-(int as f64)), Number::Int),
+            Number::Float(float) => Number::Float(-float),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Int(left), Number::Int(right)) => Some(left.cmp(right)),
+            (Number::Float(left), Number::Float(right)) => left.partial_cmp(right),
+            (Number::Int(int), Number::Float(float)) => compare_exactly(*int, *float),
+            (Number::Float(float), Number::Int(int)) => {
+                compare_exactly(*int, *float).map(Ordering::reverse)
+            }
+        }
+    }
+}
+
+/// Compares an `Int` with a `Float` by their exact values, which converting the `Int` to
+/// an `f64` can't do past 2^53: there `9007199254740993` would round to and equal
+/// `9007199254740992.0`.
+fn compare_exactly(int: i64, float: f64) -> Option<Ordering> {
+    // 2^63, just past i64::MAX
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        None
+    } else if float >= LIMIT {
+        Some(Ordering::Less)
+    } else if float < -LIMIT {
+        Some(Ordering::Greater)
+    } else if float.fract() == 0.0 {
+        Some(int.cmp(&(float as i64)))
+    } else {
+        // a float with a fraction is below 2^52, where an `Int` either converts exactly
+        // or is too large to land on the other side of it
+        (int as f64).partial_cmp(&float)
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(None))
+    }
+}
+
+/// `Int`s are written as JSON integers and `Float`s as JSON floats. JSON has no
+/// infinities or NaN, which arithmetic can produce, so those are written as the strings
+/// `"inf"`, `"-inf"` and `"NaN"`.
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Number::Int(int) => serializer.serialize_i64(*int),
+            Number::Float(float) if float.is_finite() => serializer.serialize_f64(*float),
+            Number::Float(float) => serializer.serialize_str(&float.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Number, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+struct NumberVisitor;
+
+impl de::Visitor<'_> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a number, \"inf\", \"-inf\" or \"NaN\"")
+    }
+
+    fn visit_f64<E: de::Error>(self, float: f64) -> Result<Number, E> {
+        Ok(Number::Float(float))
+    }
+
+    fn visit_i64<E: de::Error>(self, int: i64) -> Result<Number, E> {
+        Ok(Number::Int(int))
+    }
+
+    fn visit_u64<E: de::Error>(self, int: u64) -> Result<Number, E> {
+        Ok(i64::try_from(int).map_or(Number::Float(int as f64), Number::Int))
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Number, E> {
+        match text {
+            "inf" => Ok(Number::Float(f64::INFINITY)),
+            "-inf" => Ok(Number::Float(f64::NEG_INFINITY)),
+            "NaN" => Ok(Number::Float(f64::NAN)),
+            _ => Err(E::invalid_value(de::Unexpected::Str(text), &self)),
+        }
+    }
+}
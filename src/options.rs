@@ -0,0 +1,180 @@
+use std::{
+    error::Error,
+    fmt,
+    io::Write,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use crate::keywords::Edition;
+
+/// How an [`Interpreter`](crate::interpreter::Interpreter) is set up, built up one option
+/// at a time:
+///
+/// ```
+/// use rlox::options::InterpreterOptions;
+///
+/// let options = InterpreterOptions::default()
+///     .max_steps(1_000_000)
+///     .writer(Vec::new())
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Every option defaults to the command line's behavior when left out.
+#[derive(Default)]
+pub struct InterpreterOptions {
+    pub(crate) writer: Option<Box<dyn Write + Send>>,
+    pub(crate) capture_output: bool,
+    pub(crate) max_steps: Option<u64>,
+    pub(crate) max_loop_iterations: Option<u64>,
+    pub(crate) max_value_bytes: Option<usize>,
+    pub(crate) interrupt_flag: Option<Arc<AtomicBool>>,
+    pub(crate) float_precision: Option<usize>,
+    pub(crate) strip_debug: bool,
+    pub(crate) disable_asserts: bool,
+    pub(crate) allow_shadow_natives: bool,
+    pub(crate) keep_going: bool,
+    pub(crate) unbuffered: bool,
+    pub(crate) sandbox: bool,
+    pub(crate) edition: Edition,
+}
+
+impl InterpreterOptions {
+    /// Where `print` writes, instead of stdout.
+    pub fn writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Makes `print` append to a buffer read with `take_output`, instead of writing to stdout.
+    pub fn capture_output(mut self, enabled: bool) -> Self {
+        self.capture_output = enabled;
+        self
+    }
+
+    /// Stops a run with a runtime error once it has executed `steps` statements.
+    pub fn max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Fails with a runtime error, on the line of the `while`, when a loop's condition still
+    /// holds after its body ran `iterations` times. The count starts over every time the
+    /// loop is reached.
+    pub fn max_loop_iterations(mut self, iterations: u64) -> Self {
+        self.max_loop_iterations = Some(iterations);
+        self
+    }
+
+    /// Fails with a runtime error instead of creating a value larger than `bytes`, e.g. a
+    /// string doubled over and over, before allocating it.
+    pub fn max_value_bytes(mut self, bytes: usize) -> Self {
+        self.max_value_bytes = Some(bytes);
+        self
+    }
+
+    /// Stops a run with [`RLoxError::Interrupted`](crate::errors::RLoxError::Interrupted)
+    /// at the next statement once `flag` is raised, e.g. by a Ctrl-C handler.
+    pub fn interrupt_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt_flag = Some(flag);
+        self
+    }
+
+    /// Shows numbers with a fractional part rounded to `places` decimal places, e.g. `0.3`
+    /// for `0.1 + 0.2` with one place, instead of the shortest text that reads back exactly.
+    ///
+    /// Applies to `print` and to [`RLoxEngine::format_value`](crate::engine::RLoxEngine::format_value),
+    /// which the REPL echoes with. Integers are never given decimals.
+    pub fn float_precision(mut self, places: usize) -> Self {
+        self.float_precision = Some(places);
+        self
+    }
+
+    /// Skips `debug` statements without evaluating their expressions, as in a release build.
+    pub fn strip_debug(mut self, enabled: bool) -> Self {
+        self.strip_debug = enabled;
+        self
+    }
+
+    /// Skips `assert` statements without evaluating their conditions or messages.
+    pub fn disable_asserts(mut self, enabled: bool) -> Self {
+        self.disable_asserts = enabled;
+        self
+    }
+
+    /// Lets programs redefine built-in globals, which is otherwise a runtime error, e.g. to
+    /// stub them in tests.
+    pub fn allow_shadow_natives(mut self, enabled: bool) -> Self {
+        self.allow_shadow_natives = enabled;
+        self
+    }
+
+    /// Carries on with the next top-level statement after one fails with a runtime error,
+    /// instead of stopping the program. Every error is still reported once the run ends.
+    /// Interruptions and the step limit stop the program either way.
+    pub fn keep_going(mut self, enabled: bool) -> Self {
+        self.keep_going = enabled;
+        self
+    }
+
+    /// Flushes `print` output after every line, e.g. for scripts that report progress, instead
+    /// of only when a run ends.
+    pub fn unbuffered(mut self, enabled: bool) -> Self {
+        self.unbuffered = enabled;
+        self
+    }
+
+    /// Promises that programs can't reach outside the interpreter, e.g. to run untrusted
+    /// snippets. Requires [`max_steps`](Self::max_steps), so a sandboxed program always
+    /// ends.
+    ///
+    /// Lox has no built-in functions yet, so there is nothing that touches files, the
+    /// environment, stdin or the clock to withhold. Any added later won't be defined in a
+    /// sandbox, so using one there is an unknown variable.
+    pub fn sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = enabled;
+        self
+    }
+
+    /// Scans programs as written for `edition`, so words it doesn't reserve yet can be
+    /// used as names. The latest edition by default.
+    pub fn edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Checks that the options make sense together.
+    pub fn build(self) -> Result<Self, OptionsError> {
+        if self.max_steps == Some(0) {
+            return Err(OptionsError {
+                message: "max_steps must be at least 1.".to_string(),
+            });
+        }
+        if self.sandbox && self.max_steps.is_none() {
+            return Err(OptionsError {
+                message: "A sandbox needs a step limit; set max_steps too.".to_string(),
+            });
+        }
+        if self.writer.is_some() && self.capture_output {
+            return Err(OptionsError {
+                message: "Output can't go to a writer and be captured at the same time."
+                    .to_string(),
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// Why a combination of [`InterpreterOptions`] was rejected.
+#[derive(Debug)]
+pub struct OptionsError {
+    pub message: String,
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for OptionsError {}
@@ -1,12 +1,26 @@
+use std::ops::Range;
+
 use crate::{
-    ast::{expr::Expr, stmt::Stmt},
+    ast::{expr::Expr, metrics, stmt::Stmt},
     errors::RLoxError,
-    tokens::{Token, TokenType},
+    tokens::{Object, Token, TokenType},
 };
 
+/// How deeply expressions may nest before the parser gives up, so that neither it nor
+/// the visitors walking the tree run out of stack. Depth is counted as
+/// [`AstMetrics::max_depth`](crate::ast::metrics::AstMetrics::max_depth) counts it.
+pub const MAX_NESTING: usize = 200;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: u64,
+    /// How deeply the expression being parsed is nested, see [`MAX_NESTING`].
+    depth: usize,
+    /// How deeply the statement being parsed is nested in `if`s and `while`s, which is
+    /// limited to [`MAX_NESTING`] as well.
+    statement_depth: usize,
+    /// Allows the final expression statement to omit its `;`, as typed at the prompt.
+    repl_mode: bool,
 }
 impl Parser {
     /// Generates a new Parser with the given token stream.
@@ -14,21 +28,36 @@ impl Parser {
     /// Uses the Recursive Descent Parsing (top to bottom) algorithm.
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let tokens = vec![Token { token_type: TokenType::Number, .. }];
     /// let mut parser = Parser::new(tokens);
     /// ```
     pub fn new(token_stream: Vec<Token>) -> Self {
         Self {
-            tokens: token_stream,
+            tokens: token_stream
+                .into_iter()
+                .filter(|token| token.token_type != TokenType::Comment)
+                .collect(),
             current: 0,
+            depth: 0,
+            statement_depth: 0,
+            repl_mode: false,
+        }
+    }
+
+    /// Generates a new Parser for input typed at the REPL, where `1 + 2` is accepted
+    /// as shorthand for the expression statement `1 + 2;`.
+    pub fn new_repl(token_stream: Vec<Token>) -> Self {
+        Self {
+            repl_mode: true,
+            ..Self::new(token_stream)
         }
     }
 
     /// Returns true if the current token is EOF
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let eof_token = Token { token_type: TokenType::Eof, .. };
     /// let mut parser = Parser::new(vec![eof_token]);
     /// assert!(parser.is_at_end());
@@ -40,7 +69,7 @@ impl Parser {
     /// Returns the current token
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let token = Token { token_type: TokenType::Number, .. };
     /// let mut parser = Parser::new(vec![token.clone()]);
     /// assert_eq!(parser.peek(), token);
@@ -53,7 +82,7 @@ impl Parser {
     /// Does not move the `current` field's value.
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let token = Token { token_type: TokenType::Number, .. };
     /// let mut parser = Parser::new(vec![token.clone()]);
     /// assert!(parser.check(TokenType::Number));
@@ -68,7 +97,7 @@ impl Parser {
     /// Returns the previous token if in range
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let tokens = vec![
     ///     Token { token_type: TokenType::Number, .. },
     ///     Token { token_type: TokenType::Plus, .. },
@@ -88,7 +117,7 @@ impl Parser {
     /// Advances the `current` field's value by one.
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let tokens = vec![Token { token_type: TokenType::Number, .. }];
     /// let mut parser = Parser::new(tokens);
     /// parser.advance();
@@ -104,7 +133,7 @@ impl Parser {
     /// Takes a vector of `TokenType` values and advances once one of them is encountered.
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let tokens = vec![
     ///     Token { token_type: TokenType::Number, .. },
     ///     Token { token_type: TokenType::Plus, .. },
@@ -126,7 +155,7 @@ impl Parser {
     /// Handles the expression rule.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 1 == 1 != 0
     /// resolve via equality            -----
     ///                                 true != 0
@@ -137,13 +166,49 @@ impl Parser {
     /// return the resolved state       ----------------
     /// ```
     fn expression(&mut self) -> Result<Expr, RLoxError> {
-        self.equality()
+        let top_level = self.depth == 0;
+        let expr = self.nested(|parser| parser.or())?;
+        // recursing can nest less deeply than the tree does, e.g. for the left operand of
+        // `+` in `(1 * 2) + 3`, so the finished tree is measured as well
+        if top_level && metrics::expression_depth(&expr) > MAX_NESTING {
+            return Err(self.parser_error("Expression nested too deeply."));
+        }
+        Ok(expr)
+    }
+
+    /// Handles the `or` rule; `or` binds more loosely than `and`.
+    fn or(&mut self) -> Result<Expr, RLoxError> {
+        self.resolve_with(|parser| parser.and(), vec![TokenType::Or], Self::logical)
+    }
+
+    /// Handles the `and` rule, whose operands are equalities.
+    fn and(&mut self) -> Result<Expr, RLoxError> {
+        self.resolve_with(
+            |parser| parser.equality(),
+            vec![TokenType::And],
+            Self::logical,
+        )
+    }
+
+    /// Runs `rule` one level deeper, failing once expressions nest more than
+    /// [`MAX_NESTING`] levels deep.
+    fn nested<R>(&mut self, rule: R) -> Result<Expr, RLoxError>
+    where
+        R: FnOnce(&mut Parser) -> Result<Expr, RLoxError>,
+    {
+        if self.depth >= MAX_NESTING {
+            return Err(self.parser_error("Expression nested too deeply."));
+        }
+        self.depth += 1;
+        let result = rule(self);
+        self.depth -= 1;
+        result
     }
 
     /// Handles the equality rule by passing the current value to the [`comparison()`] function, until `==` or `!=` is reached.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 1 == 2 != 1 == 2
     /// resolve via comparison          -----
     ///                                 false != 1 == 2
@@ -163,7 +228,7 @@ impl Parser {
     /// Handles the comparison rule by passing the current value to the [`term()`] function, until `>`, `<`, `>=`, or `<=` is reached.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 1 + 2 >= 2 + 1
     /// resolve via term                -----
     ///                                 3     >= 2 + 1
@@ -188,7 +253,7 @@ impl Parser {
     /// Handles the term rule by passing the current value to the [`factor()`] function, until `+` or `-` is reached.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 1 + 2 - 3
     /// resolve via factor              -----
     ///                                 3     - 3
@@ -208,7 +273,7 @@ impl Parser {
     /// Handles the factor rule by passing the current value to the [`unary()`] function, until `*` or `/` is reached.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 6 / 2 * 3
     /// resolve via unary               -----
     ///                                 3     * 3
@@ -228,7 +293,7 @@ impl Parser {
     /// Handles the unary rule by checking for `!` or `-` and recursively calling itself for further unary operators or primary expressions.
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 -1 + 2
     /// resolve via unary               --
     ///                                 -1     + 2
@@ -239,7 +304,7 @@ impl Parser {
     fn unary(&mut self) -> Result<Expr, RLoxError> {
         if self.match_token(&vec![TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous().unwrap();
-            let right = self.unary()?;
+            let right = self.nested(|parser| parser.unary())?;
             return Ok(Expr::Unary {
                 operator,
                 right: Box::new(right),
@@ -251,7 +316,7 @@ impl Parser {
     /// Handles the primary rule, which is the most basic unit of an expression (e.g., literals, grouping, or variable access).
     ///
     /// # Example
-    /// ```
+    /// ```text
     ///                                 (1 + 2) * 3
     /// resolve via grouping            -----
     ///                                 ( 3 ) * 3
@@ -261,83 +326,189 @@ impl Parser {
     /// ```
     fn primary(&mut self) -> Result<Expr, RLoxError> {
         if self.match_token(&vec![TokenType::False]) {
-            return Ok(Expr::Literal {
-                value: crate::tokens::Object::Boolean(false),
-            });
+            return Ok(self.literal(Object::Boolean(false)));
         } else if self.match_token(&vec![TokenType::True]) {
-            return Ok(Expr::Literal {
-                value: crate::tokens::Object::Boolean(true),
-            });
+            return Ok(self.literal(Object::Boolean(true)));
         } else if self.match_token(&vec![TokenType::Nil]) {
-            return Ok(Expr::Literal {
-                value: crate::tokens::Object::Nil,
-            });
+            return Ok(self.literal(Object::Nil));
         } else if self.match_token(&vec![TokenType::Number, TokenType::String]) {
-            return Ok(Expr::Literal {
-                value: self.previous().unwrap().literal.unwrap(),
-            });
+            let token = self.previous().unwrap();
+            let Some(value) = token.literal else {
+                return Err(RLoxError::ParseError(
+                    token.line,
+                    "Expected a value for the literal.".to_string(),
+                ));
+            };
+            return Ok(self.literal(value));
         } else if self.match_token(&vec![TokenType::LeftParen]) {
+            let start = self.previous().unwrap().offset;
             let expr = self.expression()?;
-            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+            let end = self
+                .consume(TokenType::RightParen, "after expression")?
+                .span()
+                .end;
             return Ok(Expr::Grouping {
                 expression: Box::new(expr),
+                span: Some(start..end),
             });
         } else if self.match_token(&vec![TokenType::Identifier]) {
             return Ok(Expr::Variable {
                 name: self.previous().unwrap(),
             });
         }
-        Err(self.parser_error("Expect expression."))
+        Err(self.unexpected("expression"))
+    }
+
+    /// A literal written as the token just consumed.
+    fn literal(&self, value: Object) -> Expr {
+        Expr::Literal {
+            value,
+            span: self.previous().map(|token| token.span()),
+        }
     }
 
-    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token, RLoxError> {
+    /// Consumes a token of `token_type`, or fails with "Expected `token_type` `context` but
+    /// found ...".
+    fn consume(&mut self, token_type: TokenType, context: &str) -> Result<Token, RLoxError> {
         if self.check(&token_type) {
             let token = self.peek();
             self.advance();
             return Ok(token);
         }
-        Err(self.parser_error(message))
+        Err(self.unexpected(&format!("{} {}", token_type, context)))
     }
 
     fn parser_error(&self, message: &str) -> RLoxError {
         RLoxError::ParseError(self.peek().line, message.to_string())
     }
 
-    #[allow(dead_code)]
+    /// An error saying what was `expected` instead of the current token.
+    fn unexpected(&self, expected: &str) -> RLoxError {
+        self.parser_error(&format!(
+            "Expected {} but found {}.",
+            expected,
+            self.peek().describe()
+        ))
+    }
+
+    /// Skips past a statement that failed to parse, to where the next one begins, so its
+    /// errors don't cascade.
+    ///
+    /// Braces are balanced on the way: a `;` or statement keyword inside a `{ ... }` that
+    /// began after the error doesn't end the statement, and a `}` closing one that began
+    /// before it is left for whatever opened it.
     fn synchronize(&mut self) {
-        self.advance();
+        let mut depth = 0;
+        // the token the error was found at is skipped, whatever it is, so recovery always
+        // moves on
+        let mut skipped_any = false;
         while !self.is_at_end() {
-            if let Some(prev_token) = self.previous() {
-                if prev_token.token_type == TokenType::Semicolon {
-                    return;
-                }
-
-                match prev_token.token_type {
-                    TokenType::Class => {}
-                    TokenType::Fun => {}
-                    TokenType::For => {}
-                    TokenType::Var => {}
-                    TokenType::If => {}
-                    TokenType::While => {}
-                    TokenType::Print => {}
-                    TokenType::Return => {}
+            let token_type = self.peek().token_type;
+            if skipped_any && depth == 0 {
+                match token_type {
+                    TokenType::RightBrace
+                    | TokenType::Class
+                    | TokenType::Fun
+                    | TokenType::For
+                    | TokenType::Var
+                    | TokenType::If
+                    | TokenType::While
+                    | TokenType::Print
+                    | TokenType::Return
+                    | TokenType::Assert
+                    | TokenType::Debug => return,
                     _ => {}
                 }
-
-                self.advance();
+            }
+            self.advance();
+            skipped_any = true;
+            match token_type {
+                TokenType::Semicolon if depth == 0 => return,
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
             }
         }
     }
 
     fn print_statement(&mut self) -> Result<Stmt, RLoxError> {
         let value = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ; after value.")?;
+        self.consume(TokenType::Semicolon, "after value")?;
         Ok(Stmt::Print { expression: value })
     }
 
+    fn debug_statement(&mut self) -> Result<Stmt, RLoxError> {
+        let keyword = self.previous().expect("'debug' was just matched");
+        let start = self.current as usize;
+        let expression = self.expression()?;
+        let text = self.source_text(start..self.current as usize);
+        self.consume(TokenType::Semicolon, "after value")?;
+        Ok(Stmt::Debug {
+            keyword,
+            expression,
+            text,
+        })
+    }
+
+    fn assert_statement(&mut self) -> Result<Stmt, RLoxError> {
+        let keyword = self.previous().expect("'assert' was just matched");
+        let start = self.current as usize;
+        let condition = self.expression()?;
+        let text = self.source_text(start..self.current as usize);
+        let message = if self.match_token(&vec![TokenType::Comma]) {
+            let token = self.peek();
+            let message = self.expression()?;
+            // a literal can be checked now; anything else is shown as it evaluates
+            if let Expr::Literal { value, .. } = &message {
+                if !matches!(value, Object::String(_)) {
+                    return Err(RLoxError::ParseError(
+                        token.line,
+                        format!(
+                            "Expected a string as the assertion message but found {}.",
+                            token.describe()
+                        ),
+                    ));
+                }
+            }
+            Some(message)
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "after assertion")?;
+        Ok(Stmt::Assert {
+            keyword,
+            condition,
+            message,
+            text,
+        })
+    }
+
+    /// Recovers the source of the tokens in `range` from their lexemes and offsets.
+    /// Whitespace and comments between two tokens become a single space.
+    fn source_text(&self, range: Range<usize>) -> String {
+        let mut text = String::new();
+        let mut end = None;
+        for token in &self.tokens[range] {
+            if end.is_some_and(|end| end < token.offset) {
+                text.push(' ');
+            }
+            text.push_str(&token.lexeme);
+            end = Some(token.offset + token.lexeme.len());
+        }
+        text
+    }
+
     fn expression_statement(&mut self) -> Result<Stmt, RLoxError> {
         let value = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ; after value.")?;
+        if self.repl_mode && self.is_at_end() {
+            return Ok(Stmt::Expression { expression: value });
+        }
+        self.consume(TokenType::Semicolon, "after value")?;
         Ok(Stmt::Expression { expression: value })
     }
 
@@ -345,24 +516,119 @@ impl Parser {
         if self.match_token(&vec![TokenType::Print]) {
             return self.print_statement();
         }
+        if self.match_token(&vec![TokenType::Assert]) {
+            return self.assert_statement();
+        }
+        if self.match_token(&vec![TokenType::Debug]) {
+            return self.debug_statement();
+        }
+        if self.match_token(&vec![TokenType::If]) {
+            return self.if_statement();
+        }
+        if self.match_token(&vec![TokenType::While]) {
+            return self.while_statement();
+        }
         self.expression_statement()
     }
 
+    /// Parses `if (condition) statement`, optionally followed by `else statement`. An
+    /// `else` is taken by the innermost `if` still without one, so in
+    /// `if (a) if (b) x; else y;` it belongs to `if (b)`.
+    fn if_statement(&mut self) -> Result<Stmt, RLoxError> {
+        self.consume(TokenType::LeftParen, "after 'if'")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "after the condition")?;
+
+        let (then_branch, else_branch) = self.nested_statement(|parser| {
+            let then_branch = parser.body("an 'if'")?;
+            let else_branch = if parser.match_token(&vec![TokenType::Else]) {
+                Some(parser.body("an 'if'")?)
+            } else {
+                None
+            };
+            Ok((then_branch, else_branch))
+        })?;
+
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    /// Parses `while (condition) statement`.
+    fn while_statement(&mut self) -> Result<Stmt, RLoxError> {
+        let keyword = self.previous().expect("'while' was just matched");
+        self.consume(TokenType::LeftParen, "after 'while'")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "after the condition")?;
+        let body = self.nested_statement(|parser| parser.body("a 'while'"))?;
+        Ok(Stmt::While {
+            keyword,
+            condition,
+            body,
+        })
+    }
+
+    /// Runs `rule` one statement deeper, failing once statements nest more than
+    /// [`MAX_NESTING`] levels deep.
+    fn nested_statement<T, R>(&mut self, rule: R) -> Result<T, RLoxError>
+    where
+        R: FnOnce(&mut Parser) -> Result<T, RLoxError>,
+    {
+        if self.statement_depth >= MAX_NESTING {
+            return Err(self.parser_error("Statements nested too deeply."));
+        }
+        self.statement_depth += 1;
+        let result = rule(self);
+        self.statement_depth -= 1;
+        result
+    }
+
+    /// The statement a branch of an `if` or a loop runs; a declaration there would define
+    /// a global only sometimes, so it's refused.
+    fn body(&mut self, owner: &str) -> Result<Box<Stmt>, RLoxError> {
+        if self.check(&TokenType::Var) {
+            return Err(
+                self.parser_error(&format!("A declaration can't be the body of {}.", owner))
+            );
+        }
+        if self.is_at_end() {
+            return Err(self.unexpected("a statement after the condition"));
+        }
+        Ok(Box::new(self.statement()?))
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, RLoxError> {
-        let name: Token = self.consume(TokenType::Identifier, "Expect variable name.")?;
+        let name: Token = self.consume(TokenType::Identifier, "after 'var'")?;
+        let annotation = if self.match_token(&vec![TokenType::Colon]) {
+            Some(self.type_annotation()?)
+        } else {
+            None
+        };
 
         let mut initializer = Expr::Literal {
-            value: crate::tokens::Object::Nil,
+            value: Object::Nil,
+            span: None,
         }; // Null by default
         if self.match_token(&vec![TokenType::Equal]) {
             initializer = self.expression()?;
         }
 
-        self.consume(
-            TokenType::Semicolon,
-            "Expect ';' after variable declaration.",
-        )?;
-        Ok(Stmt::Var { name, initializer })
+        self.consume(TokenType::Semicolon, "after variable declaration")?;
+        Ok(Stmt::Var {
+            name,
+            annotation,
+            initializer,
+        })
+    }
+
+    /// The type name after a `:`; `nil` is a keyword, so it's accepted besides identifiers.
+    fn type_annotation(&mut self) -> Result<Token, RLoxError> {
+        if self.match_token(&vec![TokenType::Nil]) {
+            return Ok(self.previous().expect("'nil' was just matched"));
+        }
+        self.consume(TokenType::Identifier, "after ':'")
     }
 
     fn declaration(&mut self) -> Result<Stmt, RLoxError> {
@@ -379,44 +645,107 @@ impl Parser {
         response
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, RLoxError> {
+    /// Parses the token stream into statements.
+    ///
+    /// Recovers from errors at statement boundaries so every error in the stream is returned.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<RLoxError>> {
+        let statements = self.parse_with_spans()?;
+        Ok(statements.into_iter().map(|(stmt, _)| stmt).collect())
+    }
+
+    /// Like [`parse()`], but pairs every statement with the byte range of the source it
+    /// was parsed from.
+    pub fn parse_with_spans(&mut self) -> Result<Vec<(Stmt, Range<usize>)>, Vec<RLoxError>> {
         let mut statements = vec![];
+        let mut errors = vec![];
         while !self.is_at_end() {
-            let response = self.declaration();
-            if let Err(err) = response {
-                err.print();
-                continue;
+            let start = self.peek().offset;
+            match self.declaration() {
+                Ok(statement) => {
+                    let last = self.previous().expect("a statement consumes tokens");
+                    statements.push((statement, start..last.offset + last.lexeme.len()));
+                }
+                Err(err) => errors.push(err),
             }
-            statements.push(response.unwrap());
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
         }
         Ok(statements)
     }
 
+    /// Parses the whole token stream as a single expression, without a trailing `;`.
+    ///
+    /// Errors are returned rather than printed so callers can fall back to [`parse()`].
+    pub fn parse_expression(&mut self) -> Result<Expr, RLoxError> {
+        let expr = self.expression()?;
+        if !self.is_at_end() {
+            return Err(self.unexpected("end of expression"));
+        }
+        Ok(expr)
+    }
+
     /// Resolves binary expressions by taking an operator and a resolver function.
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let expr = parser.resolve(
     ///     |parser| parser.term(),
     ///     vec![TokenType::Plus, TokenType::Minus]
     /// );
     /// ```
-    fn resolve<R>(&mut self, mut resolver: R, operators: Vec<TokenType>) -> Result<Expr, RLoxError>
+    fn resolve<R>(&mut self, resolver: R, operators: Vec<TokenType>) -> Result<Expr, RLoxError>
+    where
+        R: FnMut(&mut Parser) -> Result<Expr, RLoxError>,
+    {
+        self.resolve_with(resolver, operators, Self::binary)
+    }
+
+    /// Like [`resolve`](Self::resolve), with `build` making the node for each operator.
+    fn resolve_with<R>(
+        &mut self,
+        mut resolver: R,
+        operators: Vec<TokenType>,
+        build: fn(Expr, Token, Expr) -> Expr,
+    ) -> Result<Expr, RLoxError>
     where
         R: FnMut(&mut Parser) -> Result<Expr, RLoxError>,
     {
         let mut expr = resolver(self)?;
 
+        // every operator in a chain like `1 + 2 + 3` nests the tree one level deeper
+        let depth = self.depth;
         while self.match_token(&operators) {
             let operator = self.previous().unwrap();
-            let right = resolver(self)?;
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
+            let right = match self.nested(&mut resolver) {
+                Ok(right) => right,
+                Err(err) => {
+                    self.depth = depth;
+                    return Err(err);
+                }
             };
+            self.depth += 1;
+            expr = build(expr, operator, right);
         }
+        self.depth = depth;
 
         Ok(expr)
     }
+
+    fn binary(left: Expr, operator: Token, right: Expr) -> Expr {
+        Expr::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }
+    }
+
+    fn logical(left: Expr, operator: Token, right: Expr) -> Expr {
+        Expr::Logical {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        }
+    }
 }
@@ -0,0 +1,190 @@
+use std::borrow::Cow;
+
+use rustyline::{
+    completion::Completer,
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    validate::Validator,
+    Context, Helper,
+};
+
+use crate::{
+    highlight,
+    keywords::{Edition, KEYWORDS},
+};
+
+use super::COMMANDS;
+
+/// Line-editing support for the prompt: tab completion of keywords, globals and commands,
+/// and syntax highlighting of the line being typed.
+pub struct ReplHelper {
+    /// Names defined in the session's global environment, refreshed before every prompt.
+    globals: Vec<String>,
+    /// Only the keywords of this edition are offered; in older ones the newer words are
+    /// names.
+    edition: Edition,
+    color: bool,
+}
+
+impl ReplHelper {
+    pub fn new(edition: Edition, color: bool) -> Self {
+        Self {
+            globals: vec![],
+            edition,
+            color,
+        }
+    }
+
+    pub fn set_globals(&mut self, globals: Vec<String>) {
+        self.globals = globals;
+    }
+
+    /// Returns the sorted, deduplicated completions for the word ending at `pos`,
+    /// along with the byte offset that word starts at.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let start = line[..pos]
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map_or(0, |index| index + 1);
+        let word = &line[start..pos];
+
+        // the command name is the only thing completed on a `:` line
+        if line.starts_with(':') {
+            if start != 1 {
+                return (pos, vec![]);
+            }
+            let mut commands: Vec<String> = COMMANDS
+                .iter()
+                .filter(|command| command[1..].starts_with(word))
+                .map(|command| command.to_string())
+                .collect();
+            commands.sort();
+            return (0, commands);
+        }
+
+        if word.is_empty() {
+            return (pos, vec![]);
+        }
+
+        let mut names: Vec<String> = KEYWORDS
+            .iter()
+            .filter(|(_, (_, since))| *since <= self.edition)
+            .map(|(keyword, _)| *keyword)
+            .chain(self.globals.iter().map(String::as_str))
+            .filter(|name| name.starts_with(word))
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names.dedup();
+        (start, names)
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if !self.color || line.starts_with(':') {
+            return Cow::Borrowed(line);
+        }
+        Cow::Owned(highlight::paint(line, self.edition))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, _kind: CmdKind) -> bool {
+        self.color
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper(edition: Edition, globals: &[&str]) -> ReplHelper {
+        let mut helper = ReplHelper::new(edition, false);
+        helper.set_globals(globals.iter().map(|name| name.to_string()).collect());
+        helper
+    }
+
+    #[test]
+    fn keywords_complete_from_the_start_of_a_word() {
+        let helper = helper(Edition::Latest, &[]);
+        assert_eq!(helper.candidates("pr", 2), (0, vec!["print".to_string()]));
+        assert_eq!(
+            helper.candidates("var x = tr", 10),
+            (8, vec!["true".to_string()])
+        );
+        assert_eq!(helper.candidates("print ", 6), (6, vec![]));
+    }
+
+    #[test]
+    fn only_the_editions_keywords_are_offered() {
+        assert_eq!(
+            helper(Edition::Latest, &[]).candidates("a", 1).1,
+            ["and", "assert"]
+        );
+        assert_eq!(helper(Edition::Lox2015, &[]).candidates("a", 1).1, ["and"]);
+        // there `debug` is a name like any other
+        assert_eq!(
+            helper(Edition::Lox2015, &["debug"]).candidates("de", 2).1,
+            ["debug"]
+        );
+    }
+
+    #[test]
+    fn globals_complete_alongside_keywords_without_duplicates() {
+        let helper = helper(Edition::Latest, &["value", "var", "vast"]);
+        assert_eq!(
+            helper.candidates("print va", 8),
+            (
+                6,
+                vec!["value".to_string(), "var".to_string(), "vast".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn commands_complete_only_as_the_first_word() {
+        let helper = helper(Edition::Latest, &["tally"]);
+        assert_eq!(
+            helper.candidates(":to", 3),
+            (0, vec![":tokens".to_string()])
+        );
+        assert_eq!(helper.candidates(":load ta", 8), (8, vec![]));
+    }
+
+    #[test]
+    fn the_line_is_colored_for_the_edition() {
+        let line = "print assert;";
+        let colored = |edition| {
+            ReplHelper::new(edition, true)
+                .highlight(line, 0)
+                .into_owned()
+        };
+        assert_eq!(
+            colored(Edition::Lox2015),
+            "\x1b[35mprint\x1b[0m assert\x1b[36m;\x1b[0m"
+        );
+        assert_eq!(
+            colored(Edition::Latest),
+            "\x1b[35mprint\x1b[0m \x1b[35massert\x1b[0m\x1b[36m;\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,507 @@
+use std::{
+    fs,
+    io::{self, IsTerminal},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Result;
+use helper::ReplHelper;
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+
+use crate::{
+    ast::print::AstPrinter,
+    engine::RLoxEngine,
+    errors::RLoxError,
+    highlight,
+    options::{InterpreterOptions, OptionsError},
+    parser::Parser,
+    scanner::Scanner,
+    tokens::Object,
+};
+
+mod helper;
+
+/// The meta-commands understood by the prompt.
+pub const COMMANDS: &[&str] = &[
+    ":ast", ":env", ":explain", ":help", ":load", ":paste", ":reset", ":save", ":timing",
+    ":tokens", ":type",
+];
+
+/// A submission of Lox code, kept for `:save`.
+struct Submission {
+    source: String,
+    /// Whether the submission scanned and parsed, whether or not it then ran without error.
+    parsed: bool,
+}
+
+/// The interactive prompt.
+///
+/// Keeps a single engine alive for the whole session so bindings made on one line
+/// are visible on the next. Lines starting with `:` are meta-commands handled by the REPL itself.
+pub struct Repl {
+    engine: RLoxEngine,
+    /// Raised by Ctrl-C while a submission is being evaluated.
+    interrupted: Arc<AtomicBool>,
+    /// The file most recently passed to `:load`, reloaded by a bare `:load`.
+    last_loaded: Option<String>,
+    /// Whether to report how long each submission took, on stderr.
+    timing: bool,
+    /// Sets up the interpreter of every engine, including the one after `:reset`.
+    options: Box<dyn Fn() -> InterpreterOptions>,
+    /// How many results have been echoed; the next one is bound to `_<results + 1>`.
+    results: usize,
+    /// Whether `:reset` carries on numbering results instead of starting again from 1.
+    keep_result_numbers: bool,
+    /// How many submissions of Lox code have been run, which labels their errors.
+    submissions: usize,
+    /// How many lines of Lox code have been submitted, so lines are numbered across the
+    /// whole session.
+    lines: u64,
+    /// Every submission of Lox code this session, in order.
+    transcript: Vec<Submission>,
+}
+
+impl Repl {
+    pub fn new(
+        timing: bool,
+        options: impl Fn() -> InterpreterOptions + 'static,
+    ) -> Result<Self, OptionsError> {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let engine = Self::new_engine(&options, &interrupted)?;
+        Ok(Self {
+            engine,
+            interrupted,
+            last_loaded: None,
+            timing,
+            options: Box::new(options),
+            results: 0,
+            keep_result_numbers: false,
+            submissions: 0,
+            lines: 0,
+            transcript: vec![],
+        })
+    }
+
+    /// Makes `:reset` carry on numbering results instead of starting again from `[1]`.
+    /// The old `_1`, `_2`, ... bindings are gone either way.
+    pub fn keep_result_numbers(&mut self, keep: bool) {
+        self.keep_result_numbers = keep;
+    }
+
+    /// Returns true for the bindings the REPL makes for echoed results: `_` and `_1`, `_2`, ...
+    fn is_result_name(name: &str) -> bool {
+        name.strip_prefix('_')
+            .is_some_and(|number| number.chars().all(|digit| digit.is_ascii_digit()))
+    }
+
+    fn new_engine(
+        options: &dyn Fn() -> InterpreterOptions,
+        interrupted: &Arc<AtomicBool>,
+    ) -> Result<RLoxEngine, OptionsError> {
+        RLoxEngine::with_options(options().interrupt_flag(interrupted.clone()))
+    }
+
+    /// Runs the prompt until `quit` or Ctrl-D.
+    ///
+    /// Ctrl-C at the prompt discards the current line; Ctrl-C during evaluation
+    /// stops the interpreter at the next statement and returns to the prompt.
+    pub fn run(&mut self) -> Result<()> {
+        let interrupted = self.interrupted.clone();
+        ctrlc::set_handler(move || interrupted.store(true, Ordering::Relaxed))?;
+
+        let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+        editor.set_helper(Some(ReplHelper::new(
+            self.engine.edition(),
+            highlight::color_enabled(),
+        )));
+        loop {
+            if let Some(helper) = editor.helper_mut() {
+                helper.set_globals(self.globals());
+            }
+
+            let input = match editor.readline("> ") {
+                Ok(input) => input.trim().to_string(),
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            if input.is_empty() {
+                continue;
+            }
+            editor.add_history_entry(&input)?;
+
+            if input == "quit" {
+                break;
+            }
+
+            self.interrupted.store(false, Ordering::Relaxed);
+
+            if input == ":paste" {
+                if let Some(block) = Self::read_paste(&mut editor)? {
+                    self.eval(block);
+                }
+                continue;
+            }
+
+            if input.starts_with(':') {
+                self.run_command(&input);
+                continue;
+            }
+
+            // a bracketed paste arrives as one multi-line submission
+            self.eval(input);
+        }
+        Ok(())
+    }
+
+    /// Reads lines until a lone `.` and returns them as one block, for terminals without
+    /// bracketed paste. Ctrl-C abandons the block.
+    fn read_paste(editor: &mut Editor<ReplHelper, DefaultHistory>) -> Result<Option<String>> {
+        println!("Paste mode: finish with a line containing only '.'");
+        let mut block = String::new();
+        loop {
+            match editor.readline("") {
+                Ok(line) if line.trim() == "." => break,
+                Ok(line) => {
+                    block.push_str(&line);
+                    block.push('\n');
+                }
+                Err(ReadlineError::Interrupted) => return Ok(None),
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Some(block))
+    }
+
+    /// The names bound in the global environment.
+    fn globals(&self) -> Vec<String> {
+        self.engine
+            .globals()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Runs a submission of Lox code, echoing the value if it was an expression.
+    ///
+    /// Echoed values are numbered, `[3] = 42`, and bound both to `_3` and to `_` so later
+    /// lines can build on them; nil is neither echoed nor bound.
+    /// A multi-line submission runs as one unit. Lines are numbered across the session and
+    /// errors are labeled with the submission, so the third submission, spanning lines 4
+    /// and 5, reports `[<repl:3>, Line 5]` for an error on its second line.
+    fn eval(&mut self, input: String) {
+        self.submissions += 1;
+        let first_line = self.lines + 1;
+        self.lines += input.trim_end().lines().count().max(1) as u64;
+        let result = self.engine.eval_from_line(first_line, &input);
+        let parsed = !result.as_ref().is_err_and(|diagnostics| {
+            diagnostics.iter().any(|diagnostic| {
+                matches!(
+                    diagnostic.error,
+                    RLoxError::ScanError(..) | RLoxError::ParseError(..)
+                )
+            })
+        });
+        self.transcript.push(Submission {
+            source: input,
+            parsed,
+        });
+
+        match result {
+            Ok(Object::Nil) => {}
+            Ok(value) => {
+                self.results += 1;
+                println!("[{}] = {}", self.results, self.engine.inspect_value(&value));
+                self.engine
+                    .define(&format!("_{}", self.results), value.clone());
+                self.engine.define("_", value);
+            }
+            Err(diagnostics) => {
+                let source = format!("<repl:{}>", self.submissions);
+                diagnostics
+                    .into_iter()
+                    .for_each(|diagnostic| diagnostic.error.print_in(&source));
+            }
+        }
+        self.report_timing();
+    }
+
+    /// Reports how long the last submission took, `(took 12.3ms)`, if timing is on.
+    ///
+    /// The line goes to stderr so it never mixes with the program's output, and is dimmed
+    /// when stderr is a terminal.
+    fn report_timing(&mut self) {
+        // taken either way, so turning timing on doesn't report earlier submissions
+        let timings = self.engine.take_timings();
+        if !self.timing {
+            return;
+        }
+        if highlight::color_enabled() && io::stderr().is_terminal() {
+            eprintln!("\x1b[2m{}\x1b[0m", timings.took());
+        } else {
+            eprintln!("{}", timings.took());
+        }
+    }
+
+    /// Dispatches a `:command argument` line.
+    fn run_command(&mut self, input: &str) {
+        let (command, argument) = input.split_once(' ').unwrap_or((input, ""));
+        match command {
+            ":env" => self.env(argument.trim()),
+            ":ast" => self.ast(argument),
+            ":tokens" => self.tokens(argument),
+            ":type" => self.type_of(argument),
+            ":explain" => self.explain(argument),
+            ":load" => self.load(argument.trim()),
+            ":reset" => self.reset(argument.trim()),
+            ":save" => self.save(argument.trim(), false),
+            ":save!" => self.save(argument.trim(), true),
+            ":timing" => self.set_timing(argument.trim()),
+            ":help" => self.help(),
+            _ => println!("Unknown command {}", command),
+        }
+    }
+
+    /// Prints the bindings of the global environment as `name = value`, sorted by name.
+    ///
+    /// The result bindings `_`, `_1`, `_2`, ... are left out unless the argument is `all`.
+    fn env(&self, argument: &str) {
+        if !argument.is_empty() && argument != "all" {
+            println!("Usage: :env [all]");
+            return;
+        }
+
+        let entries = self
+            .engine
+            .globals()
+            .filter(|(name, _)| argument == "all" || !Self::is_result_name(name));
+        for (name, value) in entries {
+            println!("{} = {}", name, self.engine.format_value(value));
+        }
+    }
+
+    /// Executes a file against the session's engine.
+    ///
+    /// Relative paths resolve against the current working directory. Without a path
+    /// the most recently loaded file is loaded again.
+    fn load(&mut self, path: &str) {
+        let path = if path.is_empty() {
+            let Some(last_loaded) = self.last_loaded.clone() else {
+                println!("Usage: :load <path>");
+                return;
+            };
+            last_loaded
+        } else {
+            path.to_string()
+        };
+
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => {
+                println!("Could not read {}: {}", path, err);
+                return;
+            }
+        };
+        self.last_loaded = Some(path.clone());
+
+        let result = self.engine.run_source(&path, &source);
+        if let Err(diagnostics) = result {
+            diagnostics
+                .into_iter()
+                .for_each(|diagnostic| diagnostic.print());
+        }
+        self.report_timing();
+    }
+
+    /// Turns reporting how long each submission took on or off, for the rest of the
+    /// session, or without an argument says whether it's on.
+    fn set_timing(&mut self, argument: &str) {
+        match argument {
+            "on" => self.timing = true,
+            "off" => self.timing = false,
+            "" => {}
+            _ => {
+                println!("Usage: :timing [on|off]");
+                return;
+            }
+        }
+        println!("Timing is {}.", if self.timing { "on" } else { "off" });
+    }
+
+    /// Lists the commands, along with the settings they change.
+    fn help(&self) {
+        println!(":ast <code>          Print the syntax tree of code without running it");
+        println!(":env [all]           List the variables, with `all` also the results");
+        println!(":explain <expression> Evaluate the expression one step at a time");
+        println!(":help                Show this list");
+        println!(":load [path]         Run a file, or the last one loaded");
+        println!(":paste               Run the lines up to a lone '.' as one unit");
+        println!(":reset [hard]        Discard every binding");
+        println!(":save[!] <path>      Write this session's code to a file");
+        println!(
+            ":timing [on|off]     Report how long each submission takes (now {})",
+            if self.timing { "on" } else { "off" }
+        );
+        println!(":tokens <code>       Print the tokens code scans into");
+        println!(":type <expression>   Print the type of the expression's value");
+        println!("quit                 Leave the prompt");
+    }
+
+    /// Writes the Lox code submitted this session to `path`, creating its directory if
+    /// needed. Submissions that didn't parse are kept as comments.
+    ///
+    /// An existing file is only replaced when `overwrite` is set, as by `:save!`.
+    fn save(&self, path: &str, overwrite: bool) {
+        if path.is_empty() {
+            println!("Usage: :save <path>");
+            return;
+        }
+        let path = Path::new(path);
+        if path.exists() && !overwrite {
+            println!(
+                "{} already exists; use :save! to overwrite it.",
+                path.display()
+            );
+            return;
+        }
+
+        let mut source = String::new();
+        for submission in &self.transcript {
+            for line in submission.source.trim_end().lines() {
+                if !submission.parsed {
+                    source.push_str("// ");
+                }
+                source.push_str(line);
+                source.push('\n');
+            }
+        }
+        let written = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+            _ => Ok(()),
+        }
+        .and_then(|_| fs::write(path, source));
+        match written {
+            Ok(()) => println!(
+                "Saved {} submissions to {}.",
+                self.transcript.len(),
+                path.display()
+            ),
+            Err(err) => println!("Could not write {}: {}", path.display(), err),
+        }
+    }
+
+    /// Replaces the session's engine with a fresh one.
+    ///
+    /// `:reset hard` additionally forgets the file remembered by `:load`.
+    fn reset(&mut self, argument: &str) {
+        match argument {
+            "" => {}
+            "hard" => self.last_loaded = None,
+            _ => {
+                println!("Usage: :reset [hard]");
+                return;
+            }
+        }
+
+        self.engine = Self::new_engine(&self.options, &self.interrupted)
+            .expect("the same options already built the first engine");
+        if !self.keep_result_numbers {
+            self.results = 0;
+        }
+        println!("Environment reset.");
+    }
+
+    /// Scans `code` in the session's edition.
+    fn scan(&self, code: &str) -> Scanner {
+        let mut scanner = Scanner::new(code.to_string());
+        scanner.edition(self.engine.edition());
+        scanner.scan_tokens();
+        scanner
+    }
+
+    /// Evaluates the expression `code` and prints the name of its type.
+    ///
+    /// The expression really runs against the session's environment, so any side effects
+    /// it has are kept.
+    fn type_of(&mut self, code: &str) {
+        let scanner = self.scan(code);
+        if !scanner.errors.is_empty() {
+            scanner.errors.into_iter().for_each(RLoxError::print);
+            return;
+        }
+
+        let expr = match Parser::new(scanner.tokens).parse_expression() {
+            Ok(expr) => expr,
+            Err(_) if code.trim_end().ends_with(';') => {
+                println!(":type takes an expression, not a statement. Try dropping the ';'.");
+                return;
+            }
+            Err(err) => {
+                err.print();
+                return;
+            }
+        };
+
+        match self.engine.evaluate(&expr) {
+            Ok(value) => println!("{}", value.type_name()),
+            Err(err) => err.print(),
+        }
+    }
+
+    /// Evaluates the expression `code`, printing it after every step that changes it:
+    /// `(1 + 2) * 3`, `→ 3 * 3`, `→ 9`.
+    ///
+    /// Like `:type`, the expression really runs against the session's environment.
+    fn explain(&mut self, code: &str) {
+        match self.engine.explain(code) {
+            Ok(steps) => println!("{}", steps.join("\n→ ")),
+            Err(_) if code.trim_end().ends_with(';') => {
+                println!(":explain takes an expression, not a statement. Try dropping the ';'.")
+            }
+            Err(diagnostics) => diagnostics
+                .into_iter()
+                .for_each(|diagnostic| diagnostic.error.print()),
+        }
+    }
+
+    /// Prints the syntax tree of `code` without executing it.
+    ///
+    /// `code` may be a bare expression (`1 + 2 * 3`) or a sequence of statements.
+    fn ast(&self, code: &str) {
+        let scanner = self.scan(code);
+        if !scanner.errors.is_empty() {
+            scanner.errors.into_iter().for_each(RLoxError::print);
+            return;
+        }
+
+        if let Ok(expr) = Parser::new(scanner.tokens.clone()).parse_expression() {
+            match AstPrinter::print_expr(&expr) {
+                Ok(tree) => println!("{}", tree),
+                Err(err) => err.print(),
+            }
+            return;
+        }
+
+        match Parser::new(scanner.tokens).parse() {
+            Ok(statements) => match AstPrinter::print_program(&statements) {
+                Ok(tree) => println!("{}", tree),
+                Err(err) => err.print(),
+            },
+            Err(errors) => errors.into_iter().for_each(RLoxError::print),
+        }
+    }
+
+    /// Prints the token stream of `code`, one token per line.
+    fn tokens(&self, code: &str) {
+        let scanner = self.scan(code);
+        scanner.errors.into_iter().for_each(RLoxError::print);
+        for token in scanner.tokens {
+            println!("{}", token);
+        }
+    }
+}
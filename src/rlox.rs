@@ -1,77 +1,655 @@
-use std::{
-    fs,
-    io::{self, Write},
-};
+use std::{fs, process};
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::{
-    environment::Environment, errors::RLoxError, interpreter::Interpreter, scanner::Scanner,
+    ast::{
+        dot::DotPrinter,
+        format::Formatter,
+        json,
+        lint::{LintRule, Linter},
+        print::AstPrinter,
+        rpn::RpnPrinter,
+        stmt::Stmt,
+        typecheck::TypeChecker,
+        validate::AstValidator,
+    },
+    cache, canonical,
+    engine::RLoxEngine,
+    errors::{Diagnostic, RLoxError},
+    highlight,
+    keywords::Edition,
+    options::{InterpreterOptions, OptionsError},
+    repl::Repl,
+    scanner::Scanner,
+    timings::Timings,
+    tokens::Object,
+    watch,
 };
 
+/// How `--ast` renders the syntax tree.
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum AstFormat {
+    /// Parenthesized prefix notation, e.g. `(+ 1 2)`
+    #[default]
+    Tree,
+    /// Reverse Polish notation, e.g. `1 2 +`
+    Rpn,
+    /// A JSON array of statements whose nodes carry a `type` tag
+    Json,
+    /// A Graphviz digraph, e.g. for `dot -Tsvg`
+    Dot,
+}
+
+impl AstFormat {
+    /// Renders every statement of a program; the text formats put one statement per line.
+    pub fn render(self, stmts: &[Stmt]) -> Result<String, RLoxError> {
+        match self {
+            AstFormat::Tree => AstPrinter::print_program(stmts),
+            AstFormat::Rpn => RpnPrinter::print_program(stmts),
+            AstFormat::Json => Ok(serde_json::to_string_pretty(stmts)
+                .expect("syntax trees only hold JSON-representable values")),
+            AstFormat::Dot => DotPrinter::print_program(stmts),
+        }
+    }
+}
+
+/// How `--tokens` and `--ast` print.
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// For people: one token per line, or the tree as `--ast-format` says
+    #[default]
+    Text,
+    /// JSON in the vocabulary of Crafting Interpreters, e.g. to diff against jlox
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print Lox files back as canonically formatted source
+    Fmt(FmtArgs),
+    /// Warn about suspicious code in Lox files without running them
+    Lint(LintArgs),
+}
+
+#[derive(Args)]
+struct FmtArgs {
+    /// Lox files to format
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Rewrite the files in place instead of printing them
+    #[arg(long)]
+    write: bool,
+
+    /// Exit with 1 if any file isn't formatted, listing them, instead of printing them
+    #[arg(long, conflicts_with = "write")]
+    check: bool,
+}
+
+#[derive(Args)]
+struct LintArgs {
+    /// Lox files to lint
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Don't run the given rule; may be repeated
+    #[arg(long, value_enum, value_name = "RULE")]
+    allow: Vec<LintRule>,
+}
+
 #[derive(Parser)]
 #[command(name = "rLox")]
 #[command(version = "1.0")]
 #[command(about = "A Lox interpreter written in Rust", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct RLox {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Lox files to run, in order, sharing one environment
+    files: Vec<String>,
+
     /// Path to the Lox file
+    #[arg(long, num_args = 1..)]
+    path: Vec<String>,
+
+    /// Print the syntax tree of the file instead of running it
+    #[arg(long)]
+    ast: bool,
+
+    /// How to print the syntax tree; implies --ast
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    ast_format: Option<AstFormat>,
+
+    /// Print the tokens of the file(s) instead of running them
+    #[arg(long, conflicts_with_all = ["ast", "ast_format"])]
+    tokens: bool,
+
+    /// How to print --tokens and --ast; json uses the names of Crafting Interpreters and
+    /// flags what rlox adds to the language
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "ast_format")]
+    format: Option<DumpFormat>,
+
+    /// Report scan and parse errors in the file(s) without running anything
+    #[arg(long, conflicts_with_all = ["ast", "ast_format", "tokens"])]
+    check: bool,
+
+    /// Print the file(s) with syntax highlighting instead of running them
+    #[arg(long, conflicts_with_all = ["ast", "ast_format", "tokens", "check"])]
+    highlight: bool,
+
+    /// Parse the file(s) and cache the syntax trees next to them as .loxc files, which
+    /// later runs use while the source is unchanged
+    #[arg(long, conflicts_with_all = ["ast", "ast_format", "tokens", "check", "highlight"])]
+    compile: bool,
+
+    /// Re-run the file(s) with a fresh interpreter whenever they change
+    #[arg(long, conflicts_with_all = ["ast", "ast_format", "tokens", "check", "highlight", "compile"])]
+    watch: bool,
+
+    /// Run a syntax tree in the form printed by --ast-format=json, skipping scanning and parsing
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["files", "path", "ast", "ast_format", "tokens", "check", "highlight", "compile", "watch"]
+    )]
+    run_ast: Option<String>,
+
+    /// Evaluate an expression, printing it after every step of the evaluation
+    #[arg(
+        long,
+        value_name = "EXPRESSION",
+        allow_hyphen_values = true,
+        conflicts_with_all = ["files", "path", "ast", "ast_format", "tokens", "check", "highlight", "compile", "watch", "run_ast"]
+    )]
+    explain: Option<String>,
+
+    /// Print how long scanning, parsing and interpreting took to stderr
+    #[arg(long)]
+    time: bool,
+
+    /// Print what the program did to stderr once it ends: statements executed, values
+    /// created by type, output bytes and errors recovered from
+    #[arg(long)]
+    stats: bool,
+
+    /// Stop a program with a runtime error once it has executed N statements
+    #[arg(long, value_name = "N")]
+    max_steps: Option<u64>,
+
+    /// Fail with a runtime error when a while loop's body would run more than N times in
+    /// a row
+    #[arg(long, value_name = "N")]
+    max_loop_iterations: Option<u64>,
+
+    /// Fail with a runtime error instead of building a string longer than N bytes
+    #[arg(long, value_name = "N")]
+    max_string_bytes: Option<usize>,
+
+    /// Print numbers with a fractional part rounded to N decimal places
+    #[arg(long, value_name = "N")]
+    float_precision: Option<usize>,
+
+    /// Skip `debug` statements without evaluating them
+    #[arg(long)]
+    release_strip_debug: bool,
+
+    /// Skip `assert` statements without evaluating them
+    #[arg(long)]
+    disable_asserts: bool,
+
+    /// Allow programs to redefine built-in globals
+    #[arg(long)]
+    allow_shadow_natives: bool,
+
+    /// After a runtime error, carry on with the next top-level statement; exits with 70 at
+    /// the end if anything failed
+    #[arg(long)]
+    keep_going: bool,
+
+    /// Print the value of the expression statement the program ends with, if any
     #[arg(long)]
-    path: Option<String>,
+    print_result: bool,
+
+    /// Flush program output after every print instead of when the program ends
+    #[arg(long)]
+    unbuffered: bool,
+
+    /// Run untrusted programs: nothing outside the interpreter is reachable; requires
+    /// --max-steps
+    #[arg(long)]
+    sandbox: bool,
+
+    /// The language edition; under 2015, words reserved since (assert, debug) are names
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "EDITION",
+        default_value_t = Edition::Latest
+    )]
+    edition: Edition,
+
+    /// Keep numbering REPL results after :reset instead of starting again from 1
+    #[arg(long)]
+    keep_result_numbers: bool,
+
+    /// Check the files for type errors before running them, and warn about any found
+    #[arg(long)]
+    typecheck: bool,
+
+    /// Report the type errors --typecheck finds as errors and run nothing; implies --typecheck
+    #[arg(long)]
+    strict_types: bool,
 }
 
 impl RLox {
     /// initiate
     pub fn init(self) -> Result<()> {
-        if self.path.is_some() {
-            // path has been passed
-            return self.run_file();
+        let paths: Vec<String> = self.path.iter().chain(&self.files).cloned().collect();
+        let ast = self.ast || self.ast_format.is_some();
+        if paths.is_empty()
+            && (ast || self.tokens || self.check || self.highlight || self.compile || self.watch)
+        {
+            bail!("--ast, --tokens, --check, --highlight, --compile and --watch need at least one file.");
+        }
+        if self.format.is_some() && !ast && !self.tokens {
+            bail!("--format needs --tokens or --ast.");
+        }
+
+        self.options().build()?;
+
+        match &self.command {
+            Some(Command::Fmt(args)) => return Self::format_files(args, self.edition),
+            Some(Command::Lint(args)) => return Self::lint_files(args, self.edition),
+            None => {}
+        }
+
+        if let Some(expression) = &self.explain {
+            self.explain_expression(expression)
+        } else if let Some(path) = &self.run_ast {
+            self.run_json(path)
+        } else if self.check {
+            Self::check_files(&paths, self.edition)
+        } else if self.highlight {
+            Self::print_highlighted(&paths, self.edition)
+        } else if self.tokens {
+            Self::print_tokens(&paths, self.format.unwrap_or_default(), self.edition)
+        } else if ast && self.format == Some(DumpFormat::Json) {
+            Self::print_canonical_ast(&paths, self.edition)
+        } else if ast {
+            Self::print_ast(&paths, self.ast_format.unwrap_or_default(), self.edition)
+        } else if self.compile {
+            Self::compile_files(&paths, self.edition)
+        } else if self.watch {
+            watch::watch(&paths, self.time, || self.options())
+        } else if !paths.is_empty() {
+            // paths have been passed
+            self.run_files(&paths)
         } else {
             // interactive mode
-            return self.run_prompt();
+            self.run_prompt()
         }
     }
 
-    fn run_file(self) -> Result<()> {
-        // read file
-        let file_bytes = fs::read_to_string(self.path.unwrap())?;
-        if let Err(err) = Self::run(file_bytes) {
-            err.print();
+    /// Runs the files in order in one shared engine, exiting with the appropriate
+    /// status code if any of them fails.
+    fn run_files(&self, paths: &[String]) -> Result<()> {
+        let sources = Self::read_sources(paths)?;
+        if self.typecheck || self.strict_types {
+            self.typecheck_files(&sources);
+        }
+        let mut engine = Self::file_engine(self.options())?;
+        let result = engine.run_sources(&Self::as_named(&sources));
+        if self.time {
+            engine.take_timings().report();
+            eprintln!("{}", engine.take_metrics());
+        }
+        if self.stats {
+            eprintln!("{}", engine.take_stats());
+        }
+        match result {
+            Ok(value) => self.print_result(&engine, value),
+            Err(diagnostics) => Self::exit_with(diagnostics, paths.len() > 1),
         }
         Ok(())
     }
 
-    fn run_prompt(self) -> Result<()> {
-        loop {
-            print!("> ");
-            io::stdout().flush()?;
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            let input = input.trim().to_string();
-
-            if input == "quit" {
-                break;
+    /// Checks the files for type errors in order, as the one program they run as. What is
+    /// found is printed as warnings, or under `--strict-types` reported as errors, exiting
+    /// with 65 before anything runs.
+    fn typecheck_files(&self, sources: &[(&String, String)]) {
+        let mut checker = TypeChecker::new(self.strict_types);
+        let mut errors = vec![];
+        for (path, source) in sources {
+            // scan and parse errors are reported when the files run
+            let Ok(statements) =
+                RLoxEngine::parse_in_edition(source.clone(), self.edition, &mut Timings::default())
+            else {
+                continue;
+            };
+            match checker.check_program(&statements) {
+                Ok(issues) if self.strict_types => errors.extend(
+                    issues
+                        .into_iter()
+                        .map(|issue| Diagnostic::new(path, issue.into_error())),
+                ),
+                Ok(issues) => issues.iter().for_each(|issue| issue.print_in(path)),
+                Err(err) => errors.push(Diagnostic::new(path, err)),
             }
+        }
+        if !errors.is_empty() {
+            Self::exit_with(errors, sources.len() > 1);
+        }
+    }
 
-            let _ = Self::run(input); // todo
+    /// Evaluates `expression`, printing every step of it, and exits with the appropriate
+    /// status code if it is malformed or fails.
+    fn explain_expression(&self, expression: &str) -> Result<()> {
+        let mut engine = RLoxEngine::with_options(self.options())?;
+        match engine.explain(expression) {
+            Ok(steps) => println!("{}", steps.join("\n→ ")),
+            Err(diagnostics) => Self::exit_with(diagnostics, false),
         }
         Ok(())
     }
 
-    fn run(input: String) -> Result<(), RLoxError> {
-        // lexing
-        let mut scanner = Scanner::new(input);
-        scanner.scan_tokens();
+    /// Deserializes a syntax tree from JSON and interprets it, exiting with the appropriate
+    /// status code if it is malformed or fails.
+    fn run_json(&self, path: &str) -> Result<()> {
+        let source = fs::read_to_string(path)?;
+        let statements = match json::from_json(&source) {
+            Ok(statements) => statements,
+            // the line is one of the JSON document
+            Err(err) => Self::exit_with(vec![Diagnostic::new(path, err)], true),
+        };
+        // from here on lines are those of the source the tree was parsed from
+        if let Err(err) = AstValidator::validate_program(&statements) {
+            Self::exit_with(vec![Diagnostic::new(path, err)], true);
+        }
 
-        // parsing
-        let mut parser = crate::parser::Parser::new(scanner.tokens);
-        let expressions = parser.parse()?;
+        let mut engine = RLoxEngine::with_options(self.options())?;
+        let result = engine.run_program(path, statements);
+        if self.time {
+            engine.take_timings().report();
+            eprintln!("{}", engine.take_metrics());
+        }
+        if self.stats {
+            eprintln!("{}", engine.take_stats());
+        }
+        match result {
+            Ok(value) => self.print_result(&engine, value),
+            Err(diagnostics) => Self::exit_with(diagnostics, false),
+        }
+        Ok(())
+    }
 
-        // interpreting
-        let mut interpreter = Interpreter {
-            environment: Environment::new(),
-        };
-        interpreter.interpret(expressions)?;
+    /// Prints the value a program ended with, quoted if it's a string, under
+    /// `--print-result`.
+    fn print_result(&self, engine: &RLoxEngine, value: Option<Object>) {
+        if let (true, Some(value)) = (self.print_result, value) {
+            println!("{}", engine.inspect_value(&value));
+        }
+    }
+
+    /// Reads every file up front, so a missing file is reported before anything runs.
+    pub fn read_sources(paths: &[String]) -> Result<Vec<(&String, String)>> {
+        paths
+            .iter()
+            .map(|path| Ok((path, fs::read_to_string(path)?)))
+            .collect()
+    }
+
+    /// Borrows the sources returned by [`read_sources`](Self::read_sources) in the form
+    /// [`RLoxEngine::run_sources`] takes.
+    pub fn as_named<'a>(sources: &'a [(&String, String)]) -> Vec<(&'a str, &'a str)> {
+        sources
+            .iter()
+            .map(|(path, source)| (path.as_str(), source.as_str()))
+            .collect()
+    }
+
+    /// The interpreter options the flags ask for.
+    fn options(&self) -> InterpreterOptions {
+        let mut options = InterpreterOptions::default();
+        if let Some(steps) = self.max_steps {
+            options = options.max_steps(steps);
+        }
+        if let Some(iterations) = self.max_loop_iterations {
+            options = options.max_loop_iterations(iterations);
+        }
+        if let Some(bytes) = self.max_string_bytes {
+            options = options.max_value_bytes(bytes);
+        }
+        if let Some(places) = self.float_precision {
+            options = options.float_precision(places);
+        }
+        options = options
+            .strip_debug(self.release_strip_debug)
+            .disable_asserts(self.disable_asserts)
+            .allow_shadow_natives(self.allow_shadow_natives)
+            .keep_going(self.keep_going)
+            .unbuffered(self.unbuffered)
+            .sandbox(self.sandbox)
+            .edition(self.edition);
+        options
+    }
+
+    /// An engine for running files, which uses their `.loxc` caches.
+    pub fn file_engine(options: InterpreterOptions) -> Result<RLoxEngine, OptionsError> {
+        let mut engine = RLoxEngine::with_options(options)?;
+        engine.use_cache(true);
+        Ok(engine)
+    }
+
+    /// Parses every file and writes its syntax tree to the `.loxc` cache next to it.
+    ///
+    /// Exits with 65 without writing anything if any file has errors.
+    fn compile_files(paths: &[String], edition: Edition) -> Result<()> {
+        let sources = Self::read_sources(paths)?;
+        let mut programs = vec![];
+        let mut errors = vec![];
+        for (path, source) in sources {
+            match RLoxEngine::parse_in_edition(source.clone(), edition, &mut Timings::default()) {
+                Ok(statements) => programs.push((path, source, statements)),
+                Err(file_errors) => errors.extend(Diagnostic::all(path, file_errors)),
+            }
+        }
+        if !errors.is_empty() {
+            Self::exit_with(errors, paths.len() > 1);
+        }
+
+        for (path, source, statements) in programs {
+            cache::write(path, &source, edition, &statements)?;
+        }
+        Ok(())
+    }
+
+    /// Scans and parses every file, reporting all errors without executing anything.
+    ///
+    /// Exits with 65 if any file had errors.
+    fn check_files(paths: &[String], edition: Edition) -> Result<()> {
+        let mut code = 0;
+        for path in paths {
+            let source = fs::read_to_string(path)?;
+            match RLoxEngine::parse_in_edition(source, edition, &mut Timings::default()) {
+                Ok(_) if paths.len() > 1 => println!("OK: {}", path),
+                Ok(_) => {}
+                Err(errors) => {
+                    code = errors.first().map_or(code, RLoxError::exit_code);
+                    errors.into_iter().for_each(|err| err.print_in(path));
+                }
+            }
+        }
+
+        if code != 0 {
+            process::exit(code);
+        }
         Ok(())
     }
+
+    /// Prints every statement of the files as a syntax tree, one per line, without running anything.
+    fn print_ast(paths: &[String], format: AstFormat, edition: Edition) -> Result<()> {
+        for path in paths {
+            let source = fs::read_to_string(path)?;
+            let statements =
+                match RLoxEngine::parse_in_edition(source, edition, &mut Timings::default()) {
+                    Ok(statements) => statements,
+                    Err(errors) => Self::exit_with(Diagnostic::all(path, errors), paths.len() > 1),
+                };
+
+            match format.render(&statements) {
+                Ok(tree) if tree.is_empty() => {}
+                Ok(tree) => println!("{}", tree),
+                Err(err) => Self::exit_with(vec![Diagnostic::new(path, err)], paths.len() > 1),
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the tokens of every file, without parsing or running anything. Exits with 65
+    /// on a scan error.
+    fn print_tokens(paths: &[String], format: DumpFormat, edition: Edition) -> Result<()> {
+        for path in paths {
+            let mut scanner = Scanner::new(fs::read_to_string(path)?);
+            scanner.edition(edition);
+            scanner.scan_tokens();
+            if !scanner.errors.is_empty() {
+                Self::exit_with(Diagnostic::all(path, scanner.errors), paths.len() > 1);
+            }
+            match format {
+                DumpFormat::Text => scanner
+                    .tokens
+                    .iter()
+                    .for_each(|token| println!("{}", token)),
+                DumpFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&canonical::tokens(&scanner.tokens))?
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the syntax tree of every file as [`canonical`] JSON, without running anything.
+    fn print_canonical_ast(paths: &[String], edition: Edition) -> Result<()> {
+        for path in paths {
+            let source = fs::read_to_string(path)?;
+            let statements =
+                match RLoxEngine::parse_in_edition(source, edition, &mut Timings::default()) {
+                    Ok(statements) => statements,
+                    Err(errors) => Self::exit_with(Diagnostic::all(path, errors), paths.len() > 1),
+                };
+            match canonical::program(&statements) {
+                Ok(tree) => println!("{}", serde_json::to_string_pretty(&tree)?),
+                Err(err) => Self::exit_with(vec![Diagnostic::new(path, err)], paths.len() > 1),
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats every file, printing the result, rewriting the file or checking it
+    /// depending on `args`. Nothing is written for a file with scan or parse errors.
+    fn format_files(args: &FmtArgs, edition: Edition) -> Result<()> {
+        let mut unformatted = false;
+        for path in &args.files {
+            let source = fs::read_to_string(path)?;
+            let formatted = match Formatter::format_source(&source, edition) {
+                Ok(formatted) => formatted,
+                Err(errors) => Self::exit_with(Diagnostic::all(path, errors), true),
+            };
+
+            if args.check {
+                if formatted != source {
+                    println!("Not formatted: {}", path);
+                    unformatted = true;
+                }
+            } else if args.write {
+                if formatted != source {
+                    fs::write(path, formatted)?;
+                }
+            } else {
+                print!("{}", formatted);
+            }
+        }
+
+        if unformatted {
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    /// Lints every file, printing the warnings of each.
+    ///
+    /// Exits with 1 if there were any warnings, or with 65 if a file doesn't parse.
+    fn lint_files(args: &LintArgs, edition: Edition) -> Result<()> {
+        let mut warned = false;
+        for path in &args.files {
+            let source = fs::read_to_string(path)?;
+            let statements =
+                match RLoxEngine::parse_in_edition(source, edition, &mut Timings::default()) {
+                    Ok(statements) => statements,
+                    Err(errors) => Self::exit_with(Diagnostic::all(path, errors), true),
+                };
+
+            match Linter::lint_program(&statements, &args.allow) {
+                Ok(warnings) => {
+                    warned |= !warnings.is_empty();
+                    warnings.iter().for_each(|warning| warning.print_in(path));
+                }
+                Err(err) => Self::exit_with(vec![Diagnostic::new(path, err)], true),
+            }
+        }
+
+        if warned {
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    /// Prints the files with ANSI colors for each kind of token; bad spans are underlined.
+    ///
+    /// Falls back to the plain text when colors are disabled.
+    fn print_highlighted(paths: &[String], edition: Edition) -> Result<()> {
+        let color = highlight::color_enabled();
+        for path in paths {
+            let source = fs::read_to_string(path)?;
+            if color {
+                print!("{}", highlight::paint(&source, edition));
+            } else {
+                print!("{}", source);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reports the errors and exits with the status code of the first one.
+    fn exit_with(diagnostics: Vec<Diagnostic>, name_files: bool) -> ! {
+        let code = diagnostics
+            .first()
+            .map_or(0, |diagnostic| diagnostic.error.exit_code());
+        Self::report(diagnostics, name_files);
+        process::exit(code)
+    }
+
+    /// Prints the errors, prefixed with the file name when `name_files` is set.
+    pub fn report(diagnostics: Vec<Diagnostic>, name_files: bool) {
+        for diagnostic in diagnostics {
+            if name_files {
+                diagnostic.print();
+            } else {
+                diagnostic.error.print();
+            }
+        }
+    }
+
+    fn run_prompt(self) -> Result<()> {
+        let time = self.time;
+        let keep_result_numbers = self.keep_result_numbers;
+        let mut repl = Repl::new(time, move || self.options())?;
+        repl.keep_result_numbers(keep_result_numbers);
+        repl.run()
+    }
 }
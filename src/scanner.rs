@@ -1,50 +1,104 @@
-use std::iter::Peekable;
-use std::str::Chars;
-
-use crate::errors::rlox_error;
-use crate::keywords::KEYWORDS;
+use crate::errors::RLoxError;
+use crate::keywords::{self, Edition};
+use crate::number::Number;
+use crate::symbol::{Interner, Symbol};
 use crate::tokens::{Object, Token, TokenType};
 
+/// The byte order mark some editors start UTF-8 files with.
+const BOM: char = '\u{feff}';
+
 pub struct Scanner {
     pub start: u64,
+    /// Byte offset of the next character in `source`.
     pub current: u64,
     pub line: u64,
     pub source: String,
-    pub chars: Peekable<Chars<'static>>, // Use Peekable iterator
     pub tokens: Vec<Token>,
+    pub errors: Vec<RLoxError>,
+    /// Emit comments as [`TokenType::Comment`] tokens instead of discarding them.
+    preserve_comments: bool,
+    /// Decides which words are keywords.
+    edition: Edition,
+    /// Where identifiers and keywords are interned.
+    interner: Interner,
 }
 
 impl Scanner {
     /// Generates a new Scanner.
+    ///
+    /// A byte order mark at the very start is skipped; offsets still count its bytes, so
+    /// they stay offsets into `source`.
     pub fn new(source: String) -> Self {
-        let chars = Box::leak(source.clone().into_boxed_str())
-            .chars()
-            .peekable();
+        let skipped = if source.starts_with(BOM) {
+            BOM.len_utf8() as u64
+        } else {
+            0
+        };
         Self {
-            start: 0,
-            current: 0,
+            start: skipped,
+            current: skipped,
             line: 1,
-            chars,
-            source: source,
+            source,
             tokens: vec![],
+            errors: vec![],
+            preserve_comments: false,
+            edition: Edition::default(),
+            interner: Interner::new(),
         }
     }
 
+    /// Scans only the keywords of `edition`, so words reserved later stay identifiers.
+    /// The latest edition by default.
+    pub fn edition(&mut self, edition: Edition) {
+        self.edition = edition;
+    }
+
+    /// Makes the scanner emit every comment as a [`TokenType::Comment`] token holding its
+    /// full text, for tools like the formatter. The parser ignores them either way.
+    pub fn preserve_comments(&mut self, preserve: bool) {
+        self.preserve_comments = preserve;
+    }
+
+    /// Interns identifiers and keywords into `interner`, e.g. that of the environment the
+    /// tokens will run in, instead of a fresh one. Get it back with
+    /// [`take_interner`](Self::take_interner).
+    pub fn interner(&mut self, interner: Interner) {
+        self.interner = interner;
+    }
+
+    /// Returns the interner, with the names scanned so far added.
+    pub fn take_interner(&mut self) -> Interner {
+        std::mem::take(&mut self.interner)
+    }
+
+    /// Numbers lines from `line` instead of 1, e.g. to continue the line count of a REPL
+    /// session. Has to be called before scanning.
+    pub fn first_line(&mut self, line: u64) {
+        self.line = line;
+    }
+
+    /// The part of the source that hasn't been consumed yet.
+    fn rest(&self) -> &str {
+        &self.source[self.current as usize..]
+    }
+
     /// Returns `true` if there are no more characters
-    fn is_at_end(&mut self) -> bool {
-        self.chars.peek().is_none() // Peek to check if we're at the end
+    fn is_at_end(&self) -> bool {
+        self.current as usize >= self.source.len()
     }
 
-    /// Consumes the next character and advances the iterator
+    /// Consumes the next character.
+    /// `current` is a byte offset, so it moves by the character's UTF-8 length.
     fn advance(&mut self) -> Option<char> {
-        self.current += 1;
-        self.chars.next() // Use .next() to advance the iterator
+        let character = self.peek()?;
+        self.current += character.len_utf8() as u64;
+        Some(character)
     }
 
     /// Returns `true` if the next character is the same as `expectation`.
     /// Advances by one if the result is `true`;
     fn expected(&mut self, expectation: &str) -> bool {
-        if let Some(&next_char) = self.chars.peek() {
+        if let Some(next_char) = self.peek() {
             if next_char == expectation.chars().next().unwrap() {
                 self.advance(); // Only advance if the expectation is met
                 return true;
@@ -53,25 +107,22 @@ impl Scanner {
         false
     }
 
-    /// Returns the character that is one index ahead
-    /// Use .peek() to look at the next character without advancing
-    fn peek(&mut self) -> Option<char> {
-        self.chars.peek().copied()
+    /// Returns the character that is one index ahead, without advancing
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
     }
 
-    // Returns the character that is two indexes ahead
-    /// Use .peek() to look at the second next character without advancing
-    fn peek_next(&mut self) -> Option<char> {
-        let mut cloned_chars = self.chars.clone();
-        cloned_chars.next();
-        cloned_chars.next()
+    /// Returns the character that is two indexes ahead, without advancing
+    fn peek_next(&self) -> Option<char> {
+        self.rest().chars().nth(1)
     }
 
     fn identify_string(&mut self) {
+        let start_line = self.line;
         // we continue advancing until the next character is the closing double quotation mark
         while self.peek() != Some('"') && !self.is_at_end() {
             // supporting multi-line strings.
-            if self.peek() == Some(char::from('\n')) {
+            if self.peek() == Some('\n') {
                 self.line += 1;
             }
             self.advance();
@@ -79,7 +130,10 @@ impl Scanner {
 
         // we did not reach a double quotation mark but the file ended.
         if self.is_at_end() {
-            rlox_error(self.line, "Unterminated string.");
+            self.error_at(
+                start_line,
+                &format!("Unterminated string (started on line {}).", start_line),
+            );
             return;
         }
 
@@ -96,7 +150,7 @@ impl Scanner {
     fn identify_number(&mut self) {
         // we continue advancing until the next character is not a digit anymore
         loop {
-            if self.is_at_end() || !self.peek().unwrap().is_digit(10) {
+            if self.is_at_end() || !self.peek().unwrap().is_ascii_digit() {
                 break;
             } else {
                 self.advance();
@@ -106,10 +160,10 @@ impl Scanner {
         // we did not reach a double quotation mark but the file ended.
         if self.peek() == Some('.') {
             if let Some(peek_next_character) = self.peek_next() {
-                if peek_next_character.is_digit(10) {
+                if peek_next_character.is_ascii_digit() {
                     self.advance();
                     while !self.is_at_end() {
-                        if self.peek().unwrap().is_digit(10) {
+                        if self.peek().unwrap().is_ascii_digit() {
                             self.advance();
                         } else {
                             break;
@@ -119,9 +173,14 @@ impl Scanner {
             }
         }
 
-        let number: f64 = self.source[self.start as usize..self.current as usize]
-            .parse::<f64>()
-            .expect("FAILED TO CONVERT STRING TO F64");
+        let number = match Number::parse(&self.source[self.start as usize..self.current as usize]) {
+            Some(number) => number,
+            None => {
+                self.error("Number literal out of range.");
+                // still a token, so the parser doesn't report the gap as well
+                Number::Float(f64::INFINITY)
+            }
+        };
 
         self.add_token(TokenType::Number, Some(Object::Number(number)));
     }
@@ -130,7 +189,7 @@ impl Scanner {
         // Loop until we find a non-alphanumeric or non-underscore character.
         while self
             .peek()
-            .map_or(false, |c| c.is_ascii_alphanumeric() || c == '_')
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
         {
             self.advance();
         }
@@ -139,10 +198,11 @@ impl Scanner {
         let text = &self.source[self.start as usize..self.current as usize];
 
         // Check if the identifier is a keyword.
-        let token_type = KEYWORDS.get(text).copied().unwrap_or(TokenType::Identifier);
+        let token_type = keywords::keyword(text, self.edition).unwrap_or(TokenType::Identifier);
+        let symbol = self.interner.intern(text);
 
         // Add the token.
-        self.add_token(token_type, None);
+        self.push_token(token_type, None, Some(symbol));
     }
 
     fn scan_token(&mut self) {
@@ -159,6 +219,7 @@ impl Scanner {
             '}' => TokenType::RightBrace,
             ',' => TokenType::Comma,
             '.' => TokenType::Dot,
+            ':' => TokenType::Colon,
             '-' => TokenType::Minus,
             '+' => TokenType::Plus,
             ';' => TokenType::Semicolon,
@@ -176,6 +237,9 @@ impl Scanner {
             }
             '=' => {
                 if self.expected("=") {
+                    if self.expected("=") {
+                        self.error("Lox uses '==' for equality.");
+                    }
                     TokenType::EqualEqual
                 } else {
                     TokenType::Equal
@@ -197,6 +261,7 @@ impl Scanner {
             }
             '/' => {
                 if self.expected("*") {
+                    let start_line = self.line;
                     let mut counter = 1;
                     while let Some(c) = self.peek() {
                         if self.is_at_end() {
@@ -205,7 +270,9 @@ impl Scanner {
 
                         self.advance();
 
-                        if c == '/' && self.expected("*") {
+                        if c == '\n' {
+                            self.line += 1;
+                        } else if c == '/' && self.expected("*") {
                             counter += 1;
                         } else if c == '*' && self.expected("/") {
                             counter -= 1;
@@ -214,6 +281,17 @@ impl Scanner {
                             }
                         }
                     }
+                    if counter > 0 {
+                        self.error_at(
+                            start_line,
+                            &format!(
+                                "Unterminated block comment (started on line {}).",
+                                start_line
+                            ),
+                        );
+                        return;
+                    }
+                    self.add_comment();
                     return;
                 } else if self.expected("/") {
                     while let Some(c) = self.peek() {
@@ -222,24 +300,46 @@ impl Scanner {
                         }
                         self.advance();
                     }
+                    self.add_comment();
                     return;
                 } else {
                     TokenType::Slash
                 }
             }
+            // mistakes carried over from C or JavaScript are reported, then scanned as what
+            // was meant so the parser doesn't pile more errors on top
+            '&' if self.expected("&") => {
+                self.error("Use 'and' instead of '&&'.");
+                TokenType::And
+            }
+            '|' if self.expected("|") => {
+                self.error("Use 'or' instead of '||'.");
+                TokenType::Or
+            }
+            '#' => {
+                self.error("Comments start with '//' in Lox, not '#'.");
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.advance();
+                }
+                return;
+            }
             '\r' | '\t' | ' ' => return, // Ignore whitespace characters
+            BOM => {
+                self.error("Unexpected byte order mark (U+FEFF); one is only allowed at the start of a file.");
+                return;
+            }
             '\n' => {
                 self.line += 1;
                 return;
             }
             _ => {
-                if character.is_digit(10) {
+                if character.is_ascii_digit() {
                     // it is a base10 digit!
                     self.identify_number();
                 } else if character.is_ascii_alphabetic() || character == '_' {
                     self.scan_identifier();
                 } else {
-                    rlox_error(self.line, &format!("Unexpected character {}", character));
+                    self.error(&format!("Unexpected character {}", character));
                 }
                 return;
             }
@@ -248,16 +348,39 @@ impl Scanner {
         self.add_token(token_type, None);
     }
 
+    fn error(&mut self, message: &str) {
+        self.error_at(self.line, message);
+    }
+
+    /// Reports an error on `line` rather than the current one, e.g. where an unterminated
+    /// string started.
+    fn error_at(&mut self, line: u64, message: &str) {
+        self.errors
+            .push(RLoxError::ScanError(line, message.to_string()));
+    }
+
     fn add_token(&mut self, token: TokenType, literal: Option<Object>) {
+        self.push_token(token, literal, None);
+    }
+
+    fn push_token(&mut self, token: TokenType, literal: Option<Object>, symbol: Option<Symbol>) {
         let lexeme = self.source[self.start as usize..self.current as usize].to_string();
         self.tokens.push(Token {
             token_type: token,
             lexeme,
             literal,
             line: self.line,
+            offset: self.start as usize,
+            symbol,
         });
     }
 
+    fn add_comment(&mut self) {
+        if self.preserve_comments {
+            self.add_token(TokenType::Comment, None);
+        }
+    }
+
     pub fn scan_tokens(&mut self) {
         while !self.is_at_end() {
             // Start of the next lexeme
@@ -271,6 +394,23 @@ impl Scanner {
             lexeme: String::default(),
             literal: None,
             line: self.line,
+            offset: self.source.len(),
+            symbol: None,
         });
     }
 }
+
+/// Scans `source` and returns the token covering the byte `offset`, e.g. to describe what
+/// an editor's cursor is on.
+///
+/// Ranges are half-open, so at an offset where one token ends and the next begins, the
+/// later one is returned. Whitespace, comments and characters that don't scan are covered
+/// by no token.
+pub fn scan_one(source: &str, offset: usize) -> Option<Token> {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens();
+    scanner
+        .tokens
+        .into_iter()
+        .find(|token| token.span().contains(&offset))
+}
@@ -0,0 +1,85 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tokens::Object;
+
+/// Bumped whenever the snapshot format changes; older versions are refused on restore.
+const FORMAT_VERSION: u32 = 2;
+
+/// What every snapshot starts with, followed by the format version.
+const MAGIC: &str = "rloxs";
+
+/// Why a snapshot couldn't be restored.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The bytes don't start with a snapshot header.
+    NotASnapshot,
+    /// The snapshot was written by a different format version.
+    UnsupportedVersion(u32),
+    /// The header is fine but the globals aren't, e.g. a value of a kind this version
+    /// doesn't know.
+    Invalid(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "Not an rlox snapshot."),
+            SnapshotError::UnsupportedVersion(version) => write!(
+                f,
+                "Snapshot format version {} is not supported; expected {}.",
+                version, FORMAT_VERSION
+            ),
+            SnapshotError::Invalid(message) => write!(f, "Invalid snapshot: {}.", message),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+/// The global bindings read back from a snapshot.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Every binding, sorted by name.
+    pub globals: BTreeMap<String, Object>,
+    /// The names among them that are built-in.
+    pub builtins: BTreeSet<String>,
+}
+
+/// Serializes global bindings: a header line with the format version, followed by a
+/// JSON object with the `globals`, mapping each name to its value, and the names of the
+/// `builtins`, both sorted by name.
+pub fn write<'a>(
+    globals: impl Iterator<Item = (&'a str, &'a Object)>,
+    builtins: impl Iterator<Item = &'a str>,
+) -> Vec<u8> {
+    let snapshot = Snapshot {
+        globals: globals
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect(),
+        builtins: builtins.map(str::to_string).collect(),
+    };
+    let values =
+        serde_json::to_string(&snapshot).expect("global values only hold JSON-representable data");
+    format!("{} {}\n{}", MAGIC, FORMAT_VERSION, values).into_bytes()
+}
+
+/// Reads back the bindings written by [`write`].
+pub fn read(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+    let text = std::str::from_utf8(bytes).map_err(|_| SnapshotError::NotASnapshot)?;
+    let (header, values) = text.split_once('\n').ok_or(SnapshotError::NotASnapshot)?;
+    let version = match header.split_once(' ') {
+        Some((MAGIC, version)) => version.parse().map_err(|_| SnapshotError::NotASnapshot)?,
+        _ => return Err(SnapshotError::NotASnapshot),
+    };
+    if version != FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    serde_json::from_str(values).map_err(|err| SnapshotError::Invalid(err.to_string()))
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+
+use crate::tokens::Object;
+
+/// What the interpreter did since its stats were last taken, as reported by `--stats`.
+///
+/// The step limit counts against `statements` too, so the two can't disagree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Statements executed, including the one a run failed in.
+    pub statements: u64,
+    /// Values created by literals and operators, by type. Reading a variable or passing
+    /// an operand through, like `and` and `or` do, creates none.
+    pub nils: u64,
+    pub booleans: u64,
+    pub numbers: u64,
+    pub strings: u64,
+    /// Bytes written by `print` and `debug`, newlines included.
+    pub output_bytes: u64,
+    /// Runtime errors carried on from with `keep_going`.
+    pub recovered_errors: u64,
+}
+
+impl Stats {
+    /// Counts `value` as created.
+    pub(crate) fn created(&mut self, value: &Object) {
+        match value {
+            Object::Nil => self.nils += 1,
+            Object::Boolean(_) => self.booleans += 1,
+            Object::Number(_) => self.numbers += 1,
+            Object::String(_) => self.strings += 1,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "statements: {}, nils: {}, booleans: {}, numbers: {}, strings: {}, output bytes: {}, recovered errors: {}",
+            self.statements,
+            self.nils,
+            self.booleans,
+            self.numbers,
+            self.strings,
+            self.output_bytes,
+            self.recovered_errors
+        )
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+/// A name interned by an [`Interner`], which stands for it as a small number.
+///
+/// A symbol only means something to the interner that made it, and is a plain index into
+/// its names, so an interner always checks that the name it resolves to is the one meant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// The position of the name among those of its interner, in the order they were
+    /// interned.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Hands out one [`Symbol`] per distinct name, e.g. for the identifiers of a program, so
+/// names can be told apart without comparing or hashing their text again.
+///
+/// The scanner interns every identifier and keyword into the interner of the environment
+/// the program runs in, which then finds a variable by indexing with its symbol.
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: Vec<String>,
+    symbols: HashMap<String, Symbol>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbol of `name`, interning it first if it is new.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let symbol = Symbol(
+            u32::try_from(self.names.len()).expect("fewer than 2^32 distinct names in a program"),
+        );
+        self.names.push(name.to_string());
+        self.symbols.insert(name.to_string(), symbol);
+        symbol
+    }
+
+    /// The symbol of `name` if it has been interned, without interning it.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    /// The name `symbol` stands for. A symbol of another interner resolves to whatever
+    /// name this one has at its index, if any; see [`confirm`](Self::confirm).
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.names.get(symbol.index()).map(String::as_str)
+    }
+
+    /// Every name interned so far, in the order of their symbols' indexes.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Returns `symbol` if it is this interner's symbol for `name`, e.g. to check a token
+    /// that may have been scanned with another interner.
+    pub fn confirm(&self, symbol: Symbol, name: &str) -> Option<Symbol> {
+        (self.resolve(symbol) == Some(name)).then_some(symbol)
+    }
+}
@@ -0,0 +1,99 @@
+use std::{fmt, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+/// `std::time::Instant::now` panics on wasm32-unknown-unknown, so there phases aren't
+/// timed and every duration is zero.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Copy)]
+pub struct Instant;
+
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    pub fn now() -> Self {
+        Instant
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// The phases a run goes through, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Scan,
+    Parse,
+    Interpret,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Scan => write!(f, "scan"),
+            Phase::Parse => write!(f, "parse"),
+            Phase::Interpret => write!(f, "interpret"),
+        }
+    }
+}
+
+/// How long each phase of a run took, as reported by `--time`.
+#[derive(Debug, Default)]
+pub struct Timings {
+    pub scan: Duration,
+    pub parse: Duration,
+    pub interpret: Duration,
+    /// The phase that reported errors, if the run failed.
+    pub failed_in: Option<Phase>,
+    /// How many sources were loaded from a `.loxc` cache instead of scanned and parsed.
+    pub cached: usize,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.scan + self.parse + self.interpret
+    }
+
+    /// The total as the REPL shows it after a submission: `(took 12.3ms)`.
+    pub fn took(&self) -> String {
+        format!("(took {})", format_duration(self.total()))
+    }
+
+    /// Prints the timings to stderr.
+    pub fn report(&self) {
+        eprintln!("{}", self);
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scan: {}, parse: {}, interpret: {}, total: {}",
+            format_duration(self.scan),
+            format_duration(self.parse),
+            format_duration(self.interpret),
+            format_duration(self.total())
+        )?;
+        if self.cached > 0 {
+            write!(f, " ({} loaded from cache)", self.cached)?;
+        }
+        if let Some(phase) = self.failed_in {
+            write!(f, " (failed during {})", phase)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats `duration` with a unit that keeps about three significant digits.
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+    if micros < 1_000.0 {
+        format!("{:.0}µs", micros)
+    } else if micros < 1_000_000.0 {
+        format!("{:.1}ms", micros / 1_000.0)
+    } else {
+        format!("{:.2}s", micros / 1_000_000.0)
+    }
+}
@@ -1,46 +1,151 @@
-#[derive(Debug, Clone)]
+use std::{cmp::Ordering, fmt, ops::Range};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{number::Number, symbol::Symbol};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 pub enum Object {
     Nil,            // Represents nil
     Boolean(bool),  // Represents a boolean
-    Number(f64),    // Represents a number (using f64 as an example)
+    Number(Number), // Represents a number, integral or not
     String(String), // Represents a string
 }
 
 impl Object {
-    pub fn print(self) {
+    /// The name of the value's type as shown to Lox programmers.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Nil => "nil",
+            Object::Boolean(_) => "boolean",
+            Object::Number(_) => "number",
+            Object::String(_) => "string",
+        }
+    }
+
+    /// Orders two values of the same type: numbers by value, strings lexicographically
+    /// and `false` before `true`.
+    ///
+    /// Values of different types, nil and NaN have no order, which the error describes.
+    pub fn compare(&self, other: &Object) -> Result<Ordering, String> {
+        match (self, other) {
+            (Object::Number(left), Object::Number(right)) => left
+                .partial_cmp(right)
+                .ok_or_else(|| "NaN can't be compared.".to_string()),
+            (Object::String(left), Object::String(right)) => Ok(left.cmp(right)),
+            (Object::Boolean(left), Object::Boolean(right)) => Ok(left.cmp(right)),
+            _ => Err(format!(
+                "Can't compare {} with {}.",
+                self.type_name(),
+                other.type_name()
+            )),
+        }
+    }
+
+    /// The text a `print` statement shows for the value, without the newline. Numbers are
+    /// formatted as [`Number::format`] describes.
+    pub fn printed(&self, float_precision: Option<usize>) -> String {
+        match self {
+            Object::Nil => String::new(),
+            _ => self.formatted(float_precision),
+        }
+    }
+
+    /// The value as the REPL shows it; like [`Display`](fmt::Display), but with numbers
+    /// formatted as [`Number::format`] describes.
+    pub fn formatted(&self, float_precision: Option<usize>) -> String {
+        match self {
+            Object::Number(number) => number.format(float_precision),
+            _ => self.to_string(),
+        }
+    }
+
+    /// The value for developers rather than users: like [`formatted`](Self::formatted),
+    /// except that strings are quoted and their special characters escaped, so `"1"` and
+    /// `1` or `""` and `nil` can't be mistaken for each other.
+    pub fn inspected(&self, float_precision: Option<usize>) -> String {
+        let Object::String(string) = self else {
+            return self.formatted(float_precision);
+        };
+        let mut quoted = String::with_capacity(string.len() + 2);
+        quoted.push('"');
+        for character in string.chars() {
+            match character {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                character if character.is_control() => {
+                    quoted.push_str(&format!("\\u{{{:x}}}", character as u32))
+                }
+                character => quoted.push(character),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Object::Nil => println!(""),
-            Object::Boolean(boolean) => println!("{}", boolean),
-            Object::Number(number) => println!("{}", number.round()),
-            Object::String(string) => println!("{}", string),
+            Object::Nil => write!(f, "nil"),
+            Object::Boolean(boolean) => write!(f, "{}", boolean),
+            Object::Number(number) => write!(f, "{}", number),
+            Object::String(string) => write!(f, "{}", string),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Option<Object>,
     pub line: u64,
+    /// Byte offset of the lexeme in the source; the token spans `offset..offset + lexeme.len()`.
+    pub offset: usize,
+    /// The lexeme of an identifier or keyword as interned by the scanner. Only meaningful
+    /// to the interner it came from, so it is left out of the JSON form.
+    #[serde(skip)]
+    pub symbol: Option<Symbol>,
 }
 
 impl Token {
-    /// Returns type + lexeme + literal as a formatted string
-    #[allow(dead_code)]
-    pub fn as_string(&self) -> String {
-        format!(
-            "{:?} {} {:?}",
-            self.token_type,
-            self.lexeme,
-            self.literal
-                .clone()
-                .unwrap_or(Object::String("".to_string()))
+    /// Names the token the way error messages refer to it: `'=='`, `identifier 'count'`,
+    /// `number literal '1.5'` or `end of file`.
+    pub fn describe(&self) -> String {
+        match self.token_type {
+            TokenType::Identifier | TokenType::Number | TokenType::Comment => {
+                format!("{} '{}'", self.token_type, self.lexeme)
+            }
+            // the lexeme is already quoted
+            TokenType::String => format!("{} {}", self.token_type, self.lexeme),
+            _ => self.token_type.to_string(),
+        }
+    }
+
+    /// The byte range of the lexeme in the source.
+    pub fn span(&self) -> Range<usize> {
+        self.offset..self.offset + self.lexeme.len()
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, offset {}",
+            self.describe(),
+            self.line,
+            self.offset
         )
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParen,
@@ -49,6 +154,7 @@ pub enum TokenType {
     RightBrace,
     Comma,
     Dot,
+    Colon,
     Minus,
     Plus,
     Semicolon,
@@ -72,7 +178,9 @@ pub enum TokenType {
 
     // Keywords.
     And,
+    Assert,
     Class,
+    Debug,
     Else,
     False,
     Fun,
@@ -88,6 +196,62 @@ pub enum TokenType {
     Var,
     While,
 
+    // Only emitted when the scanner preserves comments.
+    Comment,
+
     // End of file.
     Eof,
 }
+
+impl fmt::Display for TokenType {
+    /// The source-level spelling of the token, quoted, or a name for the kinds of token
+    /// that have none.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let spelling = match self {
+            TokenType::LeftParen => "'('",
+            TokenType::RightParen => "')'",
+            TokenType::LeftBrace => "'{'",
+            TokenType::RightBrace => "'}'",
+            TokenType::Comma => "','",
+            TokenType::Dot => "'.'",
+            TokenType::Colon => "':'",
+            TokenType::Minus => "'-'",
+            TokenType::Plus => "'+'",
+            TokenType::Semicolon => "';'",
+            TokenType::Slash => "'/'",
+            TokenType::Star => "'*'",
+            TokenType::Bang => "'!'",
+            TokenType::BangEqual => "'!='",
+            TokenType::Equal => "'='",
+            TokenType::EqualEqual => "'=='",
+            TokenType::Greater => "'>'",
+            TokenType::GreaterEqual => "'>='",
+            TokenType::Less => "'<'",
+            TokenType::LessEqual => "'<='",
+            TokenType::Identifier => "identifier",
+            TokenType::String => "string literal",
+            TokenType::Number => "number literal",
+            TokenType::And => "'and'",
+            TokenType::Assert => "'assert'",
+            TokenType::Class => "'class'",
+            TokenType::Debug => "'debug'",
+            TokenType::Else => "'else'",
+            TokenType::False => "'false'",
+            TokenType::Fun => "'fun'",
+            TokenType::For => "'for'",
+            TokenType::If => "'if'",
+            TokenType::Nil => "'nil'",
+            TokenType::Or => "'or'",
+            TokenType::Print => "'print'",
+            TokenType::Return => "'return'",
+            TokenType::Super => "'super'",
+            TokenType::This => "'this'",
+            TokenType::True => "'true'",
+            TokenType::Var => "'var'",
+            TokenType::While => "'while'",
+            TokenType::Comment => "comment",
+            TokenType::Eof => "end of file",
+        };
+        write!(f, "{}", spelling)
+    }
+}
@@ -0,0 +1,97 @@
+//! JavaScript bindings, enabled by the `wasm` feature, for running rlox in the browser,
+//! e.g. behind a web REPL.
+//!
+//! ```js
+//! const lox = new RLoxWasm();
+//! lox.run("var a = 1;");
+//! lox.eval("a + 1"); // { output: "", value: "2", diagnostics: [] }
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{engine::RLoxEngine, errors::Diagnostic, tokens::Object};
+
+/// Name the errors of [`RLoxWasm::run`] are reported under.
+const RUN_SOURCE: &str = "<input>";
+
+/// What `run` and `eval` hand back to JavaScript.
+#[derive(Serialize)]
+struct Outcome {
+    /// Everything `print` wrote during the call.
+    output: String,
+    /// The value of the final expression, for `eval`; left out when there is none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    diagnostics: Vec<JsDiagnostic>,
+}
+
+#[derive(Serialize)]
+struct JsDiagnostic {
+    line: Option<u64>,
+    message: String,
+    /// The diagnostic as the command line prints it.
+    text: String,
+}
+
+impl From<Diagnostic> for JsDiagnostic {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            line: diagnostic.error.line(),
+            message: diagnostic.error.message().to_string(),
+            text: diagnostic.to_string(),
+        }
+    }
+}
+
+/// An engine whose globals persist between calls and whose output is returned rather
+/// than printed.
+#[wasm_bindgen]
+pub struct RLoxWasm {
+    engine: RLoxEngine,
+}
+
+impl Default for RLoxWasm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl RLoxWasm {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        let mut engine = RLoxEngine::new();
+        engine.capture_output(true);
+        Self { engine }
+    }
+
+    /// Runs a program, returning `{ output, diagnostics }`.
+    pub fn run(&mut self, source: &str) -> JsValue {
+        let result = self.engine.run_source(RUN_SOURCE, source);
+        self.outcome(result.map(|_| None))
+    }
+
+    /// Runs `source` like the REPL, where the final expression may omit its `;`, returning
+    /// `{ output, value, diagnostics }`. `value` is missing for nil or a statement.
+    pub fn eval(&mut self, source: &str) -> JsValue {
+        let result = self.engine.eval(source).map(|value| match value {
+            Object::Nil => None,
+            value => Some(self.engine.format_value(&value)),
+        });
+        self.outcome(result)
+    }
+
+    fn outcome(&mut self, result: Result<Option<String>, Vec<Diagnostic>>) -> JsValue {
+        let (value, diagnostics) = match result {
+            Ok(value) => (value, vec![]),
+            Err(diagnostics) => (None, diagnostics),
+        };
+        let outcome = Outcome {
+            output: self.engine.take_output(),
+            value,
+            diagnostics: diagnostics.into_iter().map(JsDiagnostic::from).collect(),
+        };
+        serde_wasm_bindgen::to_value(&outcome).expect("outcomes only hold JS-representable values")
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::{options::InterpreterOptions, rlox::RLox};
+
+/// How long to wait for further writes before re-running, so an editor saving in
+/// several steps only triggers one run.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// What the file watcher and the Ctrl-C handler tell [`run_on_changes`].
+pub enum WatchEvent {
+    Changed,
+    Stop,
+}
+
+/// Runs the files, then re-runs them with a fresh interpreter, set up by `options`, every
+/// time one changes.
+///
+/// Errors are reported without leaving watch mode. Ctrl-C interrupts a running program
+/// and exits.
+pub fn watch(paths: &[String], time: bool, options: impl Fn() -> InterpreterOptions) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let stop_sender = sender.clone();
+    let stop_flag = interrupted.clone();
+    ctrlc::set_handler(move || {
+        stop_flag.store(true, Ordering::Relaxed);
+        let _ = stop_sender.send(WatchEvent::Stop);
+    })?;
+
+    // Editors often save by replacing the file, which would end a watch on the file itself,
+    // so the parent directories are watched and events filtered down to our files.
+    let watched: Vec<PathBuf> = paths
+        .iter()
+        .map(fs::canonicalize)
+        .collect::<Result<_, _>>()?;
+    let filter = watched.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        let relevant = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) && event.paths.iter().any(|path| filter.contains(path));
+        if relevant {
+            let _ = sender.send(WatchEvent::Changed);
+        }
+    })?;
+    for path in &watched {
+        let directory = path.parent().unwrap_or(path);
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+    }
+
+    run_on_changes(&receiver, |run| {
+        if run > 0 {
+            // clear the screen and move the cursor home
+            print!("\x1b[2J\x1b[H");
+            println!("[{}] re-running {}", timestamp(), paths.join(", "));
+        }
+        rerun(paths, options().interrupt_flag(interrupted.clone()), time);
+    });
+    Ok(())
+}
+
+/// Calls `run` with `0`, then again with `1`, `2` and so on after every burst of
+/// [`WatchEvent::Changed`], until [`WatchEvent::Stop`] arrives or every sender is gone.
+pub fn run_on_changes(events: &Receiver<WatchEvent>, mut run: impl FnMut(usize)) {
+    run(0);
+    for count in 1.. {
+        match events.recv() {
+            Ok(WatchEvent::Changed) => {}
+            Ok(WatchEvent::Stop) | Err(_) => return,
+        }
+
+        // swallow the rest of a burst of writes
+        loop {
+            match events.recv_timeout(DEBOUNCE) {
+                Ok(WatchEvent::Changed) => {}
+                Ok(WatchEvent::Stop) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+
+        run(count);
+    }
+}
+
+/// Runs the files once in a fresh engine, reporting rather than exiting on errors.
+pub fn rerun(paths: &[String], options: InterpreterOptions, time: bool) {
+    let sources = match RLox::read_sources(paths) {
+        Ok(sources) => sources,
+        Err(err) => {
+            println!("Could not read the files: {}", err);
+            return;
+        }
+    };
+
+    let mut engine = match RLox::file_engine(options) {
+        Ok(engine) => engine,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    let result = engine.run_sources(&RLox::as_named(&sources));
+    if time {
+        engine.take_timings().report();
+        eprintln!("{}", engine.take_metrics());
+    }
+    if let Err(diagnostics) = result {
+        RLox::report(diagnostics, paths.len() > 1);
+    }
+}
+
+/// The current wall-clock time of day as `HH:MM:SS` (UTC).
+fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+        % 86_400;
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
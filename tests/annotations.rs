@@ -0,0 +1,26 @@
+//! Checks that type annotations on `var` are parsed and kept, but change nothing at runtime.
+
+use rlox::{ast::format::Formatter, engine::RLoxEngine, keywords::Edition};
+
+fn output_of(source: &str) -> String {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.run_source("main.lox", source).unwrap();
+    engine.take_output()
+}
+
+#[test]
+fn annotations_do_not_change_what_a_program_does() {
+    let plain = "var a = 1;\nvar b = \"two\";\nvar c;\nvar d = a + 2;\nprint a;\nprint b;\nprint c;\nprint d;";
+    let annotated = "var a: number = 1;\nvar b: string = \"two\";\nvar c: nil;\nvar d: boolean = a + 2;\nprint a;\nprint b;\nprint c;\nprint d;";
+    assert_eq!(output_of(annotated), output_of(plain));
+}
+
+#[test]
+fn formatting_keeps_annotations() {
+    assert_eq!(
+        Formatter::format_source("var  a :number=1;\nvar b:nil;\nvar c=2;", Edition::Latest)
+            .unwrap(),
+        "var a: number = 1;\nvar b: nil;\nvar c = 2;\n"
+    );
+}
@@ -0,0 +1,34 @@
+//! Checks that `assert` statements can be disabled, and what a failure reports.
+
+use rlox::{engine::RLoxEngine, options::InterpreterOptions};
+
+const PROGRAM: &str = "var x = 12;\nassert x < 10, \"too big\";\nprint \"done\";";
+
+#[test]
+fn a_failed_assertion_is_a_runtime_error_on_its_line() {
+    let mut engine =
+        RLoxEngine::with_options(InterpreterOptions::default().capture_output(true)).unwrap();
+    let diagnostics = engine.run_source("main.lox", PROGRAM).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].error.line(), Some(2));
+    assert_eq!(
+        diagnostics[0].error.message(),
+        "Assertion failed: x < 10 (x = 12): too big."
+    );
+    assert_eq!(engine.take_output(), "");
+}
+
+#[test]
+fn disabled_assertions_are_not_evaluated() {
+    let mut engine = RLoxEngine::with_options(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .disable_asserts(true),
+    )
+    .unwrap();
+    engine.run_source("main.lox", PROGRAM).unwrap();
+    engine
+        .run_source("main.lox", "assert undefined, undefined;")
+        .unwrap();
+    assert_eq!(engine.take_output(), "done\n");
+}
@@ -0,0 +1,112 @@
+//! Checks that a program run a few statements at a time ends up where a straight run does.
+
+use rlox::{engine::RLoxEngine, execution::Execution, options::InterpreterOptions};
+
+const PROGRAM: &str = "
+var a = 1;
+print a;
+var b = a + 1;
+print b;
+var c = a + b;
+print \"c is \" + \"three\";
+var d = c * 2;
+print d;
+var e = d - a;
+print e;
+";
+
+/// Globals and output after running `source` on `engine`, sorted for comparison.
+fn state(engine: &mut RLoxEngine) -> (Vec<String>, String) {
+    let mut globals: Vec<String> = engine
+        .globals()
+        .map(|(name, value)| format!("{} = {}", name, engine.format_value(value)))
+        .collect();
+    globals.sort();
+    (globals, engine.take_output())
+}
+
+fn engine() -> RLoxEngine {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine
+}
+
+#[test]
+fn running_in_budgets_matches_a_straight_run() {
+    let mut straight = engine();
+    straight.run_source("program", PROGRAM).unwrap();
+
+    let mut budgeted = engine();
+    let mut pauses = 0;
+    let mut execution = budgeted.run_budgeted("program", PROGRAM, 3);
+    let result = loop {
+        match execution {
+            Execution::Completed(result) => break result,
+            Execution::Paused(handle) => {
+                pauses += 1;
+                assert_eq!(handle.remaining(), 10 - 3 * pauses);
+                execution = handle.resume(&mut budgeted, 3);
+            }
+        }
+    };
+    result.unwrap();
+    assert_eq!(pauses, 3);
+    assert_eq!(state(&mut budgeted), state(&mut straight));
+}
+
+#[test]
+fn output_is_available_at_every_pause() {
+    let mut engine = engine();
+    let Execution::Paused(handle) = engine.run_budgeted("program", PROGRAM, 2) else {
+        panic!("ten statements don't fit a budget of two");
+    };
+    assert_eq!(engine.take_output(), "1\n");
+    assert!(matches!(
+        handle.resume(&mut engine, 10),
+        Execution::Completed(Ok(None))
+    ));
+    assert_eq!(engine.take_output(), "2\nc is three\n6\n5\n");
+}
+
+#[test]
+fn a_runtime_error_completes_the_run() {
+    let mut engine = engine();
+    let Execution::Paused(handle) = engine.run_budgeted("program", "print 1; -\"a\"; print 2;", 1)
+    else {
+        panic!("the first statement runs alone");
+    };
+    let Execution::Completed(Err(diagnostics)) = handle.resume(&mut engine, 5) else {
+        panic!("the second statement fails");
+    };
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(engine.take_output(), "1\n");
+}
+
+#[test]
+fn errors_kept_going_past_are_reported_once_the_run_completes() {
+    let options = InterpreterOptions::default()
+        .capture_output(true)
+        .keep_going(true);
+    let mut engine = RLoxEngine::with_options(options).unwrap();
+    let Execution::Paused(handle) = engine.run_budgeted("program", "-\"a\"; print 1; -nil;", 2)
+    else {
+        panic!("three statements don't fit a budget of two");
+    };
+    let Execution::Completed(Err(diagnostics)) = handle.resume(&mut engine, 2) else {
+        panic!("both errors are reported");
+    };
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(engine.take_output(), "1\n");
+}
+
+#[test]
+fn a_parse_error_runs_nothing() {
+    let mut engine = engine();
+    let Execution::Completed(Err(diagnostics)) =
+        engine.run_budgeted("program", "print 1; print", 1)
+    else {
+        panic!("the program doesn't parse");
+    };
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(engine.take_output(), "");
+}
@@ -0,0 +1,60 @@
+//! Checks that programs can't redefine built-in globals unless told they may.
+
+use rlox::{engine::RLoxEngine, options::InterpreterOptions, tokens::Object};
+
+fn engine_with_clock(options: InterpreterOptions) -> RLoxEngine {
+    let mut engine = RLoxEngine::with_options(options.capture_output(true)).unwrap();
+    engine.define_builtin("clock", Object::String("native".to_string()));
+    engine
+}
+
+fn run_error(engine: &mut RLoxEngine, source: &str) -> String {
+    let diagnostics = engine.run_source("main.lox", source).unwrap_err();
+    diagnostics[0].error.message().to_string()
+}
+
+#[test]
+fn redefining_a_builtin_is_a_runtime_error() {
+    let mut engine = engine_with_clock(InterpreterOptions::default());
+    assert_eq!(
+        run_error(&mut engine, "var clock = 5;"),
+        "Cannot redefine built-in 'clock'."
+    );
+    engine.run_source("main.lox", "print clock;").unwrap();
+    assert_eq!(engine.take_output(), "native\n");
+}
+
+#[test]
+fn allow_shadow_natives_permits_redefining() {
+    let mut engine = engine_with_clock(InterpreterOptions::default().allow_shadow_natives(true));
+    engine
+        .run_source("main.lox", "var clock = 5; print clock;")
+        .unwrap();
+    assert_eq!(engine.take_output(), "5\n");
+}
+
+#[test]
+fn user_globals_can_be_redefined() {
+    let mut engine = engine_with_clock(InterpreterOptions::default());
+    engine.define("limit", Object::Boolean(true));
+    engine
+        .run_source(
+            "main.lox",
+            "var a = 1; var a = 2; var limit = 3; print a + limit;",
+        )
+        .unwrap();
+    assert_eq!(engine.take_output(), "5\n");
+}
+
+#[test]
+fn snapshots_keep_builtins_marked() {
+    let mut engine = engine_with_clock(InterpreterOptions::default());
+    engine.run_source("main.lox", "var a = 1;").unwrap();
+
+    let mut restored = RLoxEngine::restore(&engine.snapshot()).unwrap();
+    assert_eq!(
+        run_error(&mut restored, "var clock = 5;"),
+        "Cannot redefine built-in 'clock'."
+    );
+    restored.run_source("main.lox", "var a = 2;").unwrap();
+}
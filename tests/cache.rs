@@ -0,0 +1,83 @@
+//! Checks that a `.loxc` cache replaces scanning and parsing only while it matches its
+//! source, and that a bad one falls back to parsing instead of failing.
+
+use std::{env, fs, path::PathBuf, time::Duration};
+
+use rlox::{cache, engine::RLoxEngine, keywords::Edition, timings::Timings};
+
+const SOURCE: &str = "var a = 1 + 2;\nprint a;\n";
+
+/// Writes `source` to `main.lox` in a fresh directory and caches its tree, as `--compile`
+/// does. Returns the directory and the path of the file.
+fn compiled(name: &str, source: &str) -> (PathBuf, String) {
+    let dir = env::temp_dir().join(format!("rlox-cache-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.lox").to_str().unwrap().to_string();
+    fs::write(&path, source).unwrap();
+    let statements =
+        RLoxEngine::parse_in_edition(source.to_string(), Edition::Latest, &mut Timings::default())
+            .unwrap();
+    cache::write(&path, source, Edition::Latest, &statements).unwrap();
+    (dir, path)
+}
+
+/// Runs `source` as the file at `path` with caching on, returning its output and timings.
+fn run(path: &str, source: &str) -> (String, Timings) {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.use_cache(true);
+    engine.run_sources(&[(path, source)]).unwrap();
+    (engine.take_output(), engine.take_timings())
+}
+
+#[test]
+fn a_fresh_cache_skips_scanning_and_parsing() {
+    let (dir, path) = compiled("fresh", SOURCE);
+    let (output, timings) = run(&path, SOURCE);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output, "3\n");
+    assert_eq!(timings.cached, 1);
+    assert_eq!(timings.scan, Duration::ZERO);
+    assert!(timings.to_string().ends_with("(1 loaded from cache)"));
+}
+
+#[test]
+fn a_cache_of_other_source_is_ignored() {
+    let (dir, path) = compiled("stale", SOURCE);
+    let edited = "var a = 1 + 2;\nprint a * 2;\n";
+    let (output, timings) = run(&path, edited);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output, "6\n");
+    assert_eq!(timings.cached, 0);
+}
+
+#[test]
+fn a_corrupt_cache_is_ignored() {
+    let (dir, path) = compiled("corrupt", SOURCE);
+    let cache_path = cache::cache_path(&path);
+    let written = fs::read_to_string(&cache_path).unwrap();
+    let (header, tree) = written.split_once('\n').unwrap();
+    let mut fields: Vec<&str> = header.split(' ').collect();
+    let version = (fields[1].parse::<u32>().unwrap() + 1).to_string();
+    fields[1] = &version;
+    let other_version = fields.join(" ");
+
+    for corrupt in [
+        written[..written.len() / 2].to_string(),
+        header.to_string(),
+        format!("{}\n{}", header, tree.replace('{', "[")),
+        format!("{}\n{}", header, tree.replace("\"Plus\"", "\"Print\"")),
+        format!("{}\n{}", other_version, tree),
+        String::new(),
+    ] {
+        fs::write(&cache_path, &corrupt).unwrap();
+        let (output, timings) = run(&path, SOURCE);
+        assert_eq!(output, "3\n", "with cache {:?}", corrupt);
+        assert_eq!(timings.cached, 0, "with cache {:?}", corrupt);
+    }
+    fs::write(&cache_path, [0xff, 0xfe, b'\n', 0x00]).unwrap();
+    assert_eq!(run(&path, SOURCE).1.cached, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,83 @@
+//! Pins the `--tokens --format=json` and `--ast --format=json` output to the fixtures in
+//! `tests/canonical`, which harnesses diffing rlox against other Lox implementations rely
+//! on. A change here is a change to the documented schema in `rlox::canonical`.
+
+use std::{fs, path::Path};
+
+use rlox::{canonical, engine::RLoxEngine, scanner::Scanner, timings::Timings};
+
+fn fixture(name: &str) -> String {
+    fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/canonical")
+            .join(name),
+    )
+    .unwrap()
+}
+
+fn tokens(source: &str) -> String {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens();
+    assert!(scanner.errors.is_empty());
+    serde_json::to_string_pretty(&canonical::tokens(&scanner.tokens)).unwrap() + "\n"
+}
+
+fn tree(source: &str) -> String {
+    let statements =
+        RLoxEngine::parse_source(source.to_string(), false, &mut Timings::default()).unwrap();
+    serde_json::to_string_pretty(&canonical::program(&statements).unwrap()).unwrap() + "\n"
+}
+
+#[test]
+fn tokens_match_the_fixtures() {
+    for name in ["book", "extensions"] {
+        let source = fixture(&format!("{}.lox", name));
+        assert_eq!(
+            tokens(&source),
+            fixture(&format!("{}.tokens.json", name)),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn trees_match_the_fixtures() {
+    for name in ["book", "extensions"] {
+        let source = fixture(&format!("{}.lox", name));
+        assert_eq!(
+            tree(&source),
+            fixture(&format!("{}.ast.json", name)),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn only_additions_to_the_book_are_flagged() {
+    assert!(!tokens(&fixture("book.lox")).contains("extension"));
+    assert!(!tree(&fixture("book.lox")).contains("extension"));
+
+    let flagged = |dump: String| {
+        serde_json::from_str::<Vec<serde_json::Value>>(&dump)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry["extension"] == true)
+            .map(|entry| entry.get("type").unwrap_or(&entry["kind"]).clone())
+            .collect::<Vec<_>>()
+    };
+    let source = fixture("extensions.lox");
+    assert_eq!(flagged(tokens(&source)), ["COLON", "ASSERT", "DEBUG"]);
+    assert_eq!(flagged(tree(&source)), ["Var", "Assert", "Debug"]);
+}
+
+#[test]
+fn numbers_are_floats_and_only_literal_tokens_have_literals() {
+    let dump: Vec<serde_json::Value> = serde_json::from_str(&tokens("true 1 \"a\" nil")).unwrap();
+    let literals: Vec<String> = dump
+        .iter()
+        .map(|token| token["literal"].to_string())
+        .collect();
+    assert_eq!(literals, ["null", "1.0", "\"a\"", "null", "null"]);
+}
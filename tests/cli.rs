@@ -0,0 +1,253 @@
+//! Runs the `rlox` binary on files written to a scratch directory and checks what it
+//! prints and the status it exits with.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// What a run of rlox printed, and its exit code.
+struct Run {
+    stdout: String,
+    stderr: String,
+    code: i32,
+}
+
+/// Writes `files` into a fresh directory and runs rlox there with `args`, so paths in
+/// its output are the bare file names.
+fn rlox(args: &[&str], files: &[(&str, &str)]) -> Run {
+    rlox_with_env(&[], args, files)
+}
+
+/// Like [`rlox`], with the environment variables in `vars` set.
+fn rlox_with_env(vars: &[(&str, &str)], args: &[&str], files: &[(&str, &str)]) -> Run {
+    let dir = scratch_dir();
+    for (name, source) in files {
+        fs::write(dir.join(name), source).unwrap();
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        .current_dir(&dir)
+        .env_remove("RUST_BACKTRACE")
+        .env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .envs(vars.iter().copied())
+        .output()
+        .expect("rlox runs");
+    fs::remove_dir_all(&dir).unwrap();
+    Run {
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+        code: output.status.code().unwrap(),
+    }
+}
+
+fn scratch_dir() -> PathBuf {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "rlox-cli-{}-{}",
+        std::process::id(),
+        RUNS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const PROGRAM: &str = "var a = 1 + 2 * 3;\nprint -a;\nif (a) print \"x\"; else print nil;\n";
+
+#[test]
+fn ast_prints_the_tree_without_running_it() {
+    let run = rlox(&["--ast", "program.lox"], &[("program.lox", PROGRAM)]);
+    assert_eq!(
+        run.stdout,
+        "(var a (+ 1 (* 2 3)))\n(print (- a))\n(if a (print \"x\") (print nil))\n"
+    );
+    assert_eq!(run.stderr, "");
+    assert_eq!(run.code, 0);
+}
+
+#[test]
+fn check_exits_with_65_only_for_scan_and_parse_errors() {
+    let files = [
+        ("good.lox", PROGRAM),
+        ("bad.lox", "print 1 +;\n"),
+        ("fails_at_runtime.lox", "print -\"x\";\n"),
+    ];
+
+    let run = rlox(&["--check", "good.lox"], &files);
+    assert_eq!((run.stdout.as_str(), run.code), ("", 0));
+    // runtime errors aren't looked for
+    let run = rlox(&["--check", "fails_at_runtime.lox"], &files);
+    assert_eq!((run.stdout.as_str(), run.code), ("", 0));
+
+    let run = rlox(&["--check", "good.lox", "bad.lox"], &files);
+    assert_eq!(
+        run.stdout,
+        "OK: good.lox\n[bad.lox, Line 1] Error: Expected expression but found ';'.\n"
+    );
+    assert_eq!(run.code, 65);
+
+    let run = rlox(&["--check", "missing.lox"], &files);
+    assert_eq!(run.code, 1);
+}
+
+#[test]
+fn time_reports_each_phase_on_stderr() {
+    let run = rlox(&["--time", "program.lox"], &[("program.lox", PROGRAM)]);
+    assert_eq!(run.stdout, "-7\nx\n");
+    let lines: Vec<&str> = run.stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", run.stderr);
+    let labels: Vec<&str> = lines[0]
+        .split(", ")
+        .map(|phase| phase.split(':').next().unwrap())
+        .collect();
+    assert_eq!(labels, ["scan", "parse", "interpret", "total"]);
+    assert_eq!(lines[1], "nodes: 15, statements: 5, max depth: 3");
+}
+
+#[test]
+fn errors_name_the_file_they_are_in() {
+    let files = [
+        ("first.lox", "var a = 1;\n"),
+        ("second.lox", "print a;\nprint b;\n"),
+        ("broken.lox", "\nprint 1 +;\n"),
+        ("also_broken.lox", "var = 2;\n"),
+    ];
+
+    // files share their globals, and a runtime error names the file it happened in
+    let run = rlox(&["first.lox", "second.lox"], &files);
+    assert_eq!(
+        run.stdout,
+        "1\n[second.lox, Line 2] Error: Unknown variable used.\n"
+    );
+    assert_eq!(run.code, 70);
+
+    // a parse error in any file stops all of them from running, and each is reported
+    // with its own file
+    let run = rlox(
+        &["first.lox", "broken.lox", "second.lox", "also_broken.lox"],
+        &files,
+    );
+    assert_eq!(
+        run.stdout,
+        "[broken.lox, Line 2] Error: Expected expression but found ';'.\n\
+         [also_broken.lox, Line 1] Error: Expected identifier after 'var' but found '='.\n"
+    );
+    assert_eq!(run.code, 65);
+}
+
+#[test]
+fn highlight_colors_tokens_when_forced() {
+    let files = [("colors.lox", "var s = \"hi\"; // greet\nprint s + 1 @;\n")];
+    let run = rlox_with_env(
+        &[("CLICOLOR_FORCE", "1")],
+        &["--highlight", "colors.lox"],
+        &files,
+    );
+    assert_eq!(
+        run.stdout,
+        "\x1b[35mvar\x1b[0m s \x1b[36m=\x1b[0m \x1b[32m\"hi\"\x1b[0m\x1b[36m;\x1b[0m \
+         \x1b[2m// greet\x1b[0m\n\
+         \x1b[35mprint\x1b[0m s \x1b[36m+\x1b[0m \x1b[33m1\x1b[0m \x1b[4;31m@\x1b[0m\x1b[36m;\x1b[0m\n"
+    );
+
+    // piped output stays plain unless forced, and NO_COLOR wins over forcing
+    assert_eq!(
+        rlox(&["--highlight", "colors.lox"], &files).stdout,
+        files[0].1
+    );
+    let run = rlox_with_env(
+        &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")],
+        &["--highlight", "colors.lox"],
+        &files,
+    );
+    assert_eq!(run.stdout, files[0].1);
+}
+
+#[test]
+fn a_tree_printed_as_json_runs_like_its_source() {
+    let files = [("program.lox", PROGRAM)];
+    let tree = rlox(&["--ast-format=json", "program.lox"], &files).stdout;
+    let run = rlox(&["--run-ast", "program.json"], &[("program.json", &tree)]);
+    assert_eq!(run.stdout, rlox(&["program.lox"], &files).stdout);
+    assert_eq!(run.stdout, "-7\nx\n");
+    assert_eq!(run.code, 0);
+
+    // a tree the parser couldn't have produced is rejected with the file it came from
+    let tree = rlox(
+        &["--ast-format=json", "sum.lox"],
+        &[("sum.lox", "print 1 + 2;\n")],
+    )
+    .stdout
+    .replace("\"Plus\"", "\"Print\"");
+    let run = rlox(&["--run-ast", "sum.json"], &[("sum.json", &tree)]);
+    assert_eq!(
+        run.stdout,
+        "[sum.json, Line 1] Error: Invalid syntax tree: `Print` is not a binary operator.\n"
+    );
+    assert_eq!(run.code, 65);
+}
+
+#[test]
+fn fmt_check_exits_with_1_for_unformatted_files() {
+    let files = [
+        ("formatted.lox", "var x = 1;\nx; // c\n"),
+        ("messy.lox", "var   x=1 ;\n"),
+    ];
+    let run = rlox(&["fmt", "--check", "formatted.lox"], &files);
+    assert_eq!((run.stdout.as_str(), run.code), ("", 0));
+    let run = rlox(&["fmt", "--check", "formatted.lox", "messy.lox"], &files);
+    assert_eq!(run.stdout, "Not formatted: messy.lox\n");
+    assert_eq!(run.code, 1);
+    let run = rlox(
+        &["fmt", "--check", "broken.lox"],
+        &[("broken.lox", "var = 1;\n")],
+    );
+    assert_eq!(run.code, 65);
+}
+
+#[test]
+fn fmt_and_lint_follow_the_edition() {
+    let files = [("old.lox", "var debug = 1;\nprint debug == true;\n")];
+
+    let run = rlox(&["fmt", "--check", "--edition", "2015", "old.lox"], &files);
+    assert_eq!((run.stdout.as_str(), run.code), ("", 0));
+    let run = rlox(&["lint", "--edition", "2015", "old.lox"], &files);
+    assert_eq!(
+        run.stdout,
+        "[old.lox, Line 2] Warning: Comparison with 'true' is redundant. (redundant-bool-comparison)\n"
+    );
+    assert_eq!(run.code, 1);
+
+    // `debug` is a keyword in the latest edition
+    assert_eq!(rlox(&["fmt", "--check", "old.lox"], &files).code, 65);
+    assert_eq!(rlox(&["lint", "old.lox"], &files).code, 65);
+}
+
+#[test]
+fn max_loop_iterations_stops_a_runaway_loop() {
+    let files = [("spin.lox", "print 1;\nwhile (true) print 2;\n")];
+    let run = rlox(&["--max-loop-iterations", "2", "spin.lox"], &files);
+    assert_eq!(
+        run.stdout,
+        "1\n2\n2\n[Line 2] Error: Loop exceeded 2 iterations (possible infinite loop) at line 2.\n"
+    );
+    assert_eq!(run.code, 70);
+}
+
+#[test]
+fn highlight_follows_the_edition() {
+    let files = [("old.lox", "var assert = 1; print assert;\n")];
+    let run = rlox_with_env(
+        &[("CLICOLOR_FORCE", "1")],
+        &["--highlight", "--edition", "2015", "old.lox"],
+        &files,
+    );
+    assert_eq!(
+        run.stdout,
+        "\x1b[35mvar\x1b[0m assert \x1b[36m=\x1b[0m \x1b[33m1\x1b[0m\x1b[36m;\x1b[0m \
+         \x1b[35mprint\x1b[0m assert\x1b[36m;\x1b[0m\n"
+    );
+}
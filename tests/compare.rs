@@ -0,0 +1,81 @@
+//! Checks the ordering `Object::compare` gives values.
+
+use std::cmp::Ordering;
+
+use rlox::{number::Number, tokens::Object};
+
+fn float(number: f64) -> Object {
+    Object::Number(Number::Float(number))
+}
+
+fn string(text: &str) -> Object {
+    Object::String(text.to_string())
+}
+
+#[test]
+fn values_of_one_type_are_ordered() {
+    let cases = [
+        (float(1.0), float(2.0), Ordering::Less),
+        (
+            Object::Number(Number::Int(2)),
+            float(1.5),
+            Ordering::Greater,
+        ),
+        (Object::Number(Number::Int(3)), float(3.0), Ordering::Equal),
+        (float(-0.0), float(0.0), Ordering::Equal),
+        (float(f64::INFINITY), float(1e308), Ordering::Greater),
+        (string("apple"), string("banana"), Ordering::Less),
+        (string("b"), string("abc"), Ordering::Greater),
+        (string(""), string(""), Ordering::Equal),
+        (
+            Object::Boolean(false),
+            Object::Boolean(true),
+            Ordering::Less,
+        ),
+        (
+            Object::Boolean(true),
+            Object::Boolean(true),
+            Ordering::Equal,
+        ),
+    ];
+    for (left, right, ordering) in cases {
+        assert_eq!(left.compare(&right), Ok(ordering), "{} vs {}", left, right);
+        assert_eq!(right.compare(&left), Ok(ordering.reverse()));
+    }
+}
+
+#[test]
+fn sorting_with_compare_is_stable() {
+    let mut words = [
+        (string("b"), 0),
+        (string("a"), 1),
+        (string("b"), 2),
+        (string("a"), 3),
+    ];
+    words.sort_by(|(left, _), (right, _)| left.compare(right).unwrap());
+    let sorted: Vec<String> = words
+        .iter()
+        .map(|(word, index)| format!("{}{}", word, index))
+        .collect();
+    assert_eq!(sorted, ["a1", "a3", "b0", "b2"]);
+}
+
+#[test]
+fn mixed_types_nil_and_nan_are_refused() {
+    assert_eq!(
+        float(1.0).compare(&string("1")),
+        Err("Can't compare number with string.".to_string())
+    );
+    assert_eq!(
+        Object::Nil.compare(&Object::Nil),
+        Err("Can't compare nil with nil.".to_string())
+    );
+    assert_eq!(
+        Object::Boolean(true).compare(&Object::Nil),
+        Err("Can't compare boolean with nil.".to_string())
+    );
+    assert_eq!(
+        float(f64::NAN).compare(&float(1.0)),
+        Err("NaN can't be compared.".to_string())
+    );
+}
@@ -0,0 +1,149 @@
+//! Checks that `eval_const` agrees with the interpreter, on hand-picked and on randomly
+//! generated constant expressions.
+
+use rlox::{
+    ast::{eval_const, expr::Expr},
+    engine::RLoxEngine,
+    number::Number,
+    parser::Parser,
+    scanner::Scanner,
+    tokens::{Object, Token, TokenType},
+};
+
+fn parse(source: &str) -> Expr {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens();
+    Parser::new(scanner.tokens).parse_expression().unwrap()
+}
+
+fn constant(source: &str) -> Option<String> {
+    let engine = RLoxEngine::new();
+    eval_const(&parse(source)).map(|value| engine.inspect_value(&value))
+}
+
+#[test]
+fn constant_expressions_are_evaluated() {
+    assert_eq!(constant("(1 + 2) * 3").as_deref(), Some("9"));
+    assert_eq!(constant("-(4 / 2) < 0").as_deref(), Some("true"));
+    assert_eq!(constant("\"a\" + \"b\"").as_deref(), Some("\"ab\""));
+    assert_eq!(constant("!nil == true").as_deref(), Some("true"));
+}
+
+#[test]
+fn variables_are_not_constant_unless_skipped() {
+    assert_eq!(constant("a + 1"), None);
+    assert_eq!(constant("true or a").as_deref(), Some("true"));
+    assert_eq!(constant("false or a"), None);
+}
+
+#[test]
+fn what_would_fail_at_runtime_is_not_constant() {
+    assert_eq!(constant("1 / 0"), None);
+    assert_eq!(constant("-\"a\""), None);
+    assert_eq!(constant("1 + \"a\""), None);
+}
+
+/// A xorshift generator, so that failures reproduce.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+
+    fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.below(choices.len() as u64) as usize]
+    }
+}
+
+fn token(token_type: TokenType, lexeme: &str) -> Token {
+    Token {
+        token_type,
+        lexeme: lexeme.to_string(),
+        literal: None,
+        line: 1,
+        offset: 0,
+        symbol: None,
+    }
+}
+
+/// A random expression of literals and operators at most `depth` deep.
+fn generate(random: &mut Random, depth: usize) -> Expr {
+    let choice = if depth == 0 { 0 } else { random.below(5) };
+    match choice {
+        0 => {
+            let value = match random.below(5) {
+                0 => Object::Nil,
+                1 => Object::Boolean(random.below(2) == 0),
+                2 => Object::String(random.pick(&["", "a", "bc"]).to_string()),
+                3 => Object::Number(Number::Int(random.below(7) as i64 - 2)),
+                _ => Object::Number(Number::Float(random.below(9) as f64 / 4.0)),
+            };
+            Expr::Literal { value, span: None }
+        }
+        1 => Expr::Grouping {
+            expression: Box::new(generate(random, depth - 1)),
+            span: None,
+        },
+        2 => {
+            let (token_type, lexeme) =
+                random.pick(&[(TokenType::Minus, "-"), (TokenType::Bang, "!")]);
+            Expr::Unary {
+                operator: token(token_type, lexeme),
+                right: Box::new(generate(random, depth - 1)),
+            }
+        }
+        3 => {
+            let (token_type, lexeme) =
+                random.pick(&[(TokenType::And, "and"), (TokenType::Or, "or")]);
+            Expr::Logical {
+                left: Box::new(generate(random, depth - 1)),
+                operator: token(token_type, lexeme),
+                right: Box::new(generate(random, depth - 1)),
+            }
+        }
+        _ => {
+            let (token_type, lexeme) = random.pick(&[
+                (TokenType::Plus, "+"),
+                (TokenType::Minus, "-"),
+                (TokenType::Star, "*"),
+                (TokenType::Slash, "/"),
+                (TokenType::Less, "<"),
+                (TokenType::GreaterEqual, ">="),
+                (TokenType::EqualEqual, "=="),
+                (TokenType::BangEqual, "!="),
+            ]);
+            Expr::Binary {
+                left: Box::new(generate(random, depth - 1)),
+                operator: token(token_type, lexeme),
+                right: Box::new(generate(random, depth - 1)),
+            }
+        }
+    }
+}
+
+#[test]
+fn eval_const_agrees_with_the_interpreter() {
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let mut engine = RLoxEngine::new();
+    let mut constants = 0;
+    for _ in 0..2_000 {
+        let expr = generate(&mut random, 4);
+        let expected = engine
+            .evaluate(&expr)
+            .ok()
+            .map(|value| engine.inspect_value(&value));
+        let actual = eval_const(&expr).map(|value| engine.inspect_value(&value));
+        assert_eq!(actual, expected, "for {:?}", expr);
+        constants += actual.is_some() as usize;
+    }
+    // both outcomes are exercised
+    assert!(
+        constants > 200 && constants < 1_800,
+        "{} constants",
+        constants
+    );
+}
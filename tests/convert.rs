@@ -0,0 +1,73 @@
+//! Converts between Lox values and JSON, directly and through an engine's globals.
+
+use rlox::{engine::RLoxEngine, number::Number, tokens::Object};
+use serde_json::{json, Value};
+
+#[test]
+fn json_values_round_trip() {
+    for value in [
+        json!(null),
+        json!(true),
+        json!(false),
+        json!(0.0),
+        json!(-2.5),
+        json!(1e300),
+        json!(""),
+        json!("naïve ☃"),
+    ] {
+        let object = Object::from_json(&value).unwrap();
+        assert_eq!(object.to_json(), value, "{:?}", object);
+    }
+}
+
+#[test]
+fn json_integers_stay_integers() {
+    let object = Object::from_json(&json!(42)).unwrap();
+    assert!(matches!(object, Object::Number(Number::Int(42))));
+    assert_eq!(object.to_json(), json!(42));
+
+    let object = Object::from_json(&json!(i64::MIN)).unwrap();
+    assert_eq!(object.to_json(), json!(i64::MIN));
+
+    let object = Object::from_json(&json!(u64::MAX)).unwrap();
+    assert!(matches!(object, Object::Number(Number::Float(number)) if number == u64::MAX as f64));
+}
+
+#[test]
+fn non_finite_numbers_become_null() {
+    for number in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+        assert_eq!(Object::Number(Number::Float(number)).to_json(), Value::Null);
+    }
+}
+
+#[test]
+fn arrays_and_objects_are_refused() {
+    assert_eq!(
+        Object::from_json(&json!([1, 2])).unwrap_err().to_string(),
+        "Lox has no lists, so JSON arrays can't be converted."
+    );
+    assert_eq!(
+        Object::from_json(&json!({ "a": { "b": 1 } }))
+            .unwrap_err()
+            .to_string(),
+        "Lox has no maps, so JSON objects can't be converted."
+    );
+}
+
+#[test]
+fn globals_can_be_set_and_read_as_json() {
+    let mut engine = RLoxEngine::new();
+    engine.set_global_json("width", &json!(80)).unwrap();
+    engine.set_global_json("title", &json!("rlox")).unwrap();
+    assert!(engine.set_global_json("list", &json!([])).is_err());
+
+    engine
+        .run_source(
+            "config",
+            "var area = width * width / 2; var name = title + \"!\";",
+        )
+        .unwrap();
+    assert_eq!(engine.get_global_json("area"), Some(json!(3200)));
+    assert_eq!(engine.get_global_json("name"), Some(json!("rlox!")));
+    assert_eq!(engine.get_global_json("list"), None);
+}
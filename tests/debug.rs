@@ -0,0 +1,44 @@
+//! Checks what `debug` statements print, and that they can be stripped.
+
+use rlox::{engine::RLoxEngine, options::InterpreterOptions};
+
+fn capturing(options: InterpreterOptions) -> RLoxEngine {
+    RLoxEngine::with_options(options.capture_output(true)).unwrap()
+}
+
+#[test]
+fn debug_prints_the_source_and_value_of_the_expression() {
+    let mut engine = capturing(InterpreterOptions::default());
+    engine
+        .run_source(
+            "main.lox",
+            "var a = 2;\ndebug a  *  (1+2) /* three */ ;\nprint \"after\";\ndebug \"x\" + \"y\";",
+        )
+        .unwrap();
+    assert_eq!(
+        engine.take_output(),
+        "[main.lox:2] a * (1+2) = 6\nafter\n[main.lox:4] \"x\" + \"y\" = xy\n"
+    );
+
+    engine.eval("debug 0.1 + 0.2;").unwrap();
+    assert_eq!(
+        engine.take_output(),
+        "[<eval>:1] 0.1 + 0.2 = 0.30000000000000004\n"
+    );
+}
+
+#[test]
+fn debug_formats_numbers_like_print() {
+    let mut engine = capturing(InterpreterOptions::default().float_precision(1));
+    engine.run_source("main.lox", "debug 1 / 3;").unwrap();
+    assert_eq!(engine.take_output(), "[main.lox:1] 1 / 3 = 0.3\n");
+}
+
+#[test]
+fn stripped_debug_statements_are_not_evaluated() {
+    let mut engine = capturing(InterpreterOptions::default().strip_debug(true));
+    engine
+        .run_source("main.lox", "debug undefined;\nprint 1;")
+        .unwrap();
+    assert_eq!(engine.take_output(), "1\n");
+}
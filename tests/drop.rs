@@ -0,0 +1,51 @@
+//! Checks that trees far deeper than the parser allows can be freed, as a generator might
+//! build them.
+
+use rlox::{
+    ast::{expr::Expr, stmt::Stmt},
+    number::Number,
+    tokens::{Object, Token, TokenType},
+};
+
+const DEPTH: usize = 200_000;
+
+fn number(value: i64) -> Expr {
+    Expr::Literal {
+        value: Object::Number(Number::Int(value)),
+        span: None,
+    }
+}
+
+#[test]
+fn deeply_nested_groupings_drop() {
+    let mut expr = number(1);
+    for _ in 0..DEPTH {
+        expr = Expr::Grouping {
+            expression: Box::new(expr),
+            span: None,
+        };
+    }
+    drop(expr);
+}
+
+#[test]
+fn long_operator_chains_in_a_program_drop() {
+    let plus = Token {
+        token_type: TokenType::Plus,
+        lexeme: "+".to_string(),
+        literal: None,
+        line: 1,
+        offset: 0,
+        symbol: None,
+    };
+    let mut expr = number(1);
+    for _ in 0..DEPTH {
+        expr = Expr::Binary {
+            left: Box::new(expr),
+            operator: plus.clone(),
+            right: Box::new(number(1)),
+        };
+    }
+    let program = vec![Stmt::Print { expression: expr }];
+    drop(program);
+}
@@ -0,0 +1,56 @@
+//! Checks that words reserved after an edition stay usable as names in programs written
+//! for it.
+
+use rlox::{
+    engine::RLoxEngine,
+    keywords::{self, Edition},
+    options::InterpreterOptions,
+    tokens::TokenType,
+};
+
+const SOURCE: &str = "var debug = 1;\nvar assert = debug + 1;\nprint assert;";
+
+fn engine(edition: Edition) -> RLoxEngine {
+    RLoxEngine::with_options(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .edition(edition),
+    )
+    .unwrap()
+}
+
+#[test]
+fn newer_keywords_are_names_in_the_2015_edition() {
+    let mut engine = engine(Edition::Lox2015);
+    engine.run_source("old.lox", SOURCE).unwrap();
+    assert_eq!(engine.take_output(), "2\n");
+    assert_eq!(engine.eval("debug").unwrap().to_string(), "1");
+}
+
+#[test]
+fn newer_keywords_are_reserved_in_the_latest_edition() {
+    let diagnostics = engine(Edition::Latest)
+        .run_source("new.lox", SOURCE)
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[new.lox, Line 1] Error: Expected identifier after 'var' but found 'debug'."
+    );
+    assert_eq!(RLoxEngine::new().edition(), Edition::Latest);
+}
+
+#[test]
+fn keywords_belong_to_the_edition_that_introduced_them() {
+    assert_eq!(
+        keywords::keyword("print", Edition::Lox2015),
+        Some(TokenType::Print)
+    );
+    assert_eq!(keywords::keyword("debug", Edition::Lox2015), None);
+    assert_eq!(
+        keywords::keyword("debug", Edition::Latest),
+        Some(TokenType::Debug)
+    );
+    assert_eq!(keywords::keyword("count", Edition::Latest), None);
+    assert_eq!(Edition::Lox2015.to_string(), "2015");
+    assert_eq!(Edition::Latest.to_string(), "latest");
+}
@@ -0,0 +1,218 @@
+//! Exercises the embedding API in `rlox::engine` the way a host program uses it.
+
+use std::thread;
+
+use rlox::{engine::RLoxEngine, tokens::Object};
+
+fn capturing_engine() -> RLoxEngine {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine
+}
+
+#[test]
+fn take_output_returns_what_was_printed_since_the_last_take() {
+    let mut engine = capturing_engine();
+    engine
+        .run_source("first", "print 1; print \"two\";")
+        .unwrap();
+    assert_eq!(engine.take_output(), "1\ntwo\n");
+    assert_eq!(engine.take_output(), "");
+
+    engine.run_source("second", "print true;").unwrap();
+    engine.eval("print nil;").unwrap();
+    assert_eq!(engine.take_output(), "true\n\n");
+}
+
+#[test]
+fn output_survives_a_runtime_error() {
+    let mut engine = capturing_engine();
+    let diagnostics = engine
+        .run_source(
+            "script",
+            "print \"before\";\nprint -\"x\";\nprint \"after\";",
+        )
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[script, Line 2] Error: Operand must be a number."
+    );
+    assert_eq!(engine.take_output(), "before\n");
+}
+
+#[test]
+fn nothing_is_printed_when_a_source_does_not_parse() {
+    let mut engine = capturing_engine();
+    engine
+        .run_sources(&[("good", "print 1;"), ("bad", "print ;")])
+        .unwrap_err();
+    assert_eq!(engine.take_output(), "");
+}
+
+#[test]
+fn turning_capture_off_drops_untaken_output() {
+    let mut engine = capturing_engine();
+    engine.run_source("script", "print 1;").unwrap();
+    engine.capture_output(false);
+    engine.capture_output(true);
+    assert_eq!(engine.take_output(), "");
+}
+
+#[test]
+fn snapshots_round_trip_every_kind_of_value() {
+    let mut engine = RLoxEngine::new();
+    // a literal can't overflow to infinity, but a product can
+    let big = format!("1{}", "0".repeat(300));
+    engine
+        .run_source(
+            "script",
+            &format!(
+                "var nil_ = nil; var yes = true; var no = false; var pi = 3.14159; \
+                 var text = \"two words\"; var inf = {big} * {big}; \
+                 var minus_inf = -{big} * {big}; var nan = inf - inf;"
+            ),
+        )
+        .unwrap();
+
+    let restored = RLoxEngine::restore(&engine.snapshot()).unwrap();
+    let mut globals: Vec<String> = restored
+        .globals()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect();
+    globals.sort();
+    assert_eq!(
+        globals,
+        [
+            "inf = inf",
+            "minus_inf = -inf",
+            "nan = NaN",
+            "nil_ = nil",
+            "no = false",
+            "pi = 3.14159",
+            "text = two words",
+            "yes = true",
+        ]
+    );
+}
+
+#[test]
+fn a_restored_engine_keeps_running_scripts() {
+    let mut engine = RLoxEngine::new();
+    engine
+        .run_source("script", "var greeting = \"hello\"; var count = 2;")
+        .unwrap();
+
+    let mut restored = RLoxEngine::restore(&engine.snapshot()).unwrap();
+    restored.capture_output(true);
+    restored
+        .run_source(
+            "next",
+            "var count = count + 1; print greeting + \" again\";",
+        )
+        .unwrap();
+    assert_eq!(restored.take_output(), "hello again\n");
+    assert_eq!(restored.eval("count").unwrap().to_string(), "3");
+}
+
+#[test]
+fn restoring_a_bad_snapshot_fails_cleanly() {
+    let restore_error = |bytes: &[u8]| RLoxEngine::restore(bytes).err().unwrap().to_string();
+
+    assert_eq!(restore_error(b"var a = 1;"), "Not an rlox snapshot.");
+    assert_eq!(
+        restore_error(b"rloxs 99\n{}"),
+        "Snapshot format version 99 is not supported; expected 2."
+    );
+    assert!(restore_error(
+        b"rloxs 2\n{\"globals\":{\"f\":{\"type\":\"Function\",\"value\":\"f\"}},\"builtins\":[]}"
+    )
+    .starts_with("Invalid snapshot: unknown variant `Function`"),);
+}
+
+#[test]
+fn engines_are_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<RLoxEngine>();
+}
+
+#[test]
+fn an_engine_runs_on_a_worker_thread() {
+    let mut engine = capturing_engine();
+    engine
+        .run_source("setup", "var greeting = \"hi\";")
+        .unwrap();
+
+    let worker = thread::spawn(move || {
+        engine
+            .run_source("worker", "print greeting + \" from a thread\";")
+            .unwrap();
+        engine.take_output()
+    });
+    assert_eq!(worker.join().unwrap(), "hi from a thread\n");
+}
+
+#[test]
+fn engines_do_not_share_state() {
+    let mut first = capturing_engine();
+    let mut second = capturing_engine();
+    first.run_source("first", "var x = 1;").unwrap();
+    second.run_source("second", "var x = \"two\";").unwrap();
+
+    for _ in 0..3 {
+        first.eval("var x = x + 1;").unwrap();
+        second.eval("var x = x + \"!\";").unwrap();
+    }
+    assert_eq!(first.eval("x").unwrap().to_string(), "4");
+    assert_eq!(second.eval("x").unwrap().to_string(), "two!!!");
+
+    first.eval("print x;").unwrap();
+    let diagnostics = second.eval("print -x;").unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[<eval>, Line 1] Error: Operand must be a number."
+    );
+    assert_eq!(first.take_output(), "4\n");
+    assert_eq!(second.take_output(), "");
+}
+
+#[test]
+fn equal_state_lists_and_serializes_identically() {
+    let mut first = RLoxEngine::new();
+    first
+        .run_source("first", "var zeta = 1; var alpha = \"a\"; var mid = nil;")
+        .unwrap();
+    first.define_builtin("clock", Object::Boolean(true));
+    first.define_builtin("args", Object::Nil);
+
+    let mut second = RLoxEngine::new();
+    second.define_builtin("args", Object::Nil);
+    second
+        .run_source("second", "var mid = nil; var alpha = \"a\";")
+        .unwrap();
+    second.define_builtin("clock", Object::Boolean(true));
+    second.run_source("second", "var zeta = 1;").unwrap();
+
+    assert_eq!(first.snapshot(), second.snapshot());
+    let names: Vec<&str> = first.globals().map(|(name, _)| name).collect();
+    assert_eq!(names, ["alpha", "args", "clock", "mid", "zeta"]);
+}
+
+#[test]
+fn scan_and_parse_errors_are_reported_in_source_order() {
+    let mut engine = RLoxEngine::new();
+    let diagnostics = engine
+        .run_source("script", "print 1 +;\nprint 2;\nvar a = @;")
+        .unwrap_err();
+    let errors: Vec<(Option<u64>, &str)> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.error.line(), diagnostic.error.message()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (Some(1), "Expected expression but found ';'."),
+            (Some(3), "Unexpected character @"),
+            (Some(3), "Expected expression but found ';'."),
+        ]
+    );
+}
@@ -0,0 +1,120 @@
+//! Checks the event log, and that every way of running the programs under `tests/lox`
+//! produces the same one.
+
+use std::{fs, path::Path, thread};
+
+use rlox::{
+    ast::json, engine::RLoxEngine, execution::Execution, interpreter::Event, timings::Timings,
+};
+
+fn engine() -> RLoxEngine {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.record_events(true);
+    engine
+}
+
+#[test]
+fn the_log_holds_output_globals_and_errors_in_order() {
+    let mut engine = engine();
+    let source = "var a = \"x\";\nprint a + \"y\";\nvar b = 1.5 * 2;\nprint -a;\nprint b;";
+    engine.run_source("program", source).unwrap_err();
+    assert_eq!(
+        engine.take_events(),
+        [
+            Event::Global {
+                name: "a".to_string(),
+                value: "\"x\"".to_string()
+            },
+            Event::Output("xy".to_string()),
+            Event::Global {
+                name: "b".to_string(),
+                value: "3".to_string()
+            },
+            Event::Error("[Line 4] Error: Operand must be a number.".to_string()),
+        ]
+    );
+    assert!(engine.take_events().is_empty());
+}
+
+#[test]
+fn nothing_is_recorded_unless_asked() {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.run_source("program", "var a = 1; print a;").unwrap();
+    assert!(engine.take_events().is_empty());
+}
+
+/// The events of running `source` straight through.
+fn run_straight(source: &str) -> Vec<Event> {
+    let mut engine = engine();
+    let _ = engine.run_source("program", source);
+    engine.take_events()
+}
+
+/// The events of running the tree of `source` after a round trip through JSON, as
+/// `--run-ast` does, or `None` if the tree nests too deeply for the JSON reader.
+fn run_from_json(source: &str) -> Option<Vec<Event>> {
+    let statements =
+        RLoxEngine::parse_source(source.to_string(), false, &mut Timings::default()).unwrap();
+    let tree = serde_json::to_string(&statements).unwrap();
+    let statements = json::from_json(&tree).ok()?;
+    let mut engine = engine();
+    let _ = engine.run_program("program", statements);
+    Some(engine.take_events())
+}
+
+/// The events of running `source` one top-level statement at a time.
+fn run_budgeted(source: &str) -> Vec<Event> {
+    let mut engine = engine();
+    let mut execution = engine.run_budgeted("program", source, 1);
+    while let Execution::Paused(handle) = execution {
+        execution = handle.resume(&mut engine, 1);
+    }
+    engine.take_events()
+}
+
+fn lox_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            lox_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            files.push(path);
+        }
+    }
+}
+
+#[test]
+fn every_way_of_running_the_corpus_agrees() {
+    let mut files = vec![];
+    lox_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"),
+        &mut files,
+    );
+    // some programs nest as deeply as the parser allows, which needs more stack than a
+    // test thread has
+    let checked = thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(move || {
+            let mut checked = 0;
+            for path in files {
+                let source = fs::read_to_string(&path).unwrap();
+                if RLoxEngine::parse_source(source.clone(), false, &mut Timings::default()).is_err()
+                {
+                    continue;
+                }
+                let straight = run_straight(&source);
+                if let Some(from_json) = run_from_json(&source) {
+                    assert_eq!(from_json, straight, "{}", path.display());
+                }
+                assert_eq!(run_budgeted(&source), straight, "{}", path.display());
+                checked += 1;
+            }
+            checked
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(checked > 20, "only {} programs ran", checked);
+}
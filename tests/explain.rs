@@ -0,0 +1,77 @@
+//! Checks the step-by-step transcripts `:explain` and `--explain` print.
+
+use rlox::{engine::RLoxEngine, number::Number, tokens::Object};
+
+fn explain(engine: &mut RLoxEngine, expression: &str) -> Vec<String> {
+    engine
+        .explain(expression)
+        .unwrap_or_else(|diagnostics| panic!("{}", diagnostics[0].error))
+}
+
+#[test]
+fn groupings_reduce_before_what_contains_them() {
+    let mut engine = RLoxEngine::new();
+    assert_eq!(
+        explain(&mut engine, "(1 + 2) * 3"),
+        ["(1 + 2) * 3", "3 * 3", "9"]
+    );
+}
+
+#[test]
+fn steps_follow_precedence() {
+    let mut engine = RLoxEngine::new();
+    assert_eq!(
+        explain(&mut engine, "1 + 2 * 3 - 4 / 2"),
+        [
+            "1 + 2 * 3 - 4 / 2",
+            "1 + 6 - 4 / 2",
+            "7 - 4 / 2",
+            "7 - 2",
+            "5"
+        ]
+    );
+}
+
+#[test]
+fn unary_operators_reduce_their_operand_first() {
+    let mut engine = RLoxEngine::new();
+    assert_eq!(
+        explain(&mut engine, "-(2 - 5) * 2"),
+        ["-(2 - 5) * 2", "-(-3) * 2", "3 * 2", "6"]
+    );
+    assert_eq!(
+        explain(&mut engine, "!(1 > 2)"),
+        ["!(1 > 2)", "!false", "true"]
+    );
+}
+
+#[test]
+fn variables_are_a_step_of_their_own() {
+    let mut engine = RLoxEngine::new();
+    engine.define("a", Object::Number(Number::Int(4)));
+    assert_eq!(
+        explain(&mut engine, "a * (a + 1)"),
+        ["a * (a + 1)", "4 * (a + 1)", "4 * (4 + 1)", "4 * 5", "20"]
+    );
+}
+
+#[test]
+fn short_circuits_skip_the_right_operand() {
+    let mut engine = RLoxEngine::new();
+    assert_eq!(
+        explain(&mut engine, "\"a\" + \"b\" == \"ab\" or nil"),
+        [
+            "\"a\" + \"b\" == \"ab\" or nil",
+            "\"ab\" == \"ab\" or nil",
+            "true or nil",
+            "true"
+        ]
+    );
+}
+
+#[test]
+fn a_runtime_error_is_returned_instead_of_a_transcript() {
+    let mut engine = RLoxEngine::new();
+    let diagnostics = engine.explain("1 + -\"a\"").unwrap_err();
+    assert_eq!(diagnostics[0].error.exit_code(), 70);
+}
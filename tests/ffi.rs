@@ -0,0 +1,85 @@
+//! Calls the `ffi` feature's C functions the way a C host would:
+//!
+//! ```bash
+//! cargo test --features ffi --test ffi
+//! ```
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, CStr, CString};
+
+use rlox::ffi::*;
+
+/// Copies a string the engine handed out.
+fn read(string: *const c_char) -> String {
+    assert!(!string.is_null());
+    unsafe { CStr::from_ptr(string) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn run(engine: *mut RLoxHandle, source: &str) -> i32 {
+    let source = CString::new(source).unwrap();
+    unsafe { rlox_run(engine, source.as_ptr()) }
+}
+
+#[test]
+fn globals_persist_and_output_is_per_run() {
+    let engine = rlox_engine_new();
+    assert_eq!(run(engine, "var a = 1; print a;"), RLOX_OK);
+    assert_eq!(read(unsafe { rlox_last_output(engine) }), "1\n");
+    assert_eq!(read(unsafe { rlox_last_error(engine) }), "");
+
+    assert_eq!(run(engine, "print a + 1;"), RLOX_OK);
+    assert_eq!(read(unsafe { rlox_last_output(engine) }), "2\n");
+    unsafe { rlox_engine_free(engine) };
+}
+
+#[test]
+fn errors_are_reported_with_exit_codes() {
+    let engine = rlox_engine_new();
+    assert_eq!(run(engine, "print ;"), RLOX_COMPILE_ERROR);
+    assert_eq!(
+        read(unsafe { rlox_last_error(engine) }),
+        "[Line 1] Error: Expected expression but found ';'."
+    );
+
+    assert_eq!(
+        run(engine, "print \"before\";\nprint -nil;"),
+        RLOX_RUNTIME_ERROR
+    );
+    assert_eq!(read(unsafe { rlox_last_output(engine) }), "before\n");
+    assert_eq!(
+        read(unsafe { rlox_last_error(engine) }),
+        "[Line 2] Error: Operand must be a number."
+    );
+
+    assert_eq!(run(engine, "print 1;"), RLOX_OK);
+    assert_eq!(read(unsafe { rlox_last_error(engine) }), "");
+    unsafe { rlox_engine_free(engine) };
+}
+
+#[test]
+fn bad_arguments_are_rejected() {
+    unsafe {
+        assert_eq!(
+            rlox_run(std::ptr::null_mut(), c"print 1;".as_ptr()),
+            RLOX_INVALID_ARGUMENT
+        );
+        assert!(rlox_last_output(std::ptr::null()).is_null());
+        rlox_engine_free(std::ptr::null_mut());
+
+        let engine = rlox_engine_new();
+        assert_eq!(rlox_run(engine, std::ptr::null()), RLOX_INVALID_ARGUMENT);
+        let invalid = [b'"', 0xff, b'"', b';', 0];
+        assert_eq!(
+            rlox_run(engine, invalid.as_ptr().cast()),
+            RLOX_INVALID_ARGUMENT
+        );
+        assert_eq!(
+            read(rlox_last_error(engine)),
+            "The source is not valid UTF-8."
+        );
+        rlox_engine_free(engine);
+    }
+}
@@ -0,0 +1,33 @@
+//! Checks `rlox fmt`'s output: canonical spacing, kept comments, and that formatted code
+//! formats to itself.
+
+use rlox::{ast::format::Formatter, keywords::Edition};
+
+fn format(source: &str) -> String {
+    Formatter::format_source(source, Edition::Latest).unwrap()
+}
+
+const MESSY: &str =
+    "var   x=1+2 ;\nx; // c\n\n\n/* c */\nprint   x*2;   // end\nif(x)print x;else print-x;\n";
+
+#[test]
+fn formatting_twice_changes_nothing_more() {
+    let once = format(MESSY);
+    assert_eq!(
+        once,
+        "var x = 1 + 2;\nx; // c\n\n/* c */\nprint x * 2; // end\nif (x) print x; else print -x;\n"
+    );
+    assert_eq!(format(&once), once);
+}
+
+#[test]
+fn a_comment_at_the_end_of_a_line_stays_there() {
+    assert_eq!(format("x; // c\n"), "x; // c\n");
+    assert_eq!(format("x;/* c */\n"), "x; /* c */\n");
+}
+
+#[test]
+fn a_comment_on_its_own_line_is_kept_in_place() {
+    assert_eq!(format("x;\n/* c */\ny;\n"), "x;\n/* c */\ny;\n");
+    assert_eq!(format("// first\nx;\n"), "// first\nx;\n");
+}
@@ -0,0 +1,46 @@
+//! Checks how values read when inspected, as the REPL echoes them.
+
+use rlox::{engine::RLoxEngine, number::Number, options::InterpreterOptions, tokens::Object};
+
+fn string(text: &str) -> Object {
+    Object::String(text.to_string())
+}
+
+#[test]
+fn values_other_than_strings_look_as_printed() {
+    let engine = RLoxEngine::new();
+    assert_eq!(engine.inspect_value(&Object::Nil), "nil");
+    assert_eq!(engine.inspect_value(&Object::Boolean(true)), "true");
+    assert_eq!(engine.inspect_value(&Object::Number(Number::Int(7))), "7");
+    assert_eq!(
+        engine.inspect_value(&Object::Number(Number::Float(2.0))),
+        "2"
+    );
+}
+
+#[test]
+fn strings_are_quoted_and_escaped() {
+    let engine = RLoxEngine::new();
+    assert_eq!(engine.inspect_value(&string("1")), "\"1\"");
+    assert_eq!(engine.inspect_value(&string("")), "\"\"");
+    assert_eq!(
+        engine.inspect_value(&string("a\nb\tc\r")),
+        "\"a\\nb\\tc\\r\""
+    );
+    assert_eq!(
+        engine.inspect_value(&string("say \"hi\" \\ bye")),
+        "\"say \\\"hi\\\" \\\\ bye\""
+    );
+    assert_eq!(engine.inspect_value(&string("bell\u{7}")), "\"bell\\u{7}\"");
+    assert_eq!(engine.inspect_value(&string("héllo ✓")), "\"héllo ✓\"");
+}
+
+#[test]
+fn numbers_follow_the_float_precision() {
+    let engine =
+        RLoxEngine::with_options(InterpreterOptions::default().float_precision(2)).unwrap();
+    assert_eq!(
+        engine.inspect_value(&Object::Number(Number::Float(0.125))),
+        "0.12"
+    );
+}
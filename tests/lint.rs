@@ -0,0 +1,48 @@
+//! Checks what each lint rule warns about, and what it leaves alone.
+
+use rlox::{
+    ast::lint::{LintRule, Linter},
+    engine::RLoxEngine,
+    keywords::Edition,
+    timings::Timings,
+};
+
+/// The rule and line of every warning `source` gets with the rules in `allowed` off.
+fn warnings(source: &str, allowed: &[LintRule]) -> Vec<(LintRule, u64)> {
+    let statements =
+        RLoxEngine::parse_in_edition(source.to_string(), Edition::Latest, &mut Timings::default())
+            .unwrap();
+    Linter::lint_program(&statements, allowed)
+        .unwrap()
+        .into_iter()
+        .map(|warning| (warning.rule, warning.line))
+        .collect()
+}
+
+#[test]
+fn redundant_bool_comparison_warns_on_a_boolean_literal_operand() {
+    let source =
+        "var a = true;\nprint a == true;\nif (false != a) print a;\nwhile (a == false) print a;";
+    assert_eq!(
+        warnings(source, &[]),
+        [
+            (LintRule::RedundantBoolComparison, 2),
+            (LintRule::RedundantBoolComparison, 3),
+            (LintRule::RedundantBoolComparison, 4),
+        ]
+    );
+}
+
+#[test]
+fn redundant_bool_comparison_ignores_other_comparisons() {
+    let source = "var a = true;\nprint a == nil;\nprint a != 1;\nprint a and true;\nprint !a == a;";
+    assert_eq!(warnings(source, &[]), []);
+}
+
+#[test]
+fn allowed_rules_dont_warn() {
+    assert_eq!(
+        warnings("print 1 == true;", &[LintRule::RedundantBoolComparison]),
+        []
+    );
+}
@@ -0,0 +1,79 @@
+//! Checks what `scan_one` and `expr_at` find at each offset of a fixture, for editors'
+//! hover.
+
+use rlox::{
+    ast::{format::Formatter, locate::expr_at},
+    engine::RLoxEngine,
+    scanner::scan_one,
+    timings::Timings,
+};
+
+const FIXTURE: &str = "var name = \"lox\"; // greeting\nprint -(1 + 2) * count;\n";
+
+/// The offset of the `nth` byte of the first occurrence of `text` in the fixture.
+fn at(text: &str, nth: usize) -> usize {
+    FIXTURE.find(text).expect("the text is in the fixture") + nth
+}
+
+#[test]
+fn scan_one_finds_the_token_at_an_offset() {
+    let cases = [
+        (at("var", 0), Some("var")),
+        (at("var", 2), Some("var")),
+        (at(" name", 0), None),
+        (at("\"lox\"", 0), Some("\"lox\"")),
+        (at("\"lox\"", 2), Some("\"lox\"")),
+        // the string ends where the ';' begins
+        (at(";", 0), Some(";")),
+        (at("// greeting", 4), None),
+        (at("\nprint", 0), None),
+        (at("-(", 1), Some("(")),
+        (at("count", 4), Some("count")),
+        (FIXTURE.len(), None),
+    ];
+    for (offset, expected) in cases {
+        let token = scan_one(FIXTURE, offset);
+        assert_eq!(
+            token.as_ref().map(|token| token.lexeme.as_str()),
+            expected,
+            "at offset {}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn expr_at_finds_the_innermost_expression_at_an_offset() {
+    let program =
+        RLoxEngine::parse_source(FIXTURE.to_string(), false, &mut Timings::default()).unwrap();
+    let cases = [
+        (at("var", 0), None),
+        (at("name", 1), None),
+        (at("\"lox\"", 1), Some("\"lox\"")),
+        (at(";", 0), None),
+        (at("-(", 0), Some("-(1 + 2)")),
+        (at("(1", 0), Some("(1 + 2)")),
+        (at("1 +", 0), Some("1")),
+        // between the operands of `+`
+        (at("1 +", 1), None),
+        (at("+ 2", 0), Some("1 + 2")),
+        (at("2)", 1), Some("(1 + 2)")),
+        (at("* count", 0), Some("-(1 + 2) * count")),
+        (at("count", 2), Some("count")),
+        (at("count;", 5), None),
+    ];
+    for (offset, expected) in cases {
+        let expr = expr_at(&program, offset).map(|expr| Formatter::format_expr(expr).unwrap());
+        assert_eq!(expr.as_deref(), expected, "at offset {}", offset);
+    }
+}
+
+#[test]
+fn trees_without_spans_have_nothing_to_hover() {
+    let program =
+        RLoxEngine::parse_source("1 + 2;".to_string(), false, &mut Timings::default()).unwrap();
+    let json = serde_json::to_string(&program).unwrap();
+    let loaded = rlox::ast::json::from_json(&json).unwrap();
+    assert!(expr_at(&program, 0).is_some());
+    assert!(expr_at(&loaded, 0).is_none());
+}
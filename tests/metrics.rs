@@ -0,0 +1,66 @@
+//! Checks the sizes `ast::metrics` reports for hand-written programs.
+
+use rlox::{
+    ast::{self, metrics::AstMetrics},
+    engine::RLoxEngine,
+    timings::Timings,
+};
+
+fn measure(source: &str) -> AstMetrics {
+    let statements = RLoxEngine::parse_source(source.to_string(), false, &mut Timings::default())
+        .unwrap_or_else(|errors| panic!("{:?}", errors));
+    ast::metrics(&statements)
+}
+
+fn metrics(node_count: usize, max_depth: usize, statement_count: usize) -> AstMetrics {
+    AstMetrics {
+        node_count,
+        max_depth,
+        statement_count,
+    }
+}
+
+#[test]
+fn an_empty_program_has_no_nodes() {
+    assert_eq!(measure(""), metrics(0, 0, 0));
+}
+
+#[test]
+fn literals_and_variables_are_one_level_deep() {
+    assert_eq!(measure("print 1; a;"), metrics(4, 1, 2));
+}
+
+#[test]
+fn operators_and_groupings_nest() {
+    // print, +, 1, grouping, *, 2, -, 3
+    assert_eq!(measure("print 1 + (2 * -3);"), metrics(8, 5, 1));
+    // the left operand of a chain nests as deeply as the right one
+    assert_eq!(measure("(1 * 2) + 3;"), metrics(7, 4, 1));
+    assert_eq!(measure("1 + 2 + 3 + 4;"), metrics(8, 4, 1));
+}
+
+#[test]
+fn every_expression_of_a_statement_is_counted() {
+    // a var without an initializer is initialized with a nil literal
+    assert_eq!(
+        measure("var a; var b: number = 1 and 2; assert a, \"message\";"),
+        metrics(9, 2, 3)
+    );
+}
+
+#[test]
+fn metrics_of_programs_add_up() {
+    let mut total = measure("print (1);");
+    total.add(measure("1; 2;"));
+    assert_eq!(total, metrics(7, 2, 3));
+}
+
+#[test]
+fn the_engine_measures_what_it_runs() {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.run_source("a", "print 1 + 2;").unwrap();
+    engine.eval("3").unwrap();
+    assert_eq!(engine.take_metrics(), metrics(6, 2, 2));
+    assert_eq!(engine.take_metrics(), AstMetrics::default());
+}
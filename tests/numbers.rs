@@ -0,0 +1,110 @@
+//! Pins down how numbers at the edges of `f64` print, compare and scan: negative zero,
+//! literals too large for an `f64`, and rounding to zero from below.
+
+use rlox::{engine::RLoxEngine, number::Number, options::InterpreterOptions};
+
+fn printed(options: InterpreterOptions, expression: &str) -> String {
+    let mut engine = RLoxEngine::with_options(options.capture_output(true)).unwrap();
+    engine
+        .run_source("numbers", &format!("print {};", expression))
+        .unwrap_or_else(|diagnostics| panic!("{}: {}", expression, diagnostics[0]));
+    engine.take_output().trim_end().to_string()
+}
+
+#[test]
+fn edge_values_print_and_compare_consistently() {
+    let largest = format!("17976931348623157{}", "0".repeat(292));
+    let cases = [
+        // negative zero shows as zero, however it came about
+        ("-0", "0"),
+        ("-0.0", "0"),
+        ("0.0 * -1", "0"),
+        ("-(0.0 * -1)", "0"),
+        ("0 * -1", "0"),
+        // and equals zero without being less than it
+        ("0 == -0", "true"),
+        ("0.0 == 0.0 * -1", "true"),
+        ("0.0 * -1 != 0", "false"),
+        ("0.0 * -1 < 0", "false"),
+        ("0.0 * -1 >= 0", "true"),
+        // integers too large for an i64 become floats, up to the largest f64
+        ("99999999999999999999", "100000000000000000000"),
+        (largest.as_str(), largest.as_str()),
+        ("-1.5", "-1.5"),
+    ];
+    for (expression, expected) in cases {
+        assert_eq!(
+            printed(InterpreterOptions::default(), expression),
+            expected,
+            "print {};",
+            expression
+        );
+    }
+}
+
+#[test]
+fn rounding_to_zero_from_below_drops_the_sign() {
+    let cases = [("-0.001", "0.00"), ("-0.005", "-0.01"), ("-1.5", "-1.50")];
+    for (expression, expected) in cases {
+        assert_eq!(
+            printed(InterpreterOptions::default().float_precision(2), expression),
+            expected,
+            "print {};",
+            expression
+        );
+    }
+    assert_eq!(Number::Float(-0.0).format(Some(3)), "0");
+}
+
+#[test]
+fn literals_too_large_for_an_f64_are_scan_errors() {
+    for literal in [
+        // just past f64::MAX, which would round to infinity
+        format!("18{}", "0".repeat(307)),
+        format!("1{}", "0".repeat(400)),
+        format!("1{}.5", "0".repeat(400)),
+    ] {
+        let mut engine = RLoxEngine::new();
+        let diagnostics = engine
+            .run_source("numbers", &format!("print {};", literal))
+            .unwrap_err();
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "[numbers, Line 1] Error: Number literal out of range."
+        );
+        assert_eq!(diagnostics[0].error.exit_code(), 65);
+    }
+    assert!(Number::parse(&format!("1{}", "0".repeat(400))).is_none());
+}
+
+#[test]
+fn ints_and_floats_compare_by_their_exact_values() {
+    let cases = [
+        // 2^53 + 1 has no f64 of its own and would round to 2^53
+        ("9007199254740993 == 9007199254740992.0", "false"),
+        ("9007199254740992.0 == 9007199254740993", "false"),
+        ("9007199254740993 > 9007199254740992.0", "true"),
+        ("9007199254740992.0 < 9007199254740993", "true"),
+        ("9007199254740992 == 9007199254740992.0", "true"),
+        ("9007199254740993 != 9007199254740992.0", "true"),
+        // i64::MAX is below 2^63, which is what it rounds to as an f64
+        ("9223372036854775807 < 9223372036854775808.0", "true"),
+        ("9223372036854775807 == 9223372036854775808.0", "false"),
+        ("2.5 < 3", "true"),
+        ("-2.5 > -3", "true"),
+        ("1 == 1.0", "true"),
+    ];
+    for (expression, expected) in cases {
+        assert_eq!(
+            printed(InterpreterOptions::default(), expression),
+            expected,
+            "print {};",
+            expression
+        );
+    }
+    assert!(Number::Int(i64::MIN) == Number::Float(-9_223_372_036_854_775_808.0));
+    assert!(Number::Int(1) < Number::Float(f64::INFINITY));
+    assert!(Number::Int(1) > Number::Float(f64::NEG_INFINITY));
+    assert_eq!(Number::Int(1).partial_cmp(&Number::Float(f64::NAN)), None);
+}
@@ -0,0 +1,451 @@
+//! Checks that every `InterpreterOptions` setting reaches the behavior it controls.
+
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rlox::{engine::RLoxEngine, options::InterpreterOptions};
+
+/// A writer whose contents stay readable after the engine took ownership of it.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer that always fails.
+struct BrokenPipe;
+
+impl Write for BrokenPipe {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer that records what had been written each time it was flushed.
+#[derive(Clone, Default)]
+struct FlushLog {
+    pending: Arc<Mutex<Vec<u8>>>,
+    flushed: Arc<Mutex<Vec<String>>>,
+}
+
+impl FlushLog {
+    fn flushes(&self) -> Vec<String> {
+        self.flushed.lock().unwrap().clone()
+    }
+}
+
+impl Write for FlushLog {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.pending.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if !pending.is_empty() {
+            let text = String::from_utf8(pending).unwrap();
+            self.flushed.lock().unwrap().push(text);
+        }
+        Ok(())
+    }
+}
+
+fn engine(options: InterpreterOptions) -> RLoxEngine {
+    RLoxEngine::with_options(options.build().unwrap()).unwrap()
+}
+
+#[test]
+fn writer_receives_the_output() {
+    let buffer = SharedBuffer::default();
+    let mut engine = engine(InterpreterOptions::default().writer(buffer.clone()));
+    engine
+        .run_source("script", "print 1; print \"two\";")
+        .unwrap();
+    assert_eq!(buffer.contents(), "1\ntwo\n");
+}
+
+#[test]
+fn a_failing_writer_stops_the_run() {
+    let mut engine = engine(InterpreterOptions::default().writer(BrokenPipe));
+    let diagnostics = engine.run_source("script", "print 1;").unwrap_err();
+    assert!(diagnostics[0]
+        .error
+        .message()
+        .starts_with("Could not write the output: "));
+}
+
+#[test]
+fn capture_output_buffers_the_output() {
+    let mut engine = engine(InterpreterOptions::default().capture_output(true));
+    engine.run_source("script", "print 1;").unwrap();
+    assert_eq!(engine.take_output(), "1\n");
+}
+
+#[test]
+fn max_steps_limits_each_run() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .max_steps(2),
+    );
+    let diagnostics = engine
+        .run_source("script", "print 1; print 2; print 3;")
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[script] Stopped at the step limit of 2."
+    );
+    assert_eq!(diagnostics[0].error.exit_code(), 70);
+    assert_eq!(engine.take_output(), "1\n2\n");
+
+    // the budget starts over with every run
+    engine.run_source("script", "print 4; print 5;").unwrap();
+    assert_eq!(engine.take_output(), "4\n5\n");
+}
+
+#[test]
+fn a_loop_stops_at_the_step_limit() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .max_steps(4),
+    );
+    // the loop is a step and so is every run of its body
+    let diagnostics = engine
+        .run_source("loop", "while (true) print \"tick\";")
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[loop] Stopped at the step limit of 4."
+    );
+    assert_eq!(engine.take_output(), "tick\ntick\ntick\n");
+    // the condition was checked before each of them and before the one that was stopped
+    assert_eq!(engine.take_stats().booleans, 4);
+}
+
+#[test]
+fn a_loop_whose_body_is_skipped_stops_at_the_step_limit_too() {
+    // a skipped statement is still a step, so the loop can't spin forever
+    let mut engine = engine(InterpreterOptions::default().strip_debug(true).max_steps(4));
+    let diagnostics = engine
+        .run_source("loop", "while (true) debug 1;")
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[loop] Stopped at the step limit of 4."
+    );
+}
+
+#[test]
+fn a_loop_runs_its_body_at_most_max_loop_iterations_times() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .max_loop_iterations(3),
+    );
+    let diagnostics = engine
+        .run_source("loop", "print \"start\";\nwhile (true)\n  print \"tick\";")
+        .unwrap_err();
+    // the error is on the loop's line, not the body's
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[loop, Line 2] Error: Loop exceeded 3 iterations (possible infinite loop) at line 2."
+    );
+    assert_eq!(diagnostics[0].error.exit_code(), 70);
+    assert_eq!(engine.take_output(), "start\ntick\ntick\ntick\n");
+}
+
+#[test]
+fn a_loop_that_never_runs_never_reaches_max_loop_iterations() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .max_loop_iterations(0),
+    );
+    engine
+        .run_source("loop", "while (false) print \"never\"; print \"done\";")
+        .unwrap();
+    assert_eq!(engine.take_output(), "done\n");
+}
+
+#[test]
+fn every_loop_gets_its_own_iterations() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .keep_going(true)
+            .max_loop_iterations(2),
+    );
+    let diagnostics = engine
+        .run_source(
+            "loops",
+            "while (true) print \"a\";\nwhile (true) print \"b\";\nprint \"c\";",
+        )
+        .unwrap_err();
+    let diagnostics: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        diagnostics,
+        [
+            "[loops, Line 1] Error: Loop exceeded 2 iterations (possible infinite loop) at line 1.",
+            "[loops, Line 2] Error: Loop exceeded 2 iterations (possible infinite loop) at line 2.",
+        ]
+    );
+    assert_eq!(engine.take_output(), "a\na\nb\nb\nc\n");
+}
+
+#[test]
+fn a_raised_interrupt_flag_stops_the_run() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .interrupt_flag(flag.clone()),
+    );
+    engine.run_source("script", "print 1;").unwrap();
+
+    flag.store(true, Ordering::Relaxed);
+    let diagnostics = engine.run_source("script", "print 2;").unwrap_err();
+    assert_eq!(diagnostics[0].error.message(), "Interrupted.");
+    assert_eq!(engine.take_output(), "1\n");
+}
+
+#[test]
+fn conflicting_options_are_rejected() {
+    let error = |options: InterpreterOptions| options.build().err().unwrap().to_string();
+    assert_eq!(
+        error(InterpreterOptions::default().max_steps(0)),
+        "max_steps must be at least 1."
+    );
+    assert_eq!(
+        error(
+            InterpreterOptions::default()
+                .writer(io::sink())
+                .capture_output(true)
+        ),
+        "Output can't go to a writer and be captured at the same time."
+    );
+    assert_eq!(
+        error(InterpreterOptions::default().sandbox(true)),
+        "A sandbox needs a step limit; set max_steps too."
+    );
+    assert!(RLoxEngine::with_options(InterpreterOptions::default().max_steps(0)).is_err());
+}
+
+#[test]
+fn floats_are_shortest_round_trip_by_default() {
+    let mut engine = engine(InterpreterOptions::default().capture_output(true));
+    engine
+        .run_source(
+            "floats",
+            "print 0.1 + 0.2; print 1 / 3; print 7 / 2; print 10 / 2;",
+        )
+        .unwrap();
+    assert_eq!(
+        engine.take_output(),
+        "0.30000000000000004\n0.3333333333333333\n3.5\n5\n"
+    );
+
+    let value = engine.eval("0.1 + 0.2").unwrap();
+    assert_eq!(engine.format_value(&value), "0.30000000000000004");
+}
+
+#[test]
+fn float_precision_fixes_the_decimal_places() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .float_precision(2),
+    );
+    engine
+        .run_source(
+            "floats",
+            "print 0.1 + 0.2; print 2 / 3; print 7 / 2; print 10 / 2; print 2.0; print -0.005;",
+        )
+        .unwrap();
+    assert_eq!(engine.take_output(), "0.30\n0.67\n3.50\n5\n2\n-0.01\n");
+
+    let value = engine.eval("0.1 + 0.2").unwrap();
+    assert_eq!(engine.format_value(&value), "0.30");
+    let value = engine.eval("9007199254740993").unwrap();
+    assert_eq!(engine.format_value(&value), "9007199254740993");
+}
+
+#[test]
+fn keep_going_runs_the_statements_after_a_runtime_error() {
+    let source = "print 1;\nprint -\"two\";\nprint 3;";
+
+    let mut stopping = engine(InterpreterOptions::default().capture_output(true));
+    stopping.run_source("script", source).unwrap_err();
+    assert_eq!(stopping.take_output(), "1\n");
+
+    let mut going = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .keep_going(true),
+    );
+    let diagnostics = going
+        .run_sources(&[("a", source), ("b", "print 1 < true;\nprint 4;")])
+        .unwrap_err();
+    assert_eq!(going.take_output(), "1\n3\n4\n");
+    let errors: Vec<String> = diagnostics.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        errors,
+        [
+            "[a, Line 2] Error: Operand must be a number.",
+            "[b, Line 1] Error: The resolved values on right and left are not of the same type.",
+        ]
+    );
+    assert_eq!(diagnostics[0].error.exit_code(), 70);
+}
+
+#[test]
+fn keep_going_still_stops_at_the_step_limit() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .keep_going(true)
+            .max_steps(2),
+    );
+    let diagnostics = engine
+        .run_sources(&[
+            ("a", "print 1; print -nil; print 2; print 3;"),
+            ("b", "print 4;"),
+        ])
+        .unwrap_err();
+    assert_eq!(engine.take_output(), "1\n");
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[1].error.message(),
+        "Stopped at the step limit of 2."
+    );
+}
+
+#[test]
+fn output_is_flushed_once_a_run_ends() {
+    let log = FlushLog::default();
+    let mut engine = engine(InterpreterOptions::default().writer(log.clone()));
+    engine.run_source("script", "print 1; print 2;").unwrap();
+    assert_eq!(log.flushes(), ["1\n2\n"]);
+}
+
+#[test]
+fn output_is_flushed_before_a_runtime_error_is_returned() {
+    let log = FlushLog::default();
+    let mut engine = engine(InterpreterOptions::default().writer(log.clone()));
+    let diagnostics = engine
+        .run_source("script", "print 1; print -\"a\"; print 2;")
+        .unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(log.flushes(), ["1\n"]);
+}
+
+#[test]
+fn unbuffered_output_is_flushed_after_every_print() {
+    let log = FlushLog::default();
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .writer(log.clone())
+            .unbuffered(true),
+    );
+    engine.run_source("script", "print 1; print 2;").unwrap();
+    assert_eq!(log.flushes(), ["1\n", "2\n"]);
+}
+
+/// A program that doubles a two-byte string `times` times, one line at a time.
+fn doubling(times: usize) -> String {
+    let mut program = "var s = \"ab\";\n".to_string();
+    for _ in 0..times {
+        program.push_str("var t = s + s; var s = t; print \"doubled\";\n");
+    }
+    program
+}
+
+#[test]
+fn a_value_over_the_size_limit_is_a_runtime_error() {
+    let mut engine = engine(
+        InterpreterOptions::default()
+            .capture_output(true)
+            .max_value_bytes(16),
+    );
+    // 4, 8 and 16 bytes fit; 32 doesn't
+    let diagnostics = engine.run_source("script", &doubling(5)).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].error.message(),
+        "Value exceeds the configured size limit (16 bytes)."
+    );
+    assert_eq!(diagnostics[0].error.line(), Some(5));
+    assert_eq!(engine.take_output(), "doubled\n".repeat(3));
+}
+
+#[test]
+fn values_are_unlimited_by_default() {
+    let mut engine = engine(InterpreterOptions::default().capture_output(true));
+    engine.run_source("script", &doubling(20)).unwrap();
+    assert_eq!(engine.take_output(), "doubled\n".repeat(20));
+}
+
+/// Built-ins that would reach outside the interpreter, should Lox ever get them.
+const OS_NATIVES: [&str; 8] = [
+    "readFile",
+    "writeFile",
+    "appendFile",
+    "env",
+    "setEnv",
+    "input",
+    "clock",
+    "random",
+];
+
+fn sandboxed() -> RLoxEngine {
+    engine(
+        InterpreterOptions::default()
+            .sandbox(true)
+            .max_steps(1_000)
+            .capture_output(true),
+    )
+}
+
+#[test]
+fn a_sandbox_defines_nothing_that_reaches_outside() {
+    let engine = sandboxed();
+    for (name, _) in engine.globals() {
+        assert!(!OS_NATIVES.contains(&name), "{} is defined", name);
+    }
+}
+
+#[test]
+fn reading_a_file_in_a_sandbox_fails_cleanly() {
+    let mut engine = sandboxed();
+    let diagnostics = engine
+        .run_source(
+            "script",
+            "print \"start\";\nvar text = readFile;\nprint text;",
+        )
+        .unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].error.message(), "Unknown variable used.");
+    assert_eq!(diagnostics[0].error.line(), Some(2));
+    assert_eq!(engine.take_output(), "start\n");
+}
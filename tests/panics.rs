@@ -0,0 +1,74 @@
+//! Checks that a panic while running a program becomes a diagnostic and leaves the engine
+//! usable.
+
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rlox::{engine::RLoxEngine, errors::RLoxError, options::InterpreterOptions};
+
+/// A host writer with a bug: it panics the first time it's written to, and works after.
+#[derive(Clone, Default)]
+struct PanicsOnce {
+    panicked: Arc<AtomicBool>,
+    written: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Write for PanicsOnce {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if !self.panicked.swap(true, Ordering::Relaxed) {
+            panic!("the host's writer broke");
+        }
+        self.written.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn engine(writer: PanicsOnce) -> RLoxEngine {
+    RLoxEngine::with_options(
+        InterpreterOptions::default()
+            .writer(writer)
+            .unbuffered(true),
+    )
+    .unwrap()
+}
+
+#[test]
+fn a_panic_is_reported_as_an_internal_error() {
+    let mut engine = engine(PanicsOnce::default());
+    let diagnostics = engine
+        .run_source("script", "var a = 1;\nprint a;\nvar b = 2;")
+        .unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(matches!(diagnostics[0].error, RLoxError::Internal(_)));
+    assert_eq!(diagnostics[0].error.exit_code(), 70);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "[script] Internal error: the host's writer broke. This is a bug in rlox; please report it."
+    );
+}
+
+#[test]
+fn the_engine_keeps_the_state_it_reached_and_runs_on() {
+    let writer = PanicsOnce::default();
+    let mut engine = engine(writer.clone());
+    engine
+        .run_source("script", "var a = 1;\nprint a;\nvar b = 2;")
+        .unwrap_err();
+    assert_eq!(engine.get_global_json("a"), Some(serde_json::json!(1)));
+    assert_eq!(engine.get_global_json("b"), None);
+
+    let sum = engine.eval("a + 1").unwrap();
+    assert_eq!(engine.inspect_value(&sum), "2");
+    engine.run_source("script", "print \"after\";").unwrap();
+    assert!(String::from_utf8(writer.written.lock().unwrap().clone())
+        .unwrap()
+        .ends_with("after\n"));
+}
@@ -0,0 +1,65 @@
+//! Snapshots of the syntax tree renderings behind `--ast-format`.
+
+use rlox::{
+    ast::{dot::DotPrinter, print::AstPrinter, rpn::RpnPrinter, stmt::Stmt},
+    engine::RLoxEngine,
+    timings::Timings,
+};
+
+fn parse(source: &str) -> Vec<Stmt> {
+    RLoxEngine::parse_source(source.to_string(), false, &mut Timings::default()).unwrap()
+}
+
+#[test]
+fn tree_shows_precedence_with_parentheses() {
+    assert_eq!(
+        AstPrinter::print_program(&parse("1 + 2 * 3;")).unwrap(),
+        "(expr (+ 1 (* 2 3)))"
+    );
+    assert_eq!(
+        AstPrinter::print_program(&parse("(1 + 2) * -3;\nprint \"a\";\nvar x = nil;")).unwrap(),
+        "(expr (* (group (+ 1 2)) (- 3)))\n(print \"a\")\n(var x nil)"
+    );
+}
+
+#[test]
+fn rpn_puts_operators_after_their_operands() {
+    assert_eq!(
+        RpnPrinter::print_program(&parse("1 + 2 * 3;")).unwrap(),
+        "1 2 3 * +"
+    );
+    // groupings need no parentheses, and unary minus is `~`
+    assert_eq!(
+        RpnPrinter::print_program(&parse("(1 + 2) * -3;\nprint \"a\";\nvar x = nil;")).unwrap(),
+        "1 2 + 3 ~ *\n\"a\" print\nx nil var"
+    );
+}
+
+#[test]
+fn dot_has_a_node_per_tree_node_and_an_edge_per_child() {
+    let graph = DotPrinter::print_program(&parse("1 + 2 * 3;\nprint nil;")).unwrap();
+    assert!(graph.starts_with("digraph ast {\n"));
+    assert!(graph.ends_with("\n}"));
+    let edges = graph.lines().filter(|line| line.contains(" -> ")).count();
+    let nodes = graph
+        .lines()
+        .filter(|line| line.contains("[label=") && !line.contains(" -> "))
+        .count();
+    // program, two statements, two binaries, three literals and nil
+    assert_eq!(nodes, 9);
+    // every node but the root has exactly one parent
+    assert_eq!(edges, nodes - 1);
+    assert!(graph.contains("n2 [label=\"Binary +\"];"));
+    assert!(graph.contains("n2 -> n4 [label=\"right\"];"));
+}
+
+#[test]
+fn dot_labels_are_quoted_and_escaped() {
+    let graph = DotPrinter::print_program(&parse("print \"a \\ b\nc\";")).unwrap();
+    // the value as the REPL shows it, `"a \\ b\nc"`, escaped again for DOT
+    assert!(
+        graph.contains(r#"n2 [label="Literal \"a \\\\ b\\nc\""];"#),
+        "{}",
+        graph
+    );
+}
@@ -0,0 +1,229 @@
+//! Drives the REPL through stdin and checks what it echoes.
+
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Feeds `input` to the REPL, one submission per line, and returns its stdout and stderr.
+fn run_repl(args: &[&str], input: &str) -> (String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("rlox runs");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Feeds `input` to the REPL, one submission per line, and returns its stdout.
+fn repl(args: &[&str], input: &str) -> String {
+    run_repl(args, input).0
+}
+
+#[test]
+fn echoed_results_are_numbered_and_bound() {
+    let output = repl(&[], "1 + 2\n\"a\"\nvar x = 5;\nnil\nx * 2\n_1 + _3\n_\n");
+    assert_eq!(
+        output,
+        "[1] = 3\n[2] = \"a\"\n[3] = 10\n[4] = 13\n[5] = 13\n"
+    );
+}
+
+#[test]
+fn reset_restarts_the_numbering_unless_told_to_keep_it() {
+    let input = "1\n:reset\n2\n";
+    assert_eq!(repl(&[], input), "[1] = 1\nEnvironment reset.\n[1] = 2\n");
+    assert_eq!(
+        repl(&["--keep-result-numbers"], input),
+        "[1] = 1\nEnvironment reset.\n[2] = 2\n"
+    );
+}
+
+#[test]
+fn env_hides_result_bindings_unless_asked_for_all() {
+    let output = repl(&[], "var a = 1;\n7\n:env\n:env all\n");
+    assert_eq!(output, "[1] = 7\na = 1\n_ = 7\n_1 = 7\na = 1\n");
+}
+
+#[test]
+fn reset_removes_every_variable() {
+    assert_eq!(
+        repl(&[], "var a = 1;\n:reset\n:env\na\n:reset now\n"),
+        "Environment reset.\n\
+         [<repl:2>, Line 2] Error: Unknown variable used.\n\
+         Usage: :reset [hard]\n"
+    );
+}
+
+#[test]
+fn env_lists_every_global_by_name() {
+    let output = repl(
+        &[],
+        "var b = \"x\";\nvar a = 1.5;\nvar d = true;\nvar c = nil;\n:env\n",
+    );
+    assert_eq!(output, "a = 1.5\nb = x\nc = nil\nd = true\n");
+    assert_eq!(repl(&[], ":env\n"), "");
+}
+
+#[test]
+fn ast_and_tokens_show_how_input_is_read() {
+    assert_eq!(repl(&[], ":ast 1 + 2 * 3\n"), "(+ 1 (* 2 3))\n");
+    assert_eq!(
+        repl(&[], ":tokens print \"hi there\";\n"),
+        "'print' at line 1, offset 0\n\
+         string literal \"hi there\" at line 1, offset 6\n\
+         ';' at line 1, offset 16\n\
+         end of file at line 1, offset 17\n"
+    );
+    // what's shown isn't run
+    assert_eq!(
+        repl(&[], ":ast print 1;\n:ast var a = 1;\n:env\n"),
+        "(print 1)\n(var a 1)\n"
+    );
+}
+
+#[test]
+fn reading_a_variable_in_its_own_initializer_is_explained() {
+    assert_eq!(
+        repl(
+            &[],
+            "var a = a + 1;\nvar b = missing;\nvar a = 1;\nvar a = a + 1;\na\n"
+        ),
+        "[<repl:1>, Line 1] Error: Cannot read 'a' in its own initializer; it has not been defined yet.\n\
+         [<repl:2>, Line 2] Error: Unknown variable used.\n\
+         [1] = 2\n"
+    );
+}
+
+#[test]
+fn errors_report_the_submission_and_the_session_line() {
+    let output = repl(
+        &[],
+        "var a = 1;\nprint a;\n:paste\nprint a;\nprint -\"x\";\n.\n",
+    );
+    assert_eq!(
+        output,
+        "1\n\
+         Paste mode: finish with a line containing only '.'\n\
+         1\n\
+         [<repl:3>, Line 4] Error: Operand must be a number.\n"
+    );
+}
+
+#[test]
+fn echoed_strings_are_escaped() {
+    assert_eq!(
+        repl(&[], "\"1\"\n:paste\n\"a\nb\"\n.\n"),
+        "[1] = \"1\"\nPaste mode: finish with a line containing only '.'\n[2] = \"a\\nb\"\n"
+    );
+}
+
+#[test]
+fn save_writes_the_session_with_failed_submissions_commented_out() {
+    let dir = env::temp_dir().join(format!("rlox-save-{}", std::process::id()));
+    let path = dir.join("nested").join("session.lox");
+    let path = path.to_str().unwrap();
+    let input = format!(
+        "var a = 1;\nprint a +;\n:env\na * 2\nprint -\"x\";\n:save {0}\n:save {0}\n:save! {0}\n",
+        path
+    );
+    let output = repl(&[], &input);
+    let saved = fs::read_to_string(path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(saved, "var a = 1;\n// print a +;\na * 2\nprint -\"x\";\n");
+    assert!(output.ends_with(&format!(
+        "Saved 4 submissions to {0}.\n\
+         {0} already exists; use :save! to overwrite it.\n\
+         Saved 4 submissions to {0}.\n",
+        path
+    )));
+}
+
+#[test]
+fn load_runs_a_file_into_the_session() {
+    let dir = env::temp_dir().join(format!("rlox-load-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("greeting.lox");
+    fs::write(
+        &path,
+        "print \"loading\";\nvar greeting = \"hi\";\nprint -greeting;\n",
+    )
+    .unwrap();
+    let path = path.to_str().unwrap();
+    let missing = dir.join("missing.lox");
+    let output = repl(
+        &[],
+        &format!(
+            ":load\n:load {}\ngreeting\n:load\n:load {}\n",
+            path,
+            missing.display()
+        ),
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    let loaded = format!(
+        "loading\n[{}, Line 3] Error: Operand must be a number.\n",
+        path
+    );
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("Usage: :load <path>"));
+    // the file's globals stay, and :load without a path loads the same file again
+    assert_eq!(
+        lines.by_ref().take(5).collect::<Vec<_>>().join("\n") + "\n",
+        format!("{0}[1] = \"hi\"\n{0}", loaded)
+    );
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(&format!("Could not read {}: ", missing.display())));
+}
+
+#[test]
+fn timing_reports_each_submission_on_stderr_while_on() {
+    let (output, errors) = run_repl(&[], "1\n:timing on\n2\nprint 3;\n:timing off\n4\n:timing\n");
+    assert_eq!(
+        output,
+        "[1] = 1\nTiming is on.\n[2] = 2\n3\nTiming is off.\n[3] = 4\nTiming is off.\n"
+    );
+    let reports: Vec<&str> = errors.lines().collect();
+    assert_eq!(reports.len(), 2, "{:?}", errors);
+    assert!(reports
+        .iter()
+        .all(|report| report.starts_with("(took ") && report.ends_with(')')));
+
+    let (_, errors) = run_repl(&["--time"], "1\n");
+    assert!(errors.starts_with("(took "));
+}
+
+#[test]
+fn unbuffered_output_comes_before_the_error() {
+    let output = repl(&["--unbuffered"], "print 1; print -\"a\";\nprint 2;\n");
+    let printed = output.find("1\n").unwrap();
+    let error = output.find("Error").unwrap();
+    assert!(printed < error, "{}", output);
+    assert!(output.contains("2\n"), "{}", output);
+}
+
+#[test]
+fn explain_prints_every_step() {
+    let output = repl(&[], "var a = 2;\n:explain (a + 1) * 3\n");
+    assert!(
+        output.contains("(a + 1) * 3\n→ (2 + 1) * 3\n→ 3 * 3\n→ 9\n"),
+        "{}",
+        output
+    );
+}
@@ -0,0 +1,66 @@
+//! Checks the value a program ends with, as `run_source` returns it and `--print-result`
+//! prints it.
+
+use std::{env, fs, process::Command};
+
+use rlox::{engine::RLoxEngine, tokens::Object};
+
+fn result_of(source: &str) -> Option<Object> {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.run_source("config", source).unwrap()
+}
+
+#[test]
+fn a_program_ending_in_an_expression_returns_its_value() {
+    let value = result_of("var width = 80;\nprint width;\nwidth * 2;");
+    assert!(matches!(value, Some(Object::Number(number)) if number.to_string() == "160"));
+}
+
+#[test]
+fn a_program_ending_in_a_declaration_returns_nothing() {
+    assert!(result_of("1 + 2;\nvar width = 80;").is_none());
+    assert!(result_of("1 + 2;\nprint 3;").is_none());
+}
+
+#[test]
+fn a_program_ending_in_nil_returns_nothing() {
+    assert!(result_of("1;\nnil;").is_none());
+}
+
+#[test]
+fn the_last_of_several_sources_gives_the_result() {
+    let mut engine = RLoxEngine::new();
+    let value = engine
+        .run_sources(&[("first", "var a = \"a\";"), ("second", "a + \"b\";")])
+        .unwrap();
+    assert!(matches!(value, Some(Object::String(string)) if string == "ab"));
+}
+
+/// Runs rlox with `--print-result` on a file holding `source` and returns its stdout.
+fn print_result(name: &str, source: &str) -> String {
+    let dir = env::temp_dir().join(format!("rlox-result-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg("--print-result")
+        .arg(&path)
+        .output()
+        .expect("rlox runs");
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn print_result_prints_the_value_after_the_output() {
+    assert_eq!(
+        print_result("expression.lox", "print \"running\";\n\"a\" + \"b\";\n"),
+        "running\n\"ab\"\n"
+    );
+    assert_eq!(
+        print_result("declaration.lox", "print 1;\nvar a = 2;\n"),
+        "1\n"
+    );
+}
@@ -0,0 +1,135 @@
+//! Runs every `tests/lox/**/*.lox` program and checks its output against the
+//! expectations written in its comments, in the style of the Crafting Interpreters
+//! test suite:
+//!
+//! - `// expect: value` is a line the program prints;
+//! - `// expect-error: message` is a scan or parse error reported on that line;
+//! - `// expect-runtime-error: message` is a runtime error reported on that line.
+//!
+//! Expectations are matched in file order against stdout, and the exit code has to
+//! be 65 if any error is expected, 70 for a runtime error and 0 otherwise.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_ERROR: &str = "// expect-error: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect-runtime-error: ";
+
+#[test]
+fn lox_programs() {
+    let mut paths = vec![];
+    collect_programs(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/lox"),
+        &mut paths,
+    );
+    paths.sort();
+    assert!(!paths.is_empty(), "no programs found in tests/lox");
+
+    let failures: Vec<String> = paths.iter().filter_map(|path| run(path).err()).collect();
+    if !failures.is_empty() {
+        panic!(
+            "{} of {} programs failed:\n\n{}",
+            failures.len(),
+            paths.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+fn collect_programs(directory: &Path, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).expect("tests/lox is readable") {
+        let path = entry.expect("tests/lox is readable").path();
+        if path.is_dir() {
+            collect_programs(&path, paths);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            paths.push(path);
+        }
+    }
+}
+
+/// The output a program should print and the status it should exit with.
+struct Expectations {
+    /// Each line paired with the line of the comment it came from.
+    lines: Vec<(usize, String)>,
+    exit_code: i32,
+}
+
+fn parse_expectations(source: &str) -> Expectations {
+    let mut lines = vec![];
+    let mut exit_code = 0;
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        if let Some((_, value)) = line.split_once(EXPECT) {
+            lines.push((number, value.to_string()));
+        } else if let Some((_, message)) = line.split_once(EXPECT_ERROR) {
+            lines.push((number, format!("[Line {}] Error: {}", number, message)));
+            exit_code = 65;
+        } else if let Some((_, message)) = line.split_once(EXPECT_RUNTIME_ERROR) {
+            lines.push((number, format!("[Line {}] Error: {}", number, message)));
+            exit_code = exit_code.max(70);
+        }
+    }
+    Expectations { lines, exit_code }
+}
+
+/// Runs the program, describing every mismatch if its output isn't the expected one.
+fn run(path: &Path) -> Result<(), String> {
+    let source = fs::read_to_string(path).expect("test programs are readable");
+    let expected = parse_expectations(&source);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg(path)
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("the rlox binary runs");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let actual: Vec<&str> = stdout.lines().collect();
+
+    let mut mismatches = vec![];
+    for (index, (line, expected_line)) in expected.lines.iter().enumerate() {
+        match actual.get(index) {
+            Some(actual_line) if actual_line == expected_line => {}
+            Some(actual_line) => {
+                let label = format!("line {}:", line);
+                mismatches.push(format!(
+                    "  {} expected {:?}\n  {} got      {:?}",
+                    label,
+                    expected_line,
+                    " ".repeat(label.len()),
+                    actual_line
+                ))
+            }
+            None => mismatches.push(format!(
+                "  line {}: expected {:?}, got nothing",
+                line, expected_line
+            )),
+        }
+    }
+    for unexpected in actual.iter().skip(expected.lines.len()) {
+        mismatches.push(format!("  unexpected output {:?}", unexpected));
+    }
+
+    let exit_code = output.status.code();
+    if exit_code != Some(expected.exit_code) {
+        mismatches.push(format!(
+            "  expected exit code {}, got {:?}",
+            expected.exit_code, exit_code
+        ));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.is_empty() {
+        mismatches.push(format!("  stderr: {}", stderr.trim_end()));
+    }
+
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    let name = path
+        .strip_prefix(env!("CARGO_MANIFEST_DIR"))
+        .unwrap_or(path);
+    Err(format!("{}:\n{}\n", name.display(), mismatches.join("\n")))
+}
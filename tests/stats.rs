@@ -0,0 +1,62 @@
+//! Checks the counts behind `--stats`.
+
+use rlox::{engine::RLoxEngine, options::InterpreterOptions, stats::Stats};
+
+fn engine(options: InterpreterOptions) -> RLoxEngine {
+    RLoxEngine::with_options(options.capture_output(true)).unwrap()
+}
+
+#[test]
+fn a_program_is_counted_exactly() {
+    let mut engine = engine(InterpreterOptions::default());
+    engine
+        .run_source(
+            "script",
+            "var a = 1 + 2;\nprint \"x\" + \"y\";\nprint -a > 0 or nil;\nvar b = a;",
+        )
+        .unwrap();
+    assert_eq!(
+        engine.take_stats(),
+        Stats {
+            statements: 4,
+            nils: 1,
+            booleans: 1,
+            // 1, 2, 1 + 2, 0 and -a
+            numbers: 5,
+            // "x", "y" and "xy"
+            strings: 3,
+            // "xy\n" and "\n" for nil
+            output_bytes: 4,
+            recovered_errors: 0,
+        }
+    );
+    assert_eq!(engine.take_stats(), Stats::default());
+}
+
+#[test]
+fn stats_add_up_across_runs_and_count_recovered_errors() {
+    let mut engine = engine(InterpreterOptions::default().keep_going(true));
+    engine.run_source("first", "print 1;").unwrap();
+    engine
+        .run_source("second", "print -\"a\";\nprint 2;\nprint nil + 1;")
+        .unwrap_err();
+    let stats = engine.take_stats();
+    assert_eq!(stats.statements, 4);
+    assert_eq!(stats.recovered_errors, 2);
+    assert_eq!(stats.output_bytes, 4);
+}
+
+#[test]
+fn the_step_limit_counts_each_run_from_its_start() {
+    let mut engine = engine(InterpreterOptions::default().max_steps(2));
+    engine.run_source("first", "print 1; print 2;").unwrap();
+    engine.run_source("second", "print 3; print 4;").unwrap();
+    let diagnostics = engine
+        .run_source("third", "print 5; print 6; print 7;")
+        .unwrap_err();
+    assert_eq!(
+        diagnostics[0].error.message(),
+        "Stopped at the step limit of 2."
+    );
+    assert_eq!(engine.take_stats().statements, 6);
+}
@@ -0,0 +1,74 @@
+//! Checks that identifiers are interned once, and that variables are found whether or not
+//! a program was scanned with the interner of the environment it runs in.
+
+use rlox::{
+    engine::RLoxEngine, scanner::Scanner, symbol::Interner, timings::Timings, tokens::TokenType,
+};
+
+#[test]
+fn a_name_is_interned_once() {
+    let mut interner = Interner::new();
+    let total = interner.intern("total");
+    let count = interner.intern("count");
+    assert_eq!(interner.intern("total"), total);
+    assert_ne!(total, count);
+    assert_eq!(interner.resolve(count), Some("count"));
+    assert_eq!(interner.get("missing"), None);
+    assert_eq!(interner.names().collect::<Vec<_>>(), ["total", "count"]);
+}
+
+#[test]
+fn a_symbol_of_another_interner_is_not_confirmed() {
+    let mut ours = Interner::new();
+    let mut theirs = Interner::new();
+    ours.intern("a");
+    let b = theirs.intern("b");
+    // same index, different name
+    assert_eq!(ours.confirm(b, "b"), None);
+    assert_eq!(theirs.confirm(b, "b"), Some(b));
+}
+
+#[test]
+fn the_scanner_interns_identifiers_and_keywords() {
+    let mut scanner = Scanner::new("var a = a + b; print a;".to_string());
+    scanner.scan_tokens();
+    let interner = scanner.take_interner();
+    let symbols: Vec<_> = scanner
+        .tokens
+        .iter()
+        .filter_map(|token| Some((token.token_type, interner.resolve(token.symbol?)?)))
+        .collect();
+    assert_eq!(
+        symbols,
+        [
+            (TokenType::Var, "var"),
+            (TokenType::Identifier, "a"),
+            (TokenType::Identifier, "a"),
+            (TokenType::Identifier, "b"),
+            (TokenType::Print, "print"),
+            (TokenType::Identifier, "a"),
+        ]
+    );
+    assert_eq!(scanner.tokens[1].symbol, scanner.tokens[3].symbol);
+}
+
+#[test]
+fn programs_parsed_elsewhere_find_their_variables() {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    engine.run_source("first", "var b = 2; var a = 1;").unwrap();
+
+    // scanned with a fresh interner, where `a` and `b` have other symbols
+    let statements = RLoxEngine::parse_source(
+        "print a; print b;".to_string(),
+        false,
+        &mut Timings::default(),
+    )
+    .unwrap();
+    engine.run_program("second", statements).unwrap();
+    assert_eq!(engine.take_output(), "1\n2\n");
+
+    let mut names: Vec<&str> = engine.globals().map(|(name, _)| name).collect();
+    names.retain(|name| ["a", "b"].contains(name));
+    assert_eq!(names, ["a", "b"]);
+}
@@ -0,0 +1,253 @@
+//! Checks how tokens read in error messages and the REPL's `:tokens` dump, and how the
+//! scanner treats a byte order mark and operators from other languages.
+
+use rlox::{
+    engine::RLoxEngine,
+    parser::Parser,
+    scanner::Scanner,
+    tokens::{Token, TokenType},
+};
+
+#[test]
+fn every_token_type_has_a_display() {
+    let displays = [
+        (TokenType::LeftParen, "'('"),
+        (TokenType::RightParen, "')'"),
+        (TokenType::LeftBrace, "'{'"),
+        (TokenType::RightBrace, "'}'"),
+        (TokenType::Comma, "','"),
+        (TokenType::Dot, "'.'"),
+        (TokenType::Colon, "':'"),
+        (TokenType::Minus, "'-'"),
+        (TokenType::Plus, "'+'"),
+        (TokenType::Semicolon, "';'"),
+        (TokenType::Slash, "'/'"),
+        (TokenType::Star, "'*'"),
+        (TokenType::Bang, "'!'"),
+        (TokenType::BangEqual, "'!='"),
+        (TokenType::Equal, "'='"),
+        (TokenType::EqualEqual, "'=='"),
+        (TokenType::Greater, "'>'"),
+        (TokenType::GreaterEqual, "'>='"),
+        (TokenType::Less, "'<'"),
+        (TokenType::LessEqual, "'<='"),
+        (TokenType::Identifier, "identifier"),
+        (TokenType::String, "string literal"),
+        (TokenType::Number, "number literal"),
+        (TokenType::And, "'and'"),
+        (TokenType::Assert, "'assert'"),
+        (TokenType::Class, "'class'"),
+        (TokenType::Debug, "'debug'"),
+        (TokenType::Else, "'else'"),
+        (TokenType::False, "'false'"),
+        (TokenType::Fun, "'fun'"),
+        (TokenType::For, "'for'"),
+        (TokenType::If, "'if'"),
+        (TokenType::Nil, "'nil'"),
+        (TokenType::Or, "'or'"),
+        (TokenType::Print, "'print'"),
+        (TokenType::Return, "'return'"),
+        (TokenType::Super, "'super'"),
+        (TokenType::This, "'this'"),
+        (TokenType::True, "'true'"),
+        (TokenType::Var, "'var'"),
+        (TokenType::While, "'while'"),
+        (TokenType::Comment, "comment"),
+        (TokenType::Eof, "end of file"),
+    ];
+
+    for (token_type, display) in displays {
+        assert_eq!(token_type.to_string(), display, "{:?}", token_type);
+    }
+
+    // a new variant makes this match fail to compile, as a reminder to add it above
+    for (token_type, _) in displays {
+        match token_type {
+            TokenType::LeftParen
+            | TokenType::RightParen
+            | TokenType::LeftBrace
+            | TokenType::RightBrace
+            | TokenType::Comma
+            | TokenType::Dot
+            | TokenType::Colon
+            | TokenType::Minus
+            | TokenType::Plus
+            | TokenType::Semicolon
+            | TokenType::Slash
+            | TokenType::Star
+            | TokenType::Bang
+            | TokenType::BangEqual
+            | TokenType::Equal
+            | TokenType::EqualEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Identifier
+            | TokenType::String
+            | TokenType::Number
+            | TokenType::And
+            | TokenType::Assert
+            | TokenType::Class
+            | TokenType::Debug
+            | TokenType::Else
+            | TokenType::False
+            | TokenType::Fun
+            | TokenType::For
+            | TokenType::If
+            | TokenType::Nil
+            | TokenType::Or
+            | TokenType::Print
+            | TokenType::Return
+            | TokenType::Super
+            | TokenType::This
+            | TokenType::True
+            | TokenType::Var
+            | TokenType::While
+            | TokenType::Comment
+            | TokenType::Eof => {}
+        }
+    }
+}
+
+fn scan(source: &str) -> Vec<Token> {
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.preserve_comments(true);
+    scanner.scan_tokens();
+    scanner.tokens
+}
+
+#[test]
+fn tokens_display_their_lexeme_and_location() {
+    let displays: Vec<String> = scan("var count = \"a\" == 1.5; // done")
+        .iter()
+        .map(Token::to_string)
+        .collect();
+    assert_eq!(
+        displays,
+        [
+            "'var' at line 1, offset 0",
+            "identifier 'count' at line 1, offset 4",
+            "'=' at line 1, offset 10",
+            "string literal \"a\" at line 1, offset 12",
+            "'==' at line 1, offset 16",
+            "number literal '1.5' at line 1, offset 19",
+            "';' at line 1, offset 22",
+            "comment '// done' at line 1, offset 24",
+            "end of file at line 1, offset 31",
+        ]
+    );
+}
+
+const BOM: &str = "\u{feff}";
+
+#[test]
+fn a_leading_byte_order_mark_is_skipped() {
+    let displays: Vec<String> = scan(&format!("{}print 1;\nprint 2;", BOM))
+        .iter()
+        .map(Token::to_string)
+        .collect();
+    assert_eq!(
+        displays,
+        [
+            "'print' at line 1, offset 3",
+            "number literal '1' at line 1, offset 9",
+            "';' at line 1, offset 10",
+            "'print' at line 2, offset 12",
+            "number literal '2' at line 2, offset 18",
+            "';' at line 2, offset 19",
+            "end of file at line 2, offset 20",
+        ]
+    );
+}
+
+#[test]
+fn a_byte_order_mark_elsewhere_is_an_error() {
+    let mut scanner = Scanner::new(format!("print 1;\n{}print 2;", BOM));
+    scanner.scan_tokens();
+    let errors: Vec<String> = scanner.errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(
+        errors,
+        ["[Line 2] Error: Unexpected byte order mark (U+FEFF); one is only allowed at the start of a file."]
+    );
+    assert!(scanner
+        .tokens
+        .iter()
+        .all(|token| !token.lexeme.contains(BOM)));
+}
+
+#[test]
+fn a_file_starting_with_a_byte_order_mark_runs() {
+    let mut engine = RLoxEngine::new();
+    engine.capture_output(true);
+    let source = String::from_utf8(b"\xEF\xBB\xBFprint \"bom\";".to_vec()).unwrap();
+    engine.run_source("bom.lox", &source).unwrap();
+    assert_eq!(engine.take_output(), "bom\n");
+}
+
+#[test]
+fn preserving_comments_leaves_the_other_tokens_alone() {
+    let source = "var a = 1; // one\n/* two\n   /* nested */ lines */ print a;\n";
+    let mut scanner = Scanner::new(source.to_string());
+    scanner.scan_tokens();
+    let discarded: Vec<String> = scanner.tokens.iter().map(Token::to_string).collect();
+
+    let preserved = scan(source);
+    let (comments, others): (Vec<&Token>, Vec<&Token>) = preserved
+        .iter()
+        .partition(|token| token.token_type == TokenType::Comment);
+    let others: Vec<String> = others.iter().map(|token| token.to_string()).collect();
+    assert_eq!(others, discarded);
+
+    let spans: Vec<(&str, u64, &str)> = comments
+        .iter()
+        .map(|token| {
+            let span = token.offset..token.offset + token.lexeme.len();
+            (token.lexeme.as_str(), token.line, &source[span])
+        })
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            ("// one", 1, "// one"),
+            (
+                "/* two\n   /* nested */ lines */",
+                3,
+                "/* two\n   /* nested */ lines */"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn the_parser_skips_comment_tokens() {
+    let tokens = scan("print 1; /* between */ print 2; // after");
+    let statements = Parser::new(tokens).parse().unwrap();
+    assert_eq!(statements.len(), 2);
+}
+
+#[test]
+fn operators_from_other_languages_scan_as_what_was_meant() {
+    let mut scanner = Scanner::new("a && b || c === d;".to_string());
+    scanner.scan_tokens();
+    assert_eq!(scanner.errors.len(), 3);
+    let types: Vec<TokenType> = scanner
+        .tokens
+        .iter()
+        .map(|token| token.token_type)
+        .collect();
+    assert_eq!(
+        types,
+        [
+            TokenType::Identifier,
+            TokenType::And,
+            TokenType::Identifier,
+            TokenType::Or,
+            TokenType::Identifier,
+            TokenType::EqualEqual,
+            TokenType::Identifier,
+            TokenType::Semicolon,
+            TokenType::Eof,
+        ]
+    );
+}
@@ -0,0 +1,146 @@
+//! Checks what the `--typecheck` pass flags, one test per rule, and what it leaves alone.
+
+use rlox::{ast::typecheck::TypeChecker, engine::RLoxEngine, timings::Timings};
+
+/// The line and message of every issue found in `sources`, checked in order.
+fn check_all(sources: &[&str], strict: bool) -> Vec<(u64, String)> {
+    let mut checker = TypeChecker::new(strict);
+    let mut found = vec![];
+    for source in sources {
+        let statements =
+            RLoxEngine::parse_source(source.to_string(), false, &mut Timings::default()).unwrap();
+        for issue in checker.check_program(&statements).unwrap() {
+            found.push((issue.line, issue.message));
+        }
+    }
+    found
+}
+
+fn check(source: &str) -> Vec<(u64, String)> {
+    check_all(&[source], false)
+}
+
+fn issue(line: u64, message: &str) -> (u64, String) {
+    (line, message.to_string())
+}
+
+#[test]
+fn arithmetic_and_ordering_need_numbers() {
+    assert_eq!(
+        check("print \"a\" * 2;\nprint 1 - true;\nprint nil < 1;\nprint 4 / 2 >= 1;"),
+        [
+            issue(1, "Operands of '*' must be numbers, not a string."),
+            issue(2, "Operands of '-' must be numbers, not a boolean."),
+            issue(3, "Operands of '<' must be numbers, not nil."),
+        ]
+    );
+}
+
+#[test]
+fn negation_needs_a_number() {
+    assert_eq!(
+        check("print -\"a\";\nprint -(1 + 2);\nprint !\"a\";"),
+        [issue(1, "Operand of '-' must be a number, not a string.")]
+    );
+}
+
+#[test]
+fn addition_needs_two_numbers_or_two_strings() {
+    assert_eq!(
+        check("print 1 + \"a\";\nprint \"a\" + \"b\" + \"c\";\nprint true + 1;"),
+        [
+            issue(
+                1,
+                "Operands of '+' must be two numbers or two strings, not a number and a string."
+            ),
+            issue(
+                3,
+                "Operands of '+' must be two numbers or two strings, not a boolean and a number."
+            ),
+        ]
+    );
+}
+
+#[test]
+fn equality_needs_matching_types_unless_nil_is_on_the_left() {
+    assert_eq!(
+        check("print 1 == \"1\";\nprint nil == 1;\nprint 1 != nil;\nprint 1 == 2;"),
+        [
+            issue(1, "Can't compare a number with a string using '=='."),
+            issue(3, "Can't compare a number with nil using '!='."),
+        ]
+    );
+}
+
+#[test]
+fn variables_have_the_type_of_their_latest_declaration() {
+    assert_eq!(
+        check_all(
+            &[
+                "var name = \"lox\";\nvar count = 1 + 2;\nprint count - name;",
+                "var name = 3;\nprint count - name;\nvar flag = count > 1;\nprint -flag;",
+            ],
+            false
+        ),
+        [
+            issue(3, "Operands of '-' must be numbers, not a string."),
+            issue(4, "Operand of '-' must be a number, not a boolean."),
+        ]
+    );
+}
+
+#[test]
+fn unknown_types_are_never_flagged() {
+    assert!(check(
+        "print host * 2;\nprint host + \"a\";\nprint host == 1;\nvar a = host;\nprint -a;"
+    )
+    .is_empty());
+}
+
+#[test]
+fn literal_conditions_must_be_booleans_in_strict_mode() {
+    let source = "assert 1, \"one\";\nassert true;\nassert nil;\nassert 1 < 2;";
+    assert!(check(source).is_empty());
+    assert_eq!(
+        check_all(&[source], true),
+        [
+            issue(1, "The condition is a number, not a boolean."),
+            issue(3, "The condition is nil, not a boolean."),
+        ]
+    );
+}
+
+#[test]
+fn annotations_must_match_the_initializer() {
+    assert_eq!(
+        check("var a: number = \"one\";\nvar b: string = \"two\";\nvar c: number;\nvar d: any = 4;\nvar e: nil = false;"),
+        [
+            issue(1, "'a' is declared as a number but initialized with a string."),
+            issue(5, "'e' is declared as nil but initialized with a boolean."),
+        ]
+    );
+}
+
+#[test]
+fn annotations_seed_the_type_of_a_variable() {
+    assert_eq!(
+        check("var a: string;\nprint a * 2;\nvar b: any = 1;\nprint b + \"b\";\nvar c: number = host;\nprint -c;"),
+        [issue(2, "Operands of '*' must be numbers, not a string.")]
+    );
+}
+
+#[test]
+fn unknown_type_names_are_flagged() {
+    assert_eq!(
+        check("var a: int = 1;\nprint a + \"a\";"),
+        [issue(1, "Unknown type 'int'.")]
+    );
+}
+
+#[test]
+fn logical_operators_have_a_type_only_when_both_operands_agree() {
+    assert_eq!(
+        check("var a = nil or \"a\";\nprint a * 2;\nvar b = \"a\" or \"b\";\nprint b * 2;"),
+        [issue(4, "Operands of '*' must be numbers, not a string.")]
+    );
+}
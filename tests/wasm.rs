@@ -0,0 +1,54 @@
+//! Runs the `wasm` feature's JavaScript interface in a headless wasm runtime:
+//!
+//! ```bash
+//! cargo test --target wasm32-unknown-unknown --no-default-features --features wasm --test wasm
+//! ```
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use rlox::wasm::RLoxWasm;
+use serde_json::{json, Value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn outcome(value: JsValue) -> Value {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn run_returns_output_and_keeps_globals() {
+    let mut lox = RLoxWasm::new();
+    assert_eq!(
+        outcome(lox.run("var a = 1; print a;")),
+        json!({ "output": "1\n", "diagnostics": [] })
+    );
+    assert_eq!(
+        outcome(lox.run("print a + 1;")),
+        json!({ "output": "2\n", "diagnostics": [] })
+    );
+}
+
+#[wasm_bindgen_test]
+fn run_reports_diagnostics_after_the_output_before_them() {
+    let mut lox = RLoxWasm::new();
+    assert_eq!(
+        outcome(lox.run("print \"before\";\nprint -nil;")),
+        json!({
+            "output": "before\n",
+            "diagnostics": [{
+                "line": 2,
+                "message": "Operand must be a number.",
+                "text": "[<input>, Line 2] Error: Operand must be a number.",
+            }],
+        })
+    );
+}
+
+#[wasm_bindgen_test]
+fn eval_returns_the_value_of_the_final_expression() {
+    let mut lox = RLoxWasm::new();
+    lox.run("var a = 20;");
+    assert_eq!(
+        outcome(lox.eval("a * 2 + 2")),
+        json!({ "output": "", "value": "42", "diagnostics": [] })
+    );
+}
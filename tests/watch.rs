@@ -0,0 +1,76 @@
+//! Drives `--watch`'s rerun loop with events sent by hand instead of a file watcher.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    sync::{mpsc, Arc, Mutex},
+};
+
+use rlox::{
+    options::InterpreterOptions,
+    watch::{self, WatchEvent},
+};
+
+/// A writer whose contents stay readable after the engine took ownership of it.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn an_edit_runs_the_files_again() {
+    let dir = env::temp_dir().join(format!("rlox-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("main.lox");
+    fs::write(&path, "var a = 1;\nprint a;\n").unwrap();
+    let paths = [path.to_str().unwrap().to_string()];
+
+    let (sender, receiver) = mpsc::channel();
+    let output = SharedBuffer::default();
+    let mut runs = vec![];
+    watch::run_on_changes(&receiver, |run| {
+        watch::rerun(
+            &paths,
+            InterpreterOptions::default().writer(output.clone()),
+            false,
+        );
+        runs.push((run, output.take()));
+        if run == 0 {
+            // an editor saving in two writes causes a single run
+            fs::write(&path, "var b = 2;\n").unwrap();
+            fs::write(&path, "var b = 2;\nprint b;\n").unwrap();
+            sender.send(WatchEvent::Changed).unwrap();
+            sender.send(WatchEvent::Changed).unwrap();
+        } else {
+            sender.send(WatchEvent::Stop).unwrap();
+        }
+    });
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(runs, [(0, "1\n".to_string()), (1, "2\n".to_string())]);
+}
+
+#[test]
+fn nothing_runs_again_without_a_change() {
+    let (sender, receiver) = mpsc::channel();
+    let mut runs = 0;
+    watch::run_on_changes(&receiver, |_| {
+        runs += 1;
+        sender.send(WatchEvent::Stop).unwrap();
+    });
+    assert_eq!(runs, 1);
+}